@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::span::Span;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -17,18 +19,55 @@ pub enum Stmt {
         name: String,
         name_span: Span,
         mutable: bool,
+        /// Optional annotation: `let n: Int = ...`
+        ty: Option<TypeAnn>,
         expr: Expr,
         span: Span,
     },
+    /// `let [a, b] = expr` or `let Ok(x) = expr else fallback`.
+    /// When the pattern does not match, its one bound name takes `fallback`'s value.
+    LetPattern {
+        pattern: Pattern,
+        expr: Expr,
+        else_body: Option<Expr>,
+        span: Span,
+    },
     Set {
         name: String,
         name_span: Span,
         expr: Expr,
         span: Span,
     },
+    /// `import "lib/deploy.myl" as deploy`; the path is relative to the importing file.
+    Import {
+        path: String,
+        path_span: Span,
+        alias: String,
+        alias_span: Span,
+        span: Span,
+    },
+    /// `export release, rollback`
+    Export {
+        names: Vec<(String, Span)>,
+        span: Span,
+    },
+    /// `assert code == 0` or `assert code == 0, "build failed"`
+    Assert {
+        expr: Expr,
+        message: Option<Expr>,
+        span: Span,
+    },
     Expr(Expr),
 }
 
+/// Type annotation as written in source, e.g. `Result[String, Record]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAnn {
+    pub name: String,
+    pub args: Vec<TypeAnn>,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Pattern {
     Wildcard(Span),
@@ -38,9 +77,39 @@ pub enum Pattern {
     List(Vec<Pattern>, Span),
     Ok(Box<Pattern>, Span),
     Err(Box<Pattern>, Span),
+    /// `1..10` or `0..`; matches integers in the range.
+    Range {
+        start: i64,
+        end: Option<i64>,
+        span: Span,
+    },
 }
 
 impl Pattern {
+    /// Whether the pattern can fail on a value of the expected shape.
+    /// List patterns are treated as shape-checked, not refutable.
+    pub fn is_refutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard(_) | Pattern::Var(_, _) => false,
+            Pattern::Int(_, _) | Pattern::String(_, _) | Pattern::Range { .. } => true,
+            Pattern::Ok(_, _) | Pattern::Err(_, _) => true,
+            Pattern::List(items, _) => items.iter().any(Pattern::is_refutable),
+        }
+    }
+
+    /// Names the pattern binds, in source order.
+    pub fn bound_names(&self) -> Vec<&str> {
+        match self {
+            Pattern::Var(name, _) => vec![name.as_str()],
+            Pattern::List(items, _) => items.iter().flat_map(Pattern::bound_names).collect(),
+            Pattern::Ok(inner, _) | Pattern::Err(inner, _) => inner.bound_names(),
+            Pattern::Wildcard(_)
+            | Pattern::Int(_, _)
+            | Pattern::String(_, _)
+            | Pattern::Range { .. } => Vec::new(),
+        }
+    }
+
     pub fn span(&self) -> Span {
         match self {
             Pattern::Wildcard(s) => *s,
@@ -50,14 +119,40 @@ impl Pattern {
             Pattern::List(_, s) => *s,
             Pattern::Ok(_, s) => *s,
             Pattern::Err(_, s) => *s,
+            Pattern::Range { span, .. } => *span,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinOp {
     Eq,
     NotEq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+}
+
+impl BinOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Eq => "==",
+            BinOp::NotEq => "!=",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Gt => ">",
+            BinOp::Ge => ">=",
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+        }
+    }
+
+    pub fn is_arithmetic(self) -> bool {
+        matches!(self, BinOp::Add | BinOp::Sub)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -71,12 +166,22 @@ pub struct MatchArm {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     String(String, Span),
+    /// `p"./logs"`
+    Path(String, Span),
+    Bool(bool, Span),
     Int(i64, Span),
+    /// `30s`, `250ms`
+    Duration(Duration, Span),
+    /// `10MiB`, `2GB`; a byte count
+    Size(u64, Span),
     Var(String, Span),
     List(Vec<Expr>, Span),
+    /// `fn x -> expr end` or `fn (x: T): R -> expr end`
     Fn {
         param: String,
         param_span: Span,
+        param_ty: Option<TypeAnn>,
+        ret_ty: Option<TypeAnn>,
         body: Box<Expr>,
         span: Span,
     },
@@ -85,16 +190,19 @@ pub enum Expr {
         arms: Vec<MatchArm>,
         span: Span,
     },
-    /// `io do expr end` — side-effect boundary
+    /// `io do expr end` — side-effect boundary. `io do expr ensure cleanup end`
+    /// runs `cleanup` after `expr` even when it fails.
     Io {
         body: Box<Expr>,
+        ensure: Option<Box<Expr>>,
         span: Span,
     },
-    /// Built-in function call: `name(arg1, arg2, ...)`
+    /// Built-in function call: `name(arg1, arg2, ..., opt: value)`
     Call {
         name: String,
         name_span: Span,
         args: Vec<Expr>,
+        named: Vec<NamedArg>,
         span: Span,
     },
     /// `with pat <- expr, ... do body else fallback end`
@@ -110,8 +218,72 @@ pub enum Expr {
         rhs: Box<Expr>,
         span: Span,
     },
+    /// `-operand`; a negative integer literal is an `Int` instead.
+    Neg {
+        operand: Box<Expr>,
+        span: Span,
+    },
     /// Left-associative pipeline: `a |> b |> c` is `((a |> b) |> c)`.
     Pipe(Box<Expr>, Box<Expr>, Span),
+    /// `start..end` or `start..`; evaluates to a lazy range value.
+    Range {
+        start: Box<Expr>,
+        end: Option<Box<Expr>>,
+        span: Span,
+    },
+    /// `target.name`: record field or qualified module access.
+    Field {
+        target: Box<Expr>,
+        name: String,
+        name_span: Span,
+        span: Span,
+    },
+    /// `value.name(args)`: sugar for `name(value, args)`, or a call of the
+    /// exported function `name` when `value` is a module alias.
+    MethodCall {
+        receiver: Box<Expr>,
+        name: String,
+        name_span: Span,
+        args: Vec<Expr>,
+        named: Vec<NamedArg>,
+        span: Span,
+    },
+    /// `xs[i]`; a negative index counts from the end.
+    Index {
+        target: Box<Expr>,
+        index: Box<Expr>,
+        span: Span,
+    },
+    /// `xs[a..b]`, `xs[a..]` or `xs[..b]`; the end is exclusive.
+    Slice {
+        target: Box<Expr>,
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        span: Span,
+    },
+    /// `for x in xs when cond do items... end`; yields the last item of each
+    /// iteration as a list.
+    For {
+        var: String,
+        var_span: Span,
+        iter: Box<Expr>,
+        guard: Option<Box<Expr>>,
+        body: Vec<Expr>,
+        span: Span,
+    },
+    /// `try do body rescue e -> handler end`; without `rescue` it yields a Result.
+    Try {
+        body: Box<Expr>,
+        rescue: Option<Box<RescueClause>>,
+        span: Span,
+    },
+    /// `set name = expr` as a `for` body item; evaluates to the new value.
+    Set {
+        name: String,
+        name_span: Span,
+        expr: Box<Expr>,
+        span: Span,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -121,20 +293,51 @@ pub struct WithBinding {
     pub span: Span,
 }
 
+/// `name: value` in a call's argument list; always after positional arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArg {
+    pub name: String,
+    pub name_span: Span,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// `rescue e -> handler`; `e` is bound to the error record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RescueClause {
+    pub name: String,
+    pub name_span: Span,
+    pub body: Expr,
+    pub span: Span,
+}
+
 impl Expr {
     pub fn span(&self) -> Span {
         match self {
-            Expr::String(_, s) => s.clone(),
-            Expr::Int(_, s) => s.clone(),
-            Expr::Var(_, s) => s.clone(),
-            Expr::List(_, s) => s.clone(),
+            Expr::String(_, s) => *s,
+            Expr::Path(_, s) => *s,
+            Expr::Bool(_, s) => *s,
+            Expr::Int(_, s) => *s,
+            Expr::Duration(_, s) => *s,
+            Expr::Size(_, s) => *s,
+            Expr::Var(_, s) => *s,
+            Expr::List(_, s) => *s,
             Expr::Fn { span, .. } => *span,
             Expr::Match { span, .. } => *span,
             Expr::Io { span, .. } => *span,
             Expr::Call { span, .. } => *span,
             Expr::With { span, .. } => *span,
             Expr::BinOp { span, .. } => *span,
-            Expr::Pipe(_, _, s) => s.clone(),
+            Expr::Neg { span, .. } => *span,
+            Expr::Pipe(_, _, s) => *s,
+            Expr::Range { span, .. } => *span,
+            Expr::Field { span, .. } => *span,
+            Expr::MethodCall { span, .. } => *span,
+            Expr::Index { span, .. } => *span,
+            Expr::Slice { span, .. } => *span,
+            Expr::For { span, .. } => *span,
+            Expr::Set { span, .. } => *span,
+            Expr::Try { span, .. } => *span,
         }
     }
 }
@@ -142,7 +345,12 @@ impl Expr {
 impl Stmt {
     pub fn span(&self) -> Span {
         match self {
-            Stmt::Let { span, .. } | Stmt::Set { span, .. } => span.clone(),
+            Stmt::Let { span, .. }
+            | Stmt::LetPattern { span, .. }
+            | Stmt::Set { span, .. }
+            | Stmt::Import { span, .. }
+            | Stmt::Export { span, .. }
+            | Stmt::Assert { span, .. } => *span,
             Stmt::Expr(expr) => expr.span(),
         }
     }
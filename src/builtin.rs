@@ -1,5 +1,10 @@
+use std::path::{Component, Path, PathBuf};
+
 use miette::{IntoDiagnostic, Result};
 
+use crate::error::BuiltinArityError;
+use crate::span::Span;
+
 pub fn glob(pattern: &str) -> Result<Vec<String>> {
     let mut out = Vec::new();
     for entry in glob::glob(pattern).into_diagnostic()? {
@@ -8,3 +13,258 @@ pub fn glob(pattern: &str) -> Result<Vec<String>> {
     }
     Ok(out)
 }
+
+/// Resolves `.` and `..` lexically, without touching the filesystem.
+/// Leading `..` is kept for relative paths and dropped at the root.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => out.push(".."),
+            },
+            other => out.push(other),
+        }
+    }
+    if out.as_os_str().is_empty() {
+        out.push(".");
+    }
+    out
+}
+
+/// Why [`join_path`] refused a segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinRejection {
+    /// An absolute segment would replace the base entirely.
+    Absolute,
+    /// The segment climbs above the base with `..`.
+    Escapes,
+}
+
+/// Joins `segment` under `base`, refusing segments that could leave it.
+pub fn join_path(base: &Path, segment: &Path) -> std::result::Result<PathBuf, JoinRejection> {
+    if segment.has_root() {
+        return Err(JoinRejection::Absolute);
+    }
+    if normalize_path(segment).starts_with("..") {
+        return Err(JoinRejection::Escapes);
+    }
+    Ok(base.join(segment))
+}
+
+/// Value used for an optional named parameter the caller left out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamDefault {
+    Null,
+    Str(&'static str),
+}
+
+/// Optional named parameter, e.g. `cwd: "/srv"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NamedParam {
+    pub name: &'static str,
+    pub default: ParamDefault,
+}
+
+/// Declared parameters of a builtin; checked by the resolver before evaluation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Signature {
+    pub name: &'static str,
+    /// Required positional parameters, in order.
+    pub positional: &'static [&'static str],
+    /// Accepts any number of further positional arguments.
+    pub variadic: bool,
+    pub named: &'static [NamedParam],
+    /// Only callable inside `io do ... end`.
+    pub io: bool,
+    /// Applies its function arguments.
+    pub higher_order: bool,
+    /// Pulls items from a seq argument, running its `map` stages.
+    pub forces: bool,
+    /// Position of a regex pattern argument, checked before running when it
+    /// is a string literal.
+    pub literal_regex_arg: Option<usize>,
+}
+
+impl Signature {
+    const fn pure(name: &'static str, positional: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            positional,
+            variadic: false,
+            named: &[],
+            io: false,
+            higher_order: false,
+            forces: false,
+            literal_regex_arg: None,
+        }
+    }
+
+    pub fn named_param(&self, name: &str) -> Option<&'static NamedParam> {
+        self.named.iter().find(|param| param.name == name)
+    }
+
+    /// Human-readable parameter list, e.g. `run_text(program, args..., cwd:, env:)`.
+    pub fn display(&self) -> String {
+        let mut params: Vec<String> = self.positional.iter().map(|p| p.to_string()).collect();
+        if self.variadic {
+            params.push("args...".to_string());
+        }
+        params.extend(self.named.iter().map(|param| format!("{}:", param.name)));
+        format!("{}({})", self.name, params.join(", "))
+    }
+
+    /// Reports a call at `span` that passed `found` positional arguments.
+    pub fn arity_error(&self, found: usize, span: Span) -> BuiltinArityError {
+        let required = self.positional.len();
+        BuiltinArityError {
+            callee: self.name.to_string(),
+            expected: if self.variadic {
+                format!("at least {required}")
+            } else {
+                required.to_string()
+            },
+            found,
+            signature: self.display(),
+            span,
+        }
+    }
+}
+
+/// Options shared by `from_csv` and `to_csv`.
+const CSV_PARAMS: &[NamedParam] = &[
+    NamedParam {
+        name: "delimiter",
+        default: ParamDefault::Str(","),
+    },
+    NamedParam {
+        name: "quote",
+        default: ParamDefault::Str("\""),
+    },
+    NamedParam {
+        name: "header",
+        default: ParamDefault::Null,
+    },
+    NamedParam {
+        name: "types",
+        default: ParamDefault::Null,
+    },
+];
+
+pub const BUILTINS: &[Signature] = &[
+    Signature::pure("ok", &["value"]),
+    Signature::pure("err", &["value"]),
+    Signature::pure("is_ok", &["result"]),
+    Signature::pure("is_err", &["result"]),
+    Signature::pure("get", &["xs", "index"]),
+    Signature {
+        higher_order: true,
+        ..Signature::pure("map", &["xs", "f"])
+    },
+    Signature {
+        forces: true,
+        ..Signature::pure("take", &["xs", "n"])
+    },
+    Signature {
+        forces: true,
+        ..Signature::pure("to_list", &["xs"])
+    },
+    Signature::pure("path", &["text"]),
+    Signature::pure("join", &["base", "segment"]),
+    Signature::pure("parent", &["path"]),
+    Signature::pure("file_name", &["path"]),
+    Signature::pure("extension", &["path"]),
+    Signature::pure("normalize", &["path"]),
+    Signature::pure("lines", &["text"]),
+    Signature::pure("split", &["text", "sep"]),
+    Signature::pure("words", &["text"]),
+    Signature::pure("parse_kv", &["text"]),
+    Signature::pure("parse_logfmt", &["text"]),
+    Signature::pure("parse", &["template", "text"]),
+    Signature {
+        literal_regex_arg: Some(1),
+        ..Signature::pure("regex_match", &["text", "pattern"])
+    },
+    Signature {
+        literal_regex_arg: Some(1),
+        ..Signature::pure("regex_find_all", &["text", "pattern"])
+    },
+    Signature {
+        literal_regex_arg: Some(1),
+        ..Signature::pure("regex_replace", &["text", "pattern", "replacement"])
+    },
+    Signature {
+        literal_regex_arg: Some(1),
+        ..Signature::pure("regex_split", &["text", "pattern"])
+    },
+    Signature::pure("from_json", &["text"]),
+    Signature::pure("from_toml", &["text"]),
+    Signature::pure("to_toml", &["record"]),
+    Signature::pure("from_ini", &["text"]),
+    Signature {
+        named: CSV_PARAMS,
+        ..Signature::pure("from_csv", &["text"])
+    },
+    Signature {
+        named: CSV_PARAMS,
+        forces: true,
+        ..Signature::pure("to_csv", &["rows"])
+    },
+    Signature {
+        named: &[NamedParam {
+            name: "pretty",
+            default: ParamDefault::Null,
+        }],
+        ..Signature::pure("to_json", &["value"])
+    },
+    Signature {
+        named: &[
+            NamedParam {
+                name: "columns",
+                default: ParamDefault::Null,
+            },
+            NamedParam {
+                name: "width",
+                default: ParamDefault::Null,
+            },
+        ],
+        forces: true,
+        ..Signature::pure("table", &["rows"])
+    },
+    Signature {
+        name: "run_text",
+        positional: &["program"],
+        variadic: true,
+        named: &[
+            NamedParam {
+                name: "cwd",
+                default: ParamDefault::Null,
+            },
+            NamedParam {
+                name: "env",
+                default: ParamDefault::Null,
+            },
+            NamedParam {
+                name: "stdin",
+                default: ParamDefault::Null,
+            },
+            NamedParam {
+                name: "timeout",
+                default: ParamDefault::Null,
+            },
+        ],
+        io: true,
+        higher_order: false,
+        forces: false,
+        literal_regex_arg: None,
+    },
+];
+
+pub fn signature(name: &str) -> Option<&'static Signature> {
+    BUILTINS.iter().find(|sig| sig.name == name)
+}
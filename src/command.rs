@@ -1,27 +1,81 @@
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Program and arguments are kept as `OsString`s so paths reach the child
+/// process byte-for-byte, without a lossy UTF-8 round trip.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandSpec {
-    program: String,
-    args: Vec<String>,
+    program: OsString,
+    args: Vec<OsString>,
+    cwd: Option<PathBuf>,
+    env: Vec<(String, String)>,
+    stdin: Option<String>,
+    timeout: Option<Duration>,
 }
 
 impl CommandSpec {
-    pub fn new(program: impl Into<String>, args: impl Into<Vec<String>>) -> Self {
+    pub fn new<A: Into<OsString>>(
+        program: impl Into<OsString>,
+        args: impl IntoIterator<Item = A>,
+    ) -> Self {
         Self {
             program: program.into(),
-            args: args.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            cwd: None,
+            env: Vec::new(),
+            stdin: None,
+            timeout: None,
         }
     }
 
-    pub fn program(&self) -> &str {
+    pub fn program(&self) -> &OsStr {
         &self.program
     }
 
-    pub fn args(&self) -> &[String] {
+    pub fn args(&self) -> &[OsString] {
         &self.args
     }
 
-    pub fn with_arg(mut self, arg: impl Into<String>) -> Self {
+    pub fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    /// Variables added to the inherited environment.
+    pub fn env(&self) -> &[(String, String)] {
+        &self.env
+    }
+
+    pub fn stdin(&self) -> Option<&str> {
+        self.stdin.as_deref()
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn with_arg(mut self, arg: impl Into<OsString>) -> Self {
         self.args.push(arg.into());
         self
     }
+
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn with_stdin(mut self, stdin: impl Into<String>) -> Self {
+        self.stdin = Some(stdin.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use crate::value::Value;
+
+/// How `from_csv` / `to_csv` read and write delimited text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub quote: char,
+    /// The first row names the columns, and each later row becomes a record.
+    pub header: bool,
+    /// Convert columns whose cells all look like ints, floats or bools.
+    pub types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            header: true,
+            types: true,
+        }
+    }
+}
+
+/// A malformed row; `row` counts records from 1 (the header included) and
+/// `line` is the physical line the problem is on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvError {
+    pub message: String,
+    pub row: usize,
+    pub line: usize,
+}
+
+impl CsvError {
+    /// The `{message, row, line}` record `from_csv` returns inside `Err`.
+    pub fn into_record(self) -> Value {
+        let mut fields = BTreeMap::new();
+        fields.insert("message".to_string(), Value::String(self.message));
+        fields.insert("row".to_string(), Value::Int(self.row as i64));
+        fields.insert("line".to_string(), Value::Int(self.line as i64));
+        Value::Record(fields)
+    }
+}
+
+/// Reads delimited text into a list of records (or of lists without a
+/// header). Blank lines are skipped.
+pub fn parse(text: &str, options: &CsvOptions) -> Result<Value, CsvError> {
+    let rows = read_rows(text, options)?;
+    let (columns, body) = match (options.header, rows.split_first()) {
+        (true, Some((header, body))) => (Some(column_names(&header.cells, header.line)?), body),
+        (true, None) => return Ok(Value::List(Vec::new())),
+        (false, _) => (None, &rows[..]),
+    };
+    let width = columns
+        .as_ref()
+        .map_or_else(|| body.first().map_or(0, |row| row.cells.len()), Vec::len);
+    for (i, row) in body.iter().enumerate() {
+        if row.cells.len() != width {
+            return Err(CsvError {
+                message: format!("expected {width} fields, found {}", row.cells.len()),
+                row: i + 1 + usize::from(options.header),
+                line: row.line,
+            });
+        }
+    }
+
+    let mut cells: Vec<Vec<Value>> = body
+        .iter()
+        .map(|row| row.cells.iter().cloned().map(Value::String).collect())
+        .collect();
+    if options.types {
+        for column in 0..width {
+            coerce_column(&mut cells, column);
+        }
+    }
+    Ok(Value::List(match columns {
+        Some(columns) => cells
+            .into_iter()
+            .map(|row| Value::Record(columns.iter().cloned().zip(row).collect()))
+            .collect(),
+        None => cells.into_iter().map(Value::List).collect(),
+    }))
+}
+
+struct Row {
+    cells: Vec<String>,
+    /// Line the row starts on, from 1.
+    line: usize,
+}
+
+fn read_rows(text: &str, options: &CsvOptions) -> Result<Vec<Row>, CsvError> {
+    let mut rows = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut cells = Vec::new();
+        let mut cell = String::new();
+        let mut quoted = false;
+        loop {
+            match chars.next() {
+                None | Some('\n') => {
+                    cells.push(std::mem::take(&mut cell));
+                    line += 1;
+                    break;
+                }
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some(c) if c == options.delimiter => {
+                    cells.push(std::mem::take(&mut cell));
+                    quoted = false;
+                }
+                Some(c) if c == options.quote && cell.is_empty() && !quoted => {
+                    quoted = true;
+                    let open_line = line;
+                    loop {
+                        match chars.next() {
+                            None => {
+                                return Err(CsvError {
+                                    message: "unterminated quoted field".to_string(),
+                                    row: rows.len() + 1,
+                                    line: open_line,
+                                });
+                            }
+                            Some(c) if c == options.quote => {
+                                if chars.peek() == Some(&options.quote) {
+                                    chars.next();
+                                    cell.push(c);
+                                } else {
+                                    break;
+                                }
+                            }
+                            Some(c) => {
+                                if c == '\n' {
+                                    line += 1;
+                                }
+                                cell.push(c);
+                            }
+                        }
+                    }
+                    if !matches!(chars.peek(), None | Some('\n' | '\r'))
+                        && chars.peek() != Some(&options.delimiter)
+                    {
+                        return Err(CsvError {
+                            message: format!("unexpected text after closing {}", options.quote),
+                            row: rows.len() + 1,
+                            line,
+                        });
+                    }
+                }
+                Some(c) if c == options.quote || quoted => {
+                    return Err(CsvError {
+                        message: format!("{} inside an unquoted field", options.quote),
+                        row: rows.len() + 1,
+                        line,
+                    });
+                }
+                Some(c) => cell.push(c),
+            }
+        }
+        // A blank line is no row, but a lone `""` is a row with one empty cell.
+        if !(cells.len() == 1 && cells[0].is_empty() && !quoted) {
+            rows.push(Row {
+                cells,
+                line: start_line,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+fn column_names(header: &[String], line: usize) -> Result<Vec<String>, CsvError> {
+    let mut names: Vec<String> = Vec::with_capacity(header.len());
+    for (i, name) in header.iter().enumerate() {
+        let name = match name.trim() {
+            "" => format!("column{}", i + 1),
+            name => name.to_string(),
+        };
+        if names.contains(&name) {
+            return Err(CsvError {
+                message: format!("duplicate column `{name}`"),
+                row: 1,
+                line,
+            });
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Turns a column into ints, floats or bools when every non-empty cell reads
+/// as one; empty cells in a converted column become `null`.
+fn coerce_column(rows: &mut [Vec<Value>], column: usize) {
+    let texts: Vec<&str> = rows
+        .iter()
+        .filter_map(|row| match &row[column] {
+            Value::String(s) if !s.is_empty() => Some(s.as_str()),
+            _ => None,
+        })
+        .collect();
+    if texts.is_empty() {
+        return;
+    }
+    // Leading zeros (ids, zip codes) keep a column textual.
+    let padded = |s: &str| {
+        let digits = s.trim_start_matches(['-', '+']).as_bytes();
+        digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+    };
+    let int = |s: &str| s.parse::<i64>().ok().filter(|n| n.to_string() == s);
+    let float = |s: &str| {
+        s.parse::<f64>()
+            .ok()
+            .filter(|x| x.is_finite() && s.contains(|c: char| c.is_ascii_digit()) && !padded(s))
+    };
+    let bool = |s: &str| match s {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    };
+    let convert: fn(&str) -> Value = if texts.iter().all(|s| int(s).is_some()) {
+        |s| Value::Int(s.parse().expect("checked int"))
+    } else if texts.iter().all(|s| float(s).is_some()) {
+        |s| Value::Float(s.parse().expect("checked float"))
+    } else if texts.iter().all(|s| bool(s).is_some()) {
+        |s| Value::Bool(s == "true")
+    } else {
+        return;
+    };
+    for row in rows {
+        if let Value::String(s) = &row[column] {
+            row[column] = if s.is_empty() {
+                Value::Null
+            } else {
+                convert(s)
+            };
+        }
+    }
+}
+
+/// Writes rows as delimited text, one line each. Records share a header made
+/// of every field name; lists are written without one. Fails with the type
+/// name of the first row or cell that cannot be written.
+pub fn to_csv(rows: &[Value], options: &CsvOptions) -> Result<String, &'static str> {
+    let mut out = String::new();
+    if rows.iter().all(|row| matches!(row, Value::Record(_))) && !rows.is_empty() {
+        let mut columns: Vec<&String> = Vec::new();
+        for row in rows {
+            if let Value::Record(fields) = row {
+                for key in fields.keys() {
+                    if !columns.contains(&key) {
+                        columns.push(key);
+                    }
+                }
+            }
+        }
+        columns.sort();
+        let header: Vec<String> = columns.iter().map(|c| quote(c, options)).collect();
+        push_line(&mut out, &header, options);
+        for row in rows {
+            let Value::Record(fields) = row else {
+                unreachable!("all rows are records")
+            };
+            let cells = columns
+                .iter()
+                .map(|column| cell(fields.get(*column).unwrap_or(&Value::Null), options))
+                .collect::<Result<Vec<_>, _>>()?;
+            push_line(&mut out, &cells, options);
+        }
+        return Ok(out);
+    }
+    for row in rows {
+        let Value::List(items) = row else {
+            return Err(row.type_name());
+        };
+        let cells = items
+            .iter()
+            .map(|item| cell(item, options))
+            .collect::<Result<Vec<_>, _>>()?;
+        push_line(&mut out, &cells, options);
+    }
+    Ok(out)
+}
+
+fn push_line(out: &mut String, cells: &[String], options: &CsvOptions) {
+    out.push_str(&cells.join(&options.delimiter.to_string()));
+    out.push('\n');
+}
+
+fn cell(value: &Value, options: &CsvOptions) -> Result<String, &'static str> {
+    Ok(match value {
+        Value::Null => String::new(),
+        Value::String(s) => quote(s, options),
+        Value::Path(p) => quote(&p.to_string_lossy(), options),
+        Value::DateTime(text) => quote(text, options),
+        // A plain byte count, as `to_json` writes it, so the column reads back as ints.
+        Value::ByteSize(n) => n.to_string(),
+        Value::Bool(_) | Value::Int(_) | Value::Float(_) | Value::Duration(_) => {
+            quote(&value.to_string(), options)
+        }
+        other => return Err(other.type_name()),
+    })
+}
+
+/// Quotes a field that holds the delimiter, the quote or a line break.
+fn quote(text: &str, options: &CsvOptions) -> String {
+    let needs_quotes = text
+        .chars()
+        .any(|c| c == options.delimiter || c == options.quote || c == '\n' || c == '\r');
+    if !needs_quotes {
+        return text.to_string();
+    }
+    let q = options.quote;
+    let escaped = text.replace(q, &format!("{q}{q}"));
+    format!("{q}{escaped}{q}")
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::Expr;
+use crate::resolver::Binding;
+use crate::span::Span;
+
+/// One hop in a side-effect call chain: the callee and where it was called.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectStep {
+    pub name: String,
+    pub span: Span,
+}
+
+/// Side effect of evaluating an expression, or of applying a function value.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Effect {
+    #[default]
+    Pure,
+    /// Performs io. The chain runs from the outermost call down to the io builtin,
+    /// or is empty for a function the checker could not follow or one from an
+    /// imported module; applying it adds the call as the first step.
+    Io(Vec<EffectStep>),
+}
+
+impl Effect {
+    pub fn io(name: impl Into<String>, span: Span) -> Self {
+        Effect::Io(vec![EffectStep {
+            name: name.into(),
+            span,
+        }])
+    }
+
+    pub fn is_pure(&self) -> bool {
+        matches!(self, Effect::Pure)
+    }
+
+    /// Combines two effects; the first impure chain wins.
+    pub fn join(self, other: Effect) -> Self {
+        match self {
+            Effect::Pure => other,
+            io => io,
+        }
+    }
+
+    /// Prefixes the chain with a call to `name`, e.g. applying a function
+    /// whose latent effect is `self`.
+    pub fn through(self, name: impl Into<String>, span: Span) -> Self {
+        match self {
+            Effect::Pure => Effect::Pure,
+            Effect::Io(chain) => {
+                let mut steps = Vec::with_capacity(chain.len() + 1);
+                steps.push(EffectStep {
+                    name: name.into(),
+                    span,
+                });
+                steps.extend(chain);
+                Effect::Io(steps)
+            }
+        }
+    }
+}
+
+/// What applying a value may do, with one entry per function the value may be
+/// or hold. Empty for values that hold no function.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Latent(Vec<Applied>);
+
+#[derive(Debug, Clone)]
+pub enum Applied {
+    /// A `fn` expression, checked again for each argument it is applied to, so
+    /// its effect follows what the argument does when the body applies it.
+    Closure(Rc<Closure>),
+    /// A function the checker could not follow; applying it is assumed to
+    /// perform io.
+    Unknown,
+}
+
+/// A `fn` expression with the bindings in scope where it was written.
+#[derive(Debug, Clone)]
+pub struct Closure {
+    pub(crate) param: String,
+    pub(crate) param_span: Span,
+    pub(crate) body: Expr,
+    pub(crate) scopes: Vec<HashMap<String, Binding>>,
+    /// Defined in an imported module, whose spans point into that file rather
+    /// than the importer's; applying it reports only the call, not its chain.
+    pub(crate) imported: bool,
+}
+
+/// Closures are the same when they come from the same check of a `fn`.
+impl PartialEq for Applied {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Applied::Closure(a), Applied::Closure(b)) => Rc::ptr_eq(a, b),
+            (Applied::Unknown, Applied::Unknown) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Latent {
+    pub fn closure(closure: Closure) -> Self {
+        Latent(vec![Applied::Closure(Rc::new(closure))])
+    }
+
+    pub fn unknown() -> Self {
+        Latent(vec![Applied::Unknown])
+    }
+
+    /// The functions either value may be.
+    pub fn join(mut self, other: Latent) -> Self {
+        for applied in other.0 {
+            if !self.0.contains(&applied) {
+                self.0.push(applied);
+            }
+        }
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Applied> {
+        self.0.iter()
+    }
+
+    /// The same functions as seen from a module that imports them.
+    pub fn imported(&self) -> Self {
+        Latent(
+            self.0
+                .iter()
+                .map(|applied| match applied {
+                    Applied::Closure(closure) if !closure.imported => {
+                        Applied::Closure(Rc::new(Closure {
+                            imported: true,
+                            ..Closure::clone(closure)
+                        }))
+                    }
+                    other => other.clone(),
+                })
+                .collect(),
+        )
+    }
+}
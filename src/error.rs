@@ -1,4 +1,4 @@
-use miette::{Diagnostic, SourceSpan};
+use miette::{Diagnostic, LabeledSpan, NamedSource, Report, SourceSpan};
 use thiserror::Error;
 
 #[derive(Debug, Error, Diagnostic)]
@@ -19,6 +19,8 @@ pub struct ParseError {
     pub message: String,
     #[label("parse error here")]
     pub span: SourceSpan,
+    #[help]
+    pub help: Option<String>,
 }
 
 impl ParseError {
@@ -26,8 +28,14 @@ impl ParseError {
         Self {
             message: message.into(),
             span,
+            help: None,
         }
     }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
 }
 
 #[derive(Debug, Error, Diagnostic, Clone)]
@@ -68,6 +76,142 @@ pub struct SetUndefinedError {
     pub span: SourceSpan,
 }
 
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("`set` is not allowed here")]
+#[diagnostic(
+    code(mictylish::set_outside_loop),
+    help("`set` may appear as a statement or directly in the body of a statement-level `for`")
+)]
+pub struct SetOutsideLoopError {
+    #[label("set here")]
+    pub span: SourceSpan,
+}
+
+/// Reported while resolving when the pattern is a string literal, and during
+/// evaluation otherwise.
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("invalid regular expression: {message}")]
+#[diagnostic(code(mictylish::invalid_regex))]
+pub struct InvalidRegexError {
+    pub message: String,
+    #[label("this pattern")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("refutable pattern in `let` requires an `else` branch")]
+#[diagnostic(
+    code(mictylish::refutable_let),
+    help("add `else <fallback>`, or use `match` to handle the other cases")
+)]
+pub struct RefutableLetError {
+    #[label("this pattern may not match")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("`let ... else` needs a pattern that binds exactly one name, found {found}")]
+#[diagnostic(
+    code(mictylish::let_else_binding),
+    help("the `else` value becomes that name's value; use `match` to bind several names")
+)]
+pub struct LetElseBindingError {
+    pub found: usize,
+    #[label("this pattern")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("side-effect function '{name}' can only be called inside `io do ... end`")]
+#[diagnostic(
+    code(mictylish::io_outside_boundary),
+    help("call chain: {chain}; wrap the call in `io do ... end` to allow side effects")
+)]
+pub struct IoOutsideBoundaryError {
+    pub name: String,
+    /// Rendered call chain, e.g. `deploy -> get -> run_text`.
+    pub chain: String,
+    #[label("called here")]
+    pub span: SourceSpan,
+    #[label(collection)]
+    pub via: Vec<LabeledSpan>,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("module '{path}' has not been loaded")]
+#[diagnostic(
+    code(mictylish::unknown_module),
+    help("modules are loaded by `ModuleLoader::link` before name resolution")
+)]
+pub struct UnknownModuleError {
+    pub path: String,
+    #[label("imported here")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("module '{module}' does not export '{name}'")]
+#[diagnostic(
+    code(mictylish::not_exported),
+    help("add `export {name}` to the module to make it visible")
+)]
+pub struct NotExportedError {
+    pub module: String,
+    pub name: String,
+    #[label("not exported")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("`{callee}` has no parameter named '{name}'")]
+#[diagnostic(code(mictylish::unknown_named_arg), help("{hint}"))]
+pub struct UnknownNamedArgError {
+    pub callee: String,
+    pub name: String,
+    pub hint: String,
+    #[label("unknown parameter")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("named argument '{name}' given more than once")]
+#[diagnostic(code(mictylish::duplicate_named_arg))]
+pub struct DuplicateNamedArgError {
+    pub name: String,
+    #[label("first given here")]
+    pub first: SourceSpan,
+    #[label("given again here")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("`{callee}` expects {expected} positional argument(s), found {found}")]
+#[diagnostic(code(mictylish::builtin_arity), help("signature: {signature}"))]
+pub struct BuiltinArityError {
+    pub callee: String,
+    pub expected: String,
+    pub found: usize,
+    pub signature: String,
+    #[label("called here")]
+    pub span: SourceSpan,
+}
+
+/// Not an error: reported when `value.name(...)` has more than one reading.
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("`.{name}(...)` could be the {picked} or the {other}; using the {picked}")]
+#[diagnostic(
+    code(mictylish::ambiguous_method_call),
+    severity(Warning),
+    help("rename one of them to make the call unambiguous")
+)]
+pub struct AmbiguousMethodCallWarning {
+    pub name: String,
+    pub picked: String,
+    pub other: String,
+    #[label("resolved as the {picked}")]
+    pub span: SourceSpan,
+}
+
 #[derive(Debug, Error, Diagnostic)]
 pub enum ResolveError {
     #[error(transparent)]
@@ -85,6 +229,157 @@ pub enum ResolveError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     SetUndefined(#[from] SetUndefinedError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SetOutsideLoop(#[from] SetOutsideLoopError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    IoOutsideBoundary(#[from] IoOutsideBoundaryError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    RefutableLet(#[from] RefutableLetError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnknownModule(#[from] UnknownModuleError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NotExported(#[from] NotExportedError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnknownNamedArg(#[from] UnknownNamedArgError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DuplicateNamedArg(#[from] DuplicateNamedArgError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    BuiltinArity(#[from] BuiltinArityError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidRegex(#[from] InvalidRegexError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    LetElseBinding(#[from] LetElseBindingError),
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("cannot read module '{path}': {reason}")]
+#[diagnostic(code(mictylish::module_not_found))]
+pub struct ModuleNotFoundError {
+    pub path: String,
+    pub reason: String,
+    #[label("imported here")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("import cycle: {chain}")]
+#[diagnostic(code(mictylish::import_cycle))]
+pub struct ImportCycleError {
+    pub chain: String,
+    #[label("this import closes the cycle")]
+    pub span: SourceSpan,
+}
+
+/// Failure inside an imported file. The inner diagnostic carries that file's source.
+#[derive(Debug, Error, Diagnostic)]
+#[error("error in module '{path}'")]
+#[diagnostic(code(mictylish::module_error))]
+pub struct ModuleFileError {
+    pub path: String,
+    #[label("imported here")]
+    pub span: SourceSpan,
+    #[related]
+    pub errors: Vec<Report>,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum ModuleError {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NotFound(#[from] ModuleNotFoundError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Cycle(#[from] ImportCycleError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InFile(#[from] ModuleFileError),
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("type mismatch: expected `{expected}`, found `{found}`")]
+#[diagnostic(code(mictylish::type_mismatch))]
+pub struct TypeMismatchError {
+    pub expected: String,
+    pub found: String,
+    #[label("expected `{expected}` because of this annotation")]
+    pub annotation: SourceSpan,
+    #[label("this has type `{found}`")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("unknown type '{name}'")]
+#[diagnostic(
+    code(mictylish::unknown_type),
+    help("known types: Any, Null, Bool, Int, Float, String, Path, Bytes, Duration, ByteSize, Record, List[T], Result[T, E], Fn[A, B]")
+)]
+pub struct UnknownTypeError {
+    pub name: String,
+    #[label("here")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("type '{name}' expects {expected} type argument(s), found {found}")]
+#[diagnostic(code(mictylish::type_arity))]
+pub struct TypeArityError {
+    pub name: String,
+    pub expected: usize,
+    pub found: usize,
+    #[label("here")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("operator `{op}` cannot combine `{left}` and `{right}`")]
+#[diagnostic(
+    code(mictylish::type_operands),
+    help("`+`/`-` need two Ints, Durations or ByteSizes; comparisons need operands of one type")
+)]
+pub struct OperandTypeError {
+    pub op: String,
+    pub left: String,
+    pub right: String,
+    #[label("`{left}` {op} `{right}`")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic, Clone)]
+#[error("operator `-` cannot negate `{found}`")]
+#[diagnostic(code(mictylish::type_negate), help("only an Int can be negated"))]
+pub struct NegateTypeError {
+    pub found: String,
+    #[label("`-` applied to `{found}`")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum TypeError {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Mismatch(#[from] TypeMismatchError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnknownType(#[from] UnknownTypeError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Arity(#[from] TypeArityError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Operands(#[from] OperandTypeError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Negate(#[from] NegateTypeError),
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -121,6 +416,153 @@ pub struct EvalMatchExhaustedError {
     pub span: SourceSpan,
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[error("expected {expected}, found {found}")]
+#[diagnostic(code(mictylish::eval_type))]
+pub struct EvalTypeError {
+    pub expected: String,
+    pub found: String,
+    #[label("this is {found}")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("record has no field '{name}'")]
+#[diagnostic(code(mictylish::eval_no_field))]
+pub struct EvalNoFieldError {
+    pub name: String,
+    #[label("no such field")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("index {index} out of range for length {len}")]
+#[diagnostic(
+    code(mictylish::eval_index_out_of_range),
+    help("valid indices are -{len}..{len}; use `get(xs, i)` to receive a Result instead")
+)]
+pub struct EvalIndexOutOfRangeError {
+    pub index: i64,
+    pub len: usize,
+    #[label("out of range")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("cannot materialize an unbounded range")]
+#[diagnostic(
+    code(mictylish::eval_unbounded_range),
+    help("bound the range (`0..10`) or limit it with `take(xs, n)`")
+)]
+pub struct EvalUnboundedRangeError {
+    #[label("here")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("{reason}")]
+#[diagnostic(
+    code(mictylish::eval_assertion),
+    help("the condition evaluated to false; labels show the values of its parts")
+)]
+pub struct EvalAssertionError {
+    pub reason: String,
+    /// The value of each subexpression of the condition, innermost first.
+    #[label(collection)]
+    pub values: Vec<LabeledSpan>,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("operator `{op}` cannot combine {left} and {right}")]
+#[diagnostic(code(mictylish::eval_operands))]
+pub struct EvalOperandError {
+    pub op: String,
+    pub left: String,
+    pub right: String,
+    #[label("{left} {op} {right}")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("`{op}` {reason}")]
+#[diagnostic(code(mictylish::eval_arithmetic))]
+pub struct EvalArithmeticError {
+    pub op: String,
+    pub reason: String,
+    #[label("here")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("cannot join path segment '{segment}': {reason}")]
+#[diagnostic(
+    code(mictylish::eval_path_join),
+    help("join only relative segments that stay inside the base path")
+)]
+pub struct EvalPathJoinError {
+    pub segment: String,
+    pub reason: String,
+    #[label("rejected segment")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("{message}")]
+#[diagnostic(code(mictylish::eval_table_option))]
+pub struct EvalTableOptionError {
+    pub message: String,
+    #[label("this option")]
+    pub span: SourceSpan,
+    #[help]
+    pub help: Option<String>,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("{message}")]
+#[diagnostic(
+    code(mictylish::eval_csv_option),
+    help("the delimiter and the quote must differ, and neither may be a line break")
+)]
+pub struct EvalCsvOptionError {
+    pub message: String,
+    #[label("this option")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("`split` separator is empty")]
+#[diagnostic(
+    code(mictylish::eval_empty_separator),
+    help("use `words` to split on whitespace or `lines` to split on line breaks")
+)]
+pub struct EvalEmptySeparatorError {
+    #[label("empty separator")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("invalid template: {message}")]
+#[diagnostic(
+    code(mictylish::eval_template),
+    help(
+        "placeholders look like `{{name}}` or `{{name:int}}`; write `{{{{` and `}}}}` for literal braces"
+    )
+)]
+pub struct EvalTemplateError {
+    pub message: String,
+    #[label("in this template")]
+    pub span: SourceSpan,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("value does not match `let` pattern")]
+#[diagnostic(code(mictylish::eval_let_pattern))]
+pub struct EvalLetPatternError {
+    pub value: String,
+    #[label("pattern did not match {value}")]
+    pub span: SourceSpan,
+}
+
 #[derive(Debug, Error, Diagnostic)]
 #[error("side-effect function '{name}' can only be called inside `io do ... end`")]
 #[diagnostic(
@@ -181,14 +623,128 @@ pub enum EvalError {
     MatchExhausted(#[from] EvalMatchExhaustedError),
     #[error(transparent)]
     #[diagnostic(transparent)]
+    LetPattern(#[from] EvalLetPatternError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Type(#[from] EvalTypeError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    IndexOutOfRange(#[from] EvalIndexOutOfRangeError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NoField(#[from] EvalNoFieldError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PathJoin(#[from] EvalPathJoinError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Template(#[from] EvalTemplateError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidRegex(#[from] InvalidRegexError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Operands(#[from] EvalOperandError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Assertion(#[from] EvalAssertionError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Arithmetic(#[from] EvalArithmeticError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnboundedRange(#[from] EvalUnboundedRangeError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
     IoRequired(#[from] EvalIoRequiredError),
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnknownBuiltin(#[from] EvalUnknownBuiltinError),
     #[error(transparent)]
     #[diagnostic(transparent)]
+    BuiltinArity(#[from] BuiltinArityError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
     CommandFailed(#[from] EvalCommandFailedError),
     #[error(transparent)]
     #[diagnostic(transparent)]
     CommandIo(#[from] EvalCommandIoError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Ensure(#[from] EvalEnsureError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InModule(#[from] EvalInModuleError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SetOutsideLoop(#[from] SetOutsideLoopError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    CsvOption(#[from] EvalCsvOptionError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    TableOption(#[from] EvalTableOptionError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EmptySeparator(#[from] EvalEmptySeparatorError),
+}
+
+/// The body of `io do ... ensure ... end` failed and so did its cleanup. Reports
+/// as the original error, with the cleanup failure attached as related.
+#[derive(Debug, Error)]
+#[error("{original}")]
+pub struct EvalEnsureError {
+    pub original: Box<EvalError>,
+    pub cleanup: Vec<EvalError>,
+}
+
+impl Diagnostic for EvalEnsureError {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.original.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.original.help()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.original.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        Some(Box::new(
+            self.cleanup.iter().map(|err| err as &dyn Diagnostic),
+        ))
+    }
+}
+
+/// Failure inside a function defined in an imported module. Reports as the
+/// original error, shown against the module's source.
+#[derive(Debug, Error)]
+#[error("{error}")]
+pub struct EvalInModuleError {
+    pub source_code: NamedSource<String>,
+    pub error: Box<EvalError>,
+}
+
+impl Diagnostic for EvalInModuleError {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.error.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.error.help()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.labels()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.source_code)
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.error.related()
+    }
 }
@@ -1,14 +1,33 @@
 use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
 
-use crate::ast::{BinOp, Expr, Pattern, Program, Stmt};
+use miette::{Diagnostic, LabeledSpan, NamedSource};
+
+use crate::ast::{BinOp, Expr, NamedArg, Pattern, Program, Stmt};
+use crate::builtin::{self, JoinRejection, ParamDefault, Signature};
 use crate::command::CommandSpec;
+use crate::csv::{self, CsvOptions};
 use crate::error::{
-    EvalCommandFailedError, EvalCommandIoError, EvalError, EvalInvalidPipeRhsError,
-    EvalIoRequiredError, EvalMatchExhaustedError, EvalPipeNotCallableError, EvalUnboundError,
-    EvalUnknownBuiltinError,
+    EvalArithmeticError, EvalAssertionError, EvalCsvOptionError, EvalEmptySeparatorError,
+    EvalEnsureError, EvalError, EvalInModuleError, EvalIndexOutOfRangeError,
+    EvalInvalidPipeRhsError, EvalIoRequiredError, EvalLetPatternError, EvalMatchExhaustedError,
+    EvalNoFieldError, EvalOperandError, EvalPathJoinError, EvalPipeNotCallableError,
+    EvalTableOptionError, EvalTemplateError, EvalTypeError, EvalUnboundError,
+    EvalUnboundedRangeError, EvalUnknownBuiltinError, InvalidRegexError, SetOutsideLoopError,
 };
+use crate::ini;
+use crate::json;
+use crate::module::Imports;
+use crate::pattern;
 use crate::runtime::run_command;
-use crate::value::{UserFunction, Value};
+use crate::table::Table;
+use crate::template::Template;
+use crate::text;
+use crate::toml;
+use crate::value::{LazySeq, RangeValue, UserFunction, Value};
 
 pub type EvalEnv = HashMap<String, Value>;
 
@@ -16,23 +35,42 @@ fn is_pipe_prelude_target(name: &str) -> bool {
     matches!(name, "identity" | "id")
 }
 
-fn is_io_builtin(name: &str) -> bool {
-    matches!(name, "run_text")
-}
-
-fn is_pure_builtin(name: &str) -> bool {
-    matches!(name, "ok" | "err" | "is_ok" | "is_err")
+fn apply_function(func: &UserFunction, arg: Value, in_io: bool) -> Result<Value, EvalError> {
+    let mut local = (*func.env).clone();
+    local.insert(func.param.clone(), arg);
+    let result = eval_inner(&local, &func.body, in_io);
+    let Some(module) = &func.module else {
+        return result;
+    };
+    match result {
+        // A function made by module code reports its errors against that module too.
+        Ok(Value::Function(mut made)) if made.module.is_none() => {
+            made.module = Some(Rc::clone(module));
+            Ok(Value::Function(made))
+        }
+        Ok(value) => Ok(value),
+        Err(err @ EvalError::InModule(_)) => Err(err),
+        Err(err) => Err(EvalInModuleError {
+            source_code: NamedSource::new(&module.path, module.text.clone()),
+            error: Box::new(err),
+        }
+        .into()),
+    }
 }
 
-fn apply_function(
+/// Applies a unary function value to each argument in turn: `f(a, b)` is `f(a)(b)`.
+fn apply_args(
     env: &EvalEnv,
-    func: &UserFunction,
-    arg: Value,
+    mut func: Value,
+    args: &[Expr],
+    span: miette::SourceSpan,
     in_io: bool,
 ) -> Result<Value, EvalError> {
-    let mut local = env.clone();
-    local.insert(func.param.clone(), arg);
-    eval_inner(&local, &func.body, in_io)
+    for arg in args {
+        let arg = eval_inner(env, arg, in_io)?;
+        func = apply_function(&expect_function(func, span)?, arg, in_io)?;
+    }
+    Ok(func)
 }
 
 pub fn eval_expr(env: &EvalEnv, expr: &Expr) -> Result<Value, EvalError> {
@@ -41,8 +79,12 @@ pub fn eval_expr(env: &EvalEnv, expr: &Expr) -> Result<Value, EvalError> {
 
 fn eval_inner(env: &EvalEnv, expr: &Expr, in_io: bool) -> Result<Value, EvalError> {
     match expr {
+        Expr::Bool(b, _) => Ok(Value::Bool(*b)),
         Expr::Int(n, _) => Ok(Value::Int(*n)),
+        Expr::Duration(d, _) => Ok(Value::Duration(*d)),
+        Expr::Size(bytes, _) => Ok(Value::ByteSize(*bytes)),
         Expr::String(s, _) => Ok(Value::String(s.clone())),
+        Expr::Path(p, _) => Ok(Value::Path(PathBuf::from(p))),
         Expr::Var(name, span) => env.get(name).cloned().ok_or_else(|| {
             EvalUnboundError {
                 name: name.clone(),
@@ -57,39 +99,66 @@ fn eval_inner(env: &EvalEnv, expr: &Expr, in_io: bool) -> Result<Value, EvalErro
             }
             Ok(Value::List(out))
         }
-        Expr::Io { body, .. } => eval_inner(env, body, true),
+        Expr::Io { body, ensure, .. } => {
+            let result = eval_inner(env, body, true);
+            let Some(ensure) = ensure else {
+                return result;
+            };
+            match (result, eval_inner(env, ensure, true)) {
+                (result, Ok(_)) => result,
+                (Ok(_), Err(cleanup)) => Err(cleanup),
+                (Err(original), Err(cleanup)) => Err(EvalEnsureError {
+                    original: Box::new(original),
+                    cleanup: vec![cleanup],
+                }
+                .into()),
+            }
+        }
         Expr::Call {
             name,
             name_span,
             args,
+            named,
             span,
         } => {
-            if is_io_builtin(name) && !in_io {
+            let Some(sig) = builtin::signature(name) else {
+                return Err(EvalUnknownBuiltinError {
+                    name: name.to_string(),
+                    span: *name_span,
+                }
+                .into());
+            };
+            if sig.io && !in_io {
                 return Err(EvalIoRequiredError {
                     name: name.clone(),
                     span: *span,
                 }
                 .into());
             }
-            if is_pure_builtin(name) || is_io_builtin(name) {
-                eval_builtin_call(env, name, name_span, args, *span, in_io)
-            } else {
-                Err(EvalUnknownBuiltinError {
-                    name: name.to_string(),
-                    span: *name_span,
-                }
-                .into())
-            }
+            let options = named_values(env, sig, named, *span, in_io)?;
+            eval_builtin_call(env, name, name_span, args, options, *span, in_io)
         }
-        Expr::BinOp { op, lhs, rhs, .. } => {
+        Expr::BinOp { op, lhs, rhs, span } => {
             let l = eval_inner(env, lhs, in_io)?;
             let r = eval_inner(env, rhs, in_io)?;
-            let result = match op {
-                BinOp::Eq => l == r,
-                BinOp::NotEq => l != r,
-            };
-            Ok(Value::Bool(result))
+            eval_binop(*op, l, r, *span)
         }
+        Expr::Neg { operand, span } => match eval_inner(env, operand, in_io)? {
+            Value::Int(n) => n.checked_neg().map(Value::Int).ok_or_else(|| {
+                EvalArithmeticError {
+                    op: "-".to_string(),
+                    reason: "overflows a 64-bit integer".to_string(),
+                    span: *span,
+                }
+                .into()
+            }),
+            other => Err(EvalTypeError {
+                expected: "int".to_string(),
+                found: other.type_name().to_string(),
+                span: operand.span(),
+            }
+            .into()),
+        },
         Expr::Match {
             subject,
             arms,
@@ -132,6 +201,8 @@ fn eval_inner(env: &EvalEnv, expr: &Expr, in_io: bool) -> Result<Value, EvalErro
         Expr::Fn { param, body, .. } => Ok(Value::Function(UserFunction {
             param: param.clone(),
             body: (**body).clone(),
+            env: Rc::new(env.clone()),
+            module: None,
         })),
         Expr::Pipe(lhs, rhs, _) => {
             let left = eval_inner(env, lhs, in_io)?;
@@ -139,7 +210,7 @@ fn eval_inner(env: &EvalEnv, expr: &Expr, in_io: bool) -> Result<Value, EvalErro
                 Expr::Var(name, span) => {
                     if let Some(bound) = env.get(name) {
                         match bound {
-                            Value::Function(func) => apply_function(env, func, left, in_io),
+                            Value::Function(func) => apply_function(func, left, in_io),
                             _ => Err(EvalPipeNotCallableError {
                                 name: name.clone(),
                                 span: *span,
@@ -156,10 +227,595 @@ fn eval_inner(env: &EvalEnv, expr: &Expr, in_io: bool) -> Result<Value, EvalErro
                         .into())
                     }
                 }
+                Expr::Field { .. } => match eval_inner(env, rhs, in_io)? {
+                    Value::Function(func) => apply_function(&func, left, in_io),
+                    other => Err(EvalTypeError {
+                        expected: "function".to_string(),
+                        found: other.type_name().to_string(),
+                        span: rhs.span(),
+                    }
+                    .into()),
+                },
                 _ => Err(EvalInvalidPipeRhsError { span: rhs.span() }.into()),
             }
         }
+        Expr::MethodCall {
+            receiver,
+            name,
+            name_span,
+            args,
+            named,
+            span,
+        } => {
+            // Same precedence as the resolver: module member, function in scope, builtin.
+            // Only the import alias itself reads as a module, not a copy of its value.
+            if let Expr::Var(receiver_name, _) = receiver.as_ref()
+                && let Some(Value::Module { alias, exports }) = env.get(receiver_name)
+                && alias == receiver_name
+                && let Some(member @ Value::Function(_)) = exports.get(name)
+            {
+                return apply_args(env, member.clone(), args, *name_span, in_io);
+            }
+            if let Some(bound) = env.get(name) {
+                let Value::Function(func) = bound else {
+                    return Err(EvalPipeNotCallableError {
+                        name: name.clone(),
+                        span: *name_span,
+                    }
+                    .into());
+                };
+                let receiver = eval_inner(env, receiver, in_io)?;
+                let applied = apply_function(func, receiver, in_io)?;
+                return apply_args(env, applied, args, *name_span, in_io);
+            }
+            let mut call_args = vec![(**receiver).clone()];
+            call_args.extend(args.iter().cloned());
+            eval_inner(
+                env,
+                &Expr::Call {
+                    name: name.clone(),
+                    name_span: *name_span,
+                    args: call_args,
+                    named: named.clone(),
+                    span: *span,
+                },
+                in_io,
+            )
+        }
+        Expr::Field {
+            target,
+            name,
+            name_span,
+            ..
+        } => match eval_inner(env, target, in_io)? {
+            Value::Record(fields)
+            | Value::Module {
+                exports: fields, ..
+            } => fields.get(name).cloned().ok_or_else(|| {
+                EvalNoFieldError {
+                    name: name.clone(),
+                    span: *name_span,
+                }
+                .into()
+            }),
+            other => Err(EvalTypeError {
+                expected: "record".to_string(),
+                found: other.type_name().to_string(),
+                span: target.span(),
+            }
+            .into()),
+        },
+        Expr::For {
+            var,
+            iter,
+            guard,
+            body,
+            ..
+        } => {
+            let mut local = env.clone();
+            eval_for(&mut local, var, iter, guard.as_deref(), body, in_io)
+        }
+        Expr::Try { body, rescue, .. } => {
+            let err = match eval_inner(env, body, in_io) {
+                Ok(v) if rescue.is_some() => return Ok(v),
+                Ok(v) => return Ok(Value::Ok(Box::new(v))),
+                Err(err) => error_record(&err),
+            };
+            match rescue {
+                Some(rescue) => {
+                    let mut local = env.clone();
+                    local.insert(rescue.name.clone(), err);
+                    eval_inner(&local, &rescue.body, in_io)
+                }
+                None => Ok(Value::Err(Box::new(err))),
+            }
+        }
+        // The resolver only admits `set` items in statement-level loops.
+        Expr::Set { span, .. } => Err(SetOutsideLoopError { span: *span }.into()),
+        Expr::Range { start, end, .. } => {
+            let start = expect_int(eval_inner(env, start, in_io)?, start.span())?;
+            let end = match end {
+                Some(end) => Some(expect_int(eval_inner(env, end, in_io)?, end.span())?),
+                None => None,
+            };
+            Ok(Value::Range(RangeValue { start, end }))
+        }
+        Expr::Index { target, index, .. } => {
+            let value = eval_inner(env, target, in_io)?;
+            let len = indexable_len(&value, target.span())?;
+            let i = match eval_inner(env, index, in_io)? {
+                Value::Range(range) => {
+                    let out_of_range = |bound: i64| -> EvalError {
+                        EvalIndexOutOfRangeError {
+                            index: bound,
+                            len,
+                            span: index.span(),
+                        }
+                        .into()
+                    };
+                    let from = normalize_bound(range.start, len)
+                        .ok_or_else(|| out_of_range(range.start))?;
+                    let to = match range.end {
+                        Some(end) => normalize_bound(end, len).ok_or_else(|| out_of_range(end))?,
+                        None => len,
+                    };
+                    return Ok(slice_value(&value, from, to.max(from)));
+                }
+                other => expect_int(other, index.span())?,
+            };
+            index_value(&value, i).ok_or_else(|| {
+                EvalIndexOutOfRangeError {
+                    index: i,
+                    len,
+                    span: index.span(),
+                }
+                .into()
+            })
+        }
+        Expr::Slice {
+            target, start, end, ..
+        } => {
+            let value = eval_inner(env, target, in_io)?;
+            let len = indexable_len(&value, target.span())?;
+            let bound = |expr: &Option<Box<Expr>>, default: usize| -> Result<usize, EvalError> {
+                let Some(expr) = expr else {
+                    return Ok(default);
+                };
+                let i = expect_int(eval_inner(env, expr, in_io)?, expr.span())?;
+                normalize_bound(i, len).ok_or_else(|| {
+                    EvalIndexOutOfRangeError {
+                        index: i,
+                        len,
+                        span: expr.span(),
+                    }
+                    .into()
+                })
+            };
+            let from = bound(start, 0)?;
+            let to = bound(end, len)?;
+            Ok(slice_value(&value, from, to.max(from)))
+        }
+    }
+}
+
+fn expect_function(value: Value, span: miette::SourceSpan) -> Result<UserFunction, EvalError> {
+    match value {
+        Value::Function(func) => Ok(func),
+        other => Err(EvalTypeError {
+            expected: "function".to_string(),
+            found: other.type_name().to_string(),
+            span,
+        }
+        .into()),
+    }
+}
+
+/// Pulls items from a lazy sequence, running its `map` stages per item.
+/// Without a `limit` the source range must be bounded.
+fn pull_seq(
+    seq: &LazySeq,
+    limit: Option<usize>,
+    span: miette::SourceSpan,
+    in_io: bool,
+) -> Result<Vec<Value>, EvalError> {
+    if limit.is_none() && seq.source.end.is_none() {
+        return Err(EvalUnboundedRangeError { span }.into());
+    }
+    let mut out = Vec::new();
+    let mut n = seq.source.start;
+    while limit.is_none_or(|limit| out.len() < limit) && seq.source.contains(n) {
+        let mut item = Value::Int(n);
+        for stage in &seq.stages {
+            item = apply_function(stage, item, in_io)?;
+        }
+        out.push(item);
+        let Some(next) = n.checked_add(1) else {
+            break;
+        };
+        n = next;
+    }
+    Ok(out)
+}
+
+/// Language-level view of a runtime error, as bound by `try ... rescue e`:
+/// `{kind, message, span: {offset, len}}`.
+fn error_record(err: &EvalError) -> Value {
+    let kind = err.code().map(|code| code.to_string()).unwrap_or_default();
+    let kind = kind.strip_prefix("mictylish::").unwrap_or(&kind);
+    let kind = kind.strip_prefix("eval_").unwrap_or(kind);
+    let mut fields = std::collections::BTreeMap::new();
+    fields.insert("kind".to_string(), Value::String(kind.to_string()));
+    fields.insert("message".to_string(), Value::String(err.to_string()));
+    if let Some(label) = err.labels().and_then(|mut labels| labels.next()) {
+        let mut span = std::collections::BTreeMap::new();
+        span.insert("offset".to_string(), Value::Int(label.offset() as i64));
+        span.insert("len".to_string(), Value::Int(label.len() as i64));
+        fields.insert("span".to_string(), Value::Record(span));
+    }
+    Value::Record(fields)
+}
+
+/// Forces a list, bounded range or seq into its elements.
+fn collect_items(
+    value: Value,
+    span: miette::SourceSpan,
+    use_span: miette::SourceSpan,
+    in_io: bool,
+) -> Result<Vec<Value>, EvalError> {
+    match value {
+        Value::List(items) => Ok(items),
+        Value::Range(range) => pull_seq(&range_seq(range), None, use_span, in_io),
+        Value::Seq(seq) => pull_seq(&seq, None, use_span, in_io),
+        other => Err(EvalTypeError {
+            expected: "list, range or seq".to_string(),
+            found: other.type_name().to_string(),
+            span,
+        }
+        .into()),
+    }
+}
+
+/// Runs a `for` loop in `env`. `set` items write to `env` directly, so a
+/// caller that owns the enclosing bindings sees the final values.
+fn eval_for(
+    env: &mut EvalEnv,
+    var: &str,
+    iter: &Expr,
+    guard: Option<&Expr>,
+    body: &[Expr],
+    in_io: bool,
+) -> Result<Value, EvalError> {
+    let items = collect_items(
+        eval_inner(env, iter, in_io)?,
+        iter.span(),
+        iter.span(),
+        in_io,
+    )?;
+    let run = |env: &mut EvalEnv| -> Result<Vec<Value>, EvalError> {
+        let mut out = Vec::new();
+        for item in items {
+            env.insert(var.to_string(), item);
+            if let Some(guard) = guard
+                && !is_truthy(&eval_inner(env, guard, in_io)?)
+            {
+                continue;
+            }
+            let mut last = Value::Null;
+            for expr in body {
+                last = match expr {
+                    Expr::Set { name, expr, .. } => {
+                        let v = eval_inner(env, expr, in_io)?;
+                        env.insert(name.clone(), v.clone());
+                        v
+                    }
+                    Expr::For {
+                        var,
+                        iter,
+                        guard,
+                        body,
+                        ..
+                    } => eval_for(env, var, iter, guard.as_deref(), body, in_io)?,
+                    _ => eval_inner(env, expr, in_io)?,
+                };
+            }
+            out.push(last);
+        }
+        Ok(out)
+    };
+    let result = run(env);
+    env.remove(var);
+    result.map(Value::List)
+}
+
+/// Evaluates a statement's expression; a `for` here updates `env` in place.
+fn eval_stmt_expr(env: &mut EvalEnv, expr: &Expr) -> Result<Value, EvalError> {
+    match expr {
+        Expr::For {
+            var,
+            iter,
+            guard,
+            body,
+            ..
+        } => eval_for(env, var, iter, guard.as_deref(), body, false),
+        _ => eval_expr(env, expr),
+    }
+}
+
+fn range_seq(range: RangeValue) -> LazySeq {
+    LazySeq {
+        source: range,
+        stages: Vec::new(),
+    }
+}
+
+fn expect_int(value: Value, span: miette::SourceSpan) -> Result<i64, EvalError> {
+    match value {
+        Value::Int(n) => Ok(n),
+        other => Err(EvalTypeError {
+            expected: "int".to_string(),
+            found: other.type_name().to_string(),
+            span,
+        }
+        .into()),
+    }
+}
+
+/// Length in elements; strings count UTF-8 characters, not bytes.
+fn indexable_len(value: &Value, span: miette::SourceSpan) -> Result<usize, EvalError> {
+    match value {
+        Value::List(items) => Ok(items.len()),
+        Value::String(s) => Ok(s.chars().count()),
+        other => Err(EvalTypeError {
+            expected: "list or string".to_string(),
+            found: other.type_name().to_string(),
+            span,
+        }
+        .into()),
+    }
+}
+
+/// Maps a possibly negative index onto `0..len`.
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let i = if index < 0 { len + index } else { index };
+    (0..len).contains(&i).then_some(i as usize)
+}
+
+/// Like [`normalize_index`], but a slice bound may also equal `len`.
+fn normalize_bound(bound: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let i = if bound < 0 { len + bound } else { bound };
+    (0..=len).contains(&i).then_some(i as usize)
+}
+
+fn index_value(value: &Value, index: i64) -> Option<Value> {
+    match value {
+        Value::List(items) => normalize_index(index, items.len()).map(|i| items[i].clone()),
+        Value::String(s) => normalize_index(index, s.chars().count())
+            .and_then(|i| s.chars().nth(i))
+            .map(|c| Value::String(c.to_string())),
+        _ => None,
+    }
+}
+
+fn slice_value(value: &Value, from: usize, to: usize) -> Value {
+    match value {
+        Value::List(items) => Value::List(items[from..to].to_vec()),
+        Value::String(s) => Value::String(s.chars().skip(from).take(to - from).collect()),
+        other => other.clone(),
+    }
+}
+
+fn require_args(
+    name: &str,
+    args: &[Expr],
+    expected: usize,
+    call_span: miette::SourceSpan,
+) -> Result<(), EvalError> {
+    if args.len() == expected {
+        return Ok(());
+    }
+    Err(EvalUnknownBuiltinError {
+        name: format!("{name}() requires exactly {expected} argument(s)"),
+        span: call_span,
+    }
+    .into())
+}
+
+fn expect_string(value: Value, span: miette::SourceSpan) -> Result<String, EvalError> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(EvalTypeError {
+            expected: "string".to_string(),
+            found: other.type_name().to_string(),
+            span,
+        }
+        .into()),
+    }
+}
+
+fn expect_path(value: Value, span: miette::SourceSpan) -> Result<PathBuf, EvalError> {
+    match value {
+        Value::Path(path) => Ok(path),
+        other => Err(EvalTypeError {
+            expected: "path".to_string(),
+            found: other.type_name().to_string(),
+            span,
+        }
+        .into()),
+    }
+}
+
+/// Named argument values with their spans; defaults are filled in at the call span.
+type Options = HashMap<&'static str, (Value, miette::SourceSpan)>;
+
+fn named_values(
+    env: &EvalEnv,
+    sig: &Signature,
+    named: &[NamedArg],
+    call_span: miette::SourceSpan,
+    in_io: bool,
+) -> Result<Options, EvalError> {
+    let mut options = Options::new();
+    for param in sig.named {
+        let given = named.iter().find(|arg| arg.name == param.name);
+        let value = match (given, param.default) {
+            (Some(arg), _) => (eval_inner(env, &arg.value, in_io)?, arg.value.span()),
+            (None, ParamDefault::Null) => (Value::Null, call_span),
+            (None, ParamDefault::Str(s)) => (Value::String(s.to_string()), call_span),
+        };
+        options.insert(param.name, value);
+    }
+    Ok(options)
+}
+
+fn option_type_error(expected: &str, value: &Value, span: miette::SourceSpan) -> EvalError {
+    EvalTypeError {
+        expected: expected.to_string(),
+        found: value.type_name().to_string(),
+        span,
+    }
+    .into()
+}
+
+/// Applies `run_text`'s named options to the command.
+fn apply_command_options(
+    mut spec: CommandSpec,
+    options: &Options,
+) -> Result<CommandSpec, EvalError> {
+    for (name, (value, span)) in options {
+        spec = match (*name, value) {
+            (_, Value::Null) => spec,
+            ("cwd", Value::String(dir)) => spec.with_cwd(dir),
+            ("cwd", Value::Path(dir)) => spec.with_cwd(dir),
+            ("cwd", other) => return Err(option_type_error("string or path", other, *span)),
+            ("env", Value::Record(vars)) => vars.iter().fold(spec, |spec, (key, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                spec.with_env(key, value)
+            }),
+            ("env", other) => return Err(option_type_error("record", other, *span)),
+            ("stdin", Value::String(input)) => spec.with_stdin(input),
+            ("stdin", other) => return Err(option_type_error("string", other, *span)),
+            ("timeout", Value::Int(ms)) if *ms >= 0 => {
+                spec.with_timeout(Duration::from_millis(*ms as u64))
+            }
+            ("timeout", Value::Duration(d)) => spec.with_timeout(*d),
+            ("timeout", other) => {
+                return Err(option_type_error(
+                    "duration or non-negative int (ms)",
+                    other,
+                    *span,
+                ));
+            }
+            _ => spec,
+        };
+    }
+    Ok(spec)
+}
+
+/// Applies `table`'s named options.
+fn apply_table_options(mut table: Table, options: &Options) -> Result<Table, EvalError> {
+    for (name, (value, span)) in options {
+        table = match (*name, value) {
+            (_, Value::Null) => table,
+            ("columns", Value::List(names)) => {
+                let columns: Vec<String> = names
+                    .iter()
+                    .map(|name| match name {
+                        Value::String(s) => Ok(s.clone()),
+                        other => Err(option_type_error("list of strings", other, *span)),
+                    })
+                    .collect::<Result<_, _>>()?;
+                // With no rows there is nothing to check the names against.
+                let unknown: Vec<&String> = columns
+                    .iter()
+                    .filter(|name| !table.rows.is_empty() && !table.columns.contains(name))
+                    .collect();
+                if !unknown.is_empty() {
+                    return Err(EvalTableOptionError {
+                        message: format!("no column named {}", quoted_list(&unknown)),
+                        span: *span,
+                        help: Some(format!("the rows have {}", quoted_list(&table.columns))),
+                    }
+                    .into());
+                }
+                table.with_columns(columns)
+            }
+            ("columns", other) => return Err(option_type_error("list of strings", other, *span)),
+            ("width", Value::Int(n)) if *n > 0 => table.with_max_width(*n as usize),
+            ("width", Value::Int(n)) => {
+                return Err(EvalTableOptionError {
+                    message: format!("`width` must be at least 1, found {n}"),
+                    span: *span,
+                    help: None,
+                }
+                .into());
+            }
+            ("width", other) => return Err(option_type_error("int", other, *span)),
+            _ => table,
+        };
+    }
+    Ok(table)
+}
+
+/// `'a', 'b'`, for naming columns in a message.
+fn quoted_list(names: &[impl AsRef<str>]) -> String {
+    names
+        .iter()
+        .map(|name| format!("'{}'", name.as_ref()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Reads `from_csv` / `to_csv`'s named options.
+fn csv_options(options: &Options) -> Result<CsvOptions, EvalError> {
+    let mut csv = CsvOptions::default();
+    for (name, (value, span)) in options {
+        match (*name, value) {
+            (_, Value::Null) => {}
+            ("delimiter" | "quote", Value::String(s)) => {
+                let mut chars = s.chars();
+                let c = match (s.as_str(), chars.next(), chars.next()) {
+                    ("tab", _, _) => '\t',
+                    (_, Some(c), None) => c,
+                    _ => return Err(option_type_error("one character or \"tab\"", value, *span)),
+                };
+                if matches!(c, '\n' | '\r') {
+                    return Err(EvalCsvOptionError {
+                        message: format!("`{name}` cannot be a line break"),
+                        span: *span,
+                    }
+                    .into());
+                }
+                if *name == "delimiter" {
+                    csv.delimiter = c;
+                } else {
+                    csv.quote = c;
+                }
+            }
+            ("header", Value::Bool(b)) => csv.header = *b,
+            ("types", Value::Bool(b)) => csv.types = *b,
+            ("delimiter" | "quote", other) => {
+                return Err(option_type_error("one character or \"tab\"", other, *span));
+            }
+            (_, other) => return Err(option_type_error("bool", other, *span)),
+        }
+    }
+    if csv.delimiter == csv.quote {
+        let (_, span) = options
+            .get("delimiter")
+            .or_else(|| options.get("quote"))
+            .expect("the defaults differ, so one of them was given");
+        return Err(EvalCsvOptionError {
+            message: format!("`delimiter` and `quote` are both {:?}", csv.delimiter),
+            span: *span,
+        }
+        .into());
     }
+    Ok(csv)
 }
 
 fn eval_builtin_call(
@@ -167,6 +823,7 @@ fn eval_builtin_call(
     name: &str,
     name_span: &miette::SourceSpan,
     args: &[Expr],
+    options: Options,
     call_span: miette::SourceSpan,
     in_io: bool,
 ) -> Result<Value, EvalError> {
@@ -215,32 +872,378 @@ fn eval_builtin_call(
             let v = eval_inner(env, &args[0], in_io)?;
             Ok(Value::Bool(matches!(v, Value::Err(_))))
         }
-        "run_text" => {
-            if args.len() < 1 {
+        "get" => {
+            if args.len() != 2 {
+                return Err(EvalUnknownBuiltinError {
+                    name: "get() requires exactly 2 arguments".to_string(),
+                    span: call_span,
+                }
+                .into());
+            }
+            let value = eval_inner(env, &args[0], in_io)?;
+            let len = indexable_len(&value, args[0].span())?;
+            let i = expect_int(eval_inner(env, &args[1], in_io)?, args[1].span())?;
+            Ok(match index_value(&value, i) {
+                Some(v) => Value::Ok(Box::new(v)),
+                None => {
+                    let mut fields = std::collections::BTreeMap::new();
+                    fields.insert("index".to_string(), Value::Int(i));
+                    fields.insert("len".to_string(), Value::Int(len as i64));
+                    Value::Err(Box::new(Value::Record(fields)))
+                }
+            })
+        }
+        "map" => {
+            if args.len() != 2 {
+                return Err(EvalUnknownBuiltinError {
+                    name: "map() requires exactly 2 arguments".to_string(),
+                    span: call_span,
+                }
+                .into());
+            }
+            let source = eval_inner(env, &args[0], in_io)?;
+            let func = expect_function(eval_inner(env, &args[1], in_io)?, args[1].span())?;
+            match source {
+                Value::List(items) => items
+                    .into_iter()
+                    .map(|item| apply_function(&func, item, in_io))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::List),
+                Value::Range(range) => {
+                    let mut seq = range_seq(range);
+                    seq.stages.push(func);
+                    Ok(Value::Seq(seq))
+                }
+                Value::Seq(mut seq) => {
+                    seq.stages.push(func);
+                    Ok(Value::Seq(seq))
+                }
+                other => Err(EvalTypeError {
+                    expected: "list, range or seq".to_string(),
+                    found: other.type_name().to_string(),
+                    span: args[0].span(),
+                }
+                .into()),
+            }
+        }
+        "take" => {
+            if args.len() != 2 {
+                return Err(EvalUnknownBuiltinError {
+                    name: "take() requires exactly 2 arguments".to_string(),
+                    span: call_span,
+                }
+                .into());
+            }
+            let source = eval_inner(env, &args[0], in_io)?;
+            let n = expect_int(eval_inner(env, &args[1], in_io)?, args[1].span())?;
+            let n = usize::try_from(n).map_err(|_| EvalTypeError {
+                expected: "non-negative int".to_string(),
+                found: n.to_string(),
+                span: args[1].span(),
+            })?;
+            match source {
+                Value::List(items) => Ok(Value::List(items.into_iter().take(n).collect())),
+                Value::Range(range) => {
+                    pull_seq(&range_seq(range), Some(n), call_span, in_io).map(Value::List)
+                }
+                Value::Seq(seq) => pull_seq(&seq, Some(n), call_span, in_io).map(Value::List),
+                other => Err(EvalTypeError {
+                    expected: "list, range or seq".to_string(),
+                    found: other.type_name().to_string(),
+                    span: args[0].span(),
+                }
+                .into()),
+            }
+        }
+        "to_list" => {
+            if args.len() != 1 {
                 return Err(EvalUnknownBuiltinError {
-                    name: "run_text requires at least 1 argument (program)".to_string(),
+                    name: "to_list() requires exactly 1 argument".to_string(),
                     span: call_span,
                 }
                 .into());
             }
-            let program = match eval_inner(env, &args[0], in_io)? {
-                Value::String(s) => s,
+            let value = eval_inner(env, &args[0], in_io)?;
+            collect_items(value, args[0].span(), call_span, in_io).map(Value::List)
+        }
+        "path" => {
+            require_args(name, args, 1, call_span)?;
+            match eval_inner(env, &args[0], in_io)? {
+                Value::String(s) => Ok(Value::Path(PathBuf::from(s))),
+                path @ Value::Path(_) => Ok(path),
+                other => Err(EvalTypeError {
+                    expected: "string or path".to_string(),
+                    found: other.type_name().to_string(),
+                    span: args[0].span(),
+                }
+                .into()),
+            }
+        }
+        "join" => {
+            require_args(name, args, 2, call_span)?;
+            let base = expect_path(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let segment = match eval_inner(env, &args[1], in_io)? {
+                Value::String(s) => PathBuf::from(s),
+                Value::Path(p) => p,
+                other => {
+                    return Err(EvalTypeError {
+                        expected: "string or path".to_string(),
+                        found: other.type_name().to_string(),
+                        span: args[1].span(),
+                    }
+                    .into());
+                }
+            };
+            builtin::join_path(&base, &segment)
+                .map(Value::Path)
+                .map_err(|rejection| {
+                    let reason = match rejection {
+                        JoinRejection::Absolute => "absolute segment would replace the base",
+                        JoinRejection::Escapes => "`..` climbs above the base",
+                    };
+                    EvalPathJoinError {
+                        segment: segment.to_string_lossy().to_string(),
+                        reason: reason.to_string(),
+                        span: args[1].span(),
+                    }
+                    .into()
+                })
+        }
+        "parent" | "file_name" | "extension" | "normalize" => {
+            require_args(name, args, 1, call_span)?;
+            let path = expect_path(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let text = |s: &std::ffi::OsStr| Value::String(s.to_string_lossy().to_string());
+            Ok(match name {
+                "parent" => match path.parent() {
+                    Some(parent) if parent.as_os_str().is_empty() => Value::Path(".".into()),
+                    Some(parent) => Value::Path(parent.to_path_buf()),
+                    None => Value::Null,
+                },
+                "file_name" => path.file_name().map(text).unwrap_or(Value::Null),
+                "extension" => path.extension().map(text).unwrap_or(Value::Null),
+                _ => Value::Path(builtin::normalize_path(&path)),
+            })
+        }
+        "lines" | "words" => {
+            require_args(name, args, 1, call_span)?;
+            let text = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let pieces = if name == "lines" {
+                text::lines(&text)
+            } else {
+                text::words(&text)
+            };
+            Ok(Value::List(pieces.into_iter().map(Value::String).collect()))
+        }
+        "split" => {
+            require_args(name, args, 2, call_span)?;
+            let text = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let sep = expect_string(eval_inner(env, &args[1], in_io)?, args[1].span())?;
+            if sep.is_empty() {
+                return Err(EvalEmptySeparatorError {
+                    span: args[1].span(),
+                }
+                .into());
+            }
+            Ok(Value::List(
+                text::split(&text, &sep)
+                    .into_iter()
+                    .map(Value::String)
+                    .collect(),
+            ))
+        }
+        "parse" => {
+            require_args(name, args, 2, call_span)?;
+            let source = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let template = Template::parse(&source).map_err(|message| EvalTemplateError {
+                message,
+                span: args[0].span(),
+            })?;
+            let lines: Vec<String> = match eval_inner(env, &args[1], in_io)? {
+                Value::String(text) => text::lines(&text),
+                Value::List(items) => items
+                    .into_iter()
+                    .map(|item| expect_string(item, args[1].span()))
+                    .collect::<Result<_, _>>()?,
+                other => {
+                    let expected = "string or list of strings";
+                    return Err(option_type_error(expected, &other, args[1].span()));
+                }
+            };
+            let rows = lines
+                .into_iter()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| match template.match_line(&line) {
+                    Ok(record) => Value::Ok(Box::new(record)),
+                    Err(mismatch) => Value::Err(Box::new(mismatch.into_record(i + 1, line))),
+                })
+                .collect();
+            Ok(Value::List(rows))
+        }
+        "regex_match" | "regex_find_all" | "regex_replace" | "regex_split" => {
+            let arity = if name == "regex_replace" { 3 } else { 2 };
+            require_args(name, args, arity, call_span)?;
+            let text = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let source = expect_string(eval_inner(env, &args[1], in_io)?, args[1].span())?;
+            let re = pattern::compile(&source).map_err(|message| InvalidRegexError {
+                message,
+                span: args[1].span(),
+            })?;
+            Ok(match name {
+                "regex_match" => pattern::first_match(&re, &text),
+                "regex_find_all" => pattern::find_all(&re, &text),
+                "regex_split" => Value::List(
+                    pattern::split(&re, &text)
+                        .into_iter()
+                        .map(Value::String)
+                        .collect(),
+                ),
                 _ => {
-                    return Err(EvalUnknownBuiltinError {
-                        name: "run_text first argument must be a string".to_string(),
+                    let replacement =
+                        expect_string(eval_inner(env, &args[2], in_io)?, args[2].span())?;
+                    Value::String(pattern::replace(&re, &text, &replacement))
+                }
+            })
+        }
+        "parse_kv" | "parse_logfmt" => {
+            require_args(name, args, 1, call_span)?;
+            let text = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let parsed = if name == "parse_kv" {
+                text::parse_kv(&text)
+            } else {
+                text::parse_logfmt(&text)
+            };
+            Ok(match parsed {
+                Ok(value) => Value::Ok(Box::new(value)),
+                Err(err) => Value::Err(Box::new(err.into_record())),
+            })
+        }
+        "from_json" => {
+            require_args(name, args, 1, call_span)?;
+            let text = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            Ok(match json::parse(&text) {
+                Ok(value) => Value::Ok(Box::new(value)),
+                Err(err) => Value::Err(Box::new(err.into_record())),
+            })
+        }
+        "from_toml" | "from_ini" => {
+            require_args(name, args, 1, call_span)?;
+            let text = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            let parsed = if name == "from_toml" {
+                toml::parse(&text)
+            } else {
+                ini::parse(&text)
+            };
+            Ok(match parsed {
+                Ok(value) => Value::Ok(Box::new(value)),
+                Err(err) => Value::Err(Box::new(err.into_record())),
+            })
+        }
+        "to_toml" => {
+            require_args(name, args, 1, call_span)?;
+            let value = eval_inner(env, &args[0], in_io)?;
+            toml::to_toml(&value).map(Value::String).map_err(|found| {
+                EvalTypeError {
+                    expected: "record of values representable in TOML".to_string(),
+                    found: found.to_string(),
+                    span: args[0].span(),
+                }
+                .into()
+            })
+        }
+        "to_json" => {
+            require_args(name, args, 1, call_span)?;
+            let pretty = match &options["pretty"] {
+                (Value::Null, _) => false,
+                (Value::Bool(b), _) => *b,
+                (other, span) => return Err(option_type_error("bool", other, *span)),
+            };
+            let value = eval_inner(env, &args[0], in_io)?;
+            json::to_json(&value, pretty)
+                .map(Value::String)
+                .map_err(|found| {
+                    EvalTypeError {
+                        expected: "value representable as JSON".to_string(),
+                        found: found.to_string(),
                         span: args[0].span(),
                     }
-                    .into())
+                    .into()
+                })
+        }
+        "from_csv" => {
+            require_args(name, args, 1, call_span)?;
+            let csv = csv_options(&options)?;
+            let text = expect_string(eval_inner(env, &args[0], in_io)?, args[0].span())?;
+            Ok(match csv::parse(&text, &csv) {
+                Ok(rows) => Value::Ok(Box::new(rows)),
+                Err(err) => Value::Err(Box::new(err.into_record())),
+            })
+        }
+        "to_csv" => {
+            require_args(name, args, 1, call_span)?;
+            let csv = csv_options(&options)?;
+            let rows = match eval_inner(env, &args[0], in_io)? {
+                Value::Table(table) => table.rows.into_iter().map(Value::Record).collect(),
+                value => collect_items(value, args[0].span(), call_span, in_io)?,
+            };
+            csv::to_csv(&rows, &csv)
+                .map(Value::String)
+                .map_err(|found| {
+                    EvalTypeError {
+                        expected: "rows of records or lists of scalars".to_string(),
+                        found: found.to_string(),
+                        span: args[0].span(),
+                    }
+                    .into()
+                })
+        }
+        "table" => {
+            require_args(name, args, 1, call_span)?;
+            let value = eval_inner(env, &args[0], in_io)?;
+            let mut rows = Vec::new();
+            for item in collect_items(value, args[0].span(), call_span, in_io)? {
+                match item {
+                    Value::Record(fields) => rows.push(fields),
+                    other => {
+                        return Err(EvalTypeError {
+                            expected: "list of records".to_string(),
+                            found: format!("list containing {}", other.type_name()),
+                            span: args[0].span(),
+                        }
+                        .into());
+                    }
+                }
+            }
+            apply_table_options(Table::new(rows), &options).map(Value::Table)
+        }
+        "run_text" => {
+            if args.is_empty() {
+                let sig = builtin::signature("run_text").expect("run_text is a builtin");
+                return Err(sig.arity_error(0, call_span).into());
+            }
+            let program: OsString = match eval_inner(env, &args[0], in_io)? {
+                Value::String(s) => s.into(),
+                Value::Path(p) => p.into_os_string(),
+                other => {
+                    return Err(EvalTypeError {
+                        expected: "string or path".to_string(),
+                        found: other.type_name().to_string(),
+                        span: args[0].span(),
+                    }
+                    .into());
                 }
             };
             let mut cmd_args = Vec::new();
             for arg in &args[1..] {
                 match eval_inner(env, arg, in_io)? {
-                    Value::String(s) => cmd_args.push(s),
-                    other => cmd_args.push(format!("{other}")),
+                    Value::String(s) => cmd_args.push(OsString::from(s)),
+                    Value::Path(p) => cmd_args.push(p.into_os_string()),
+                    other => cmd_args.push(format!("{other}").into()),
                 }
             }
-            let spec = CommandSpec::new(&program, cmd_args);
+            let spec =
+                apply_command_options(CommandSpec::new(program.clone(), cmd_args), &options)?;
+            let program = program.to_string_lossy().to_string();
             match run_command(&spec) {
                 Ok(output) => {
                     if output.status.success() {
@@ -274,6 +1277,280 @@ fn eval_builtin_call(
     }
 }
 
+fn eval_binop(op: BinOp, l: Value, r: Value, span: miette::SourceSpan) -> Result<Value, EvalError> {
+    let operand_error = |l: &Value, r: &Value| -> EvalError {
+        EvalOperandError {
+            op: op.symbol().to_string(),
+            left: l.type_name().to_string(),
+            right: r.type_name().to_string(),
+            span,
+        }
+        .into()
+    };
+    let arithmetic_error = |reason: &str| -> EvalError {
+        EvalArithmeticError {
+            op: op.symbol().to_string(),
+            reason: reason.to_string(),
+            span,
+        }
+        .into()
+    };
+    match op {
+        BinOp::Eq => return Ok(Value::Bool(l == r)),
+        BinOp::NotEq => return Ok(Value::Bool(l != r)),
+        BinOp::Add | BinOp::Sub => {}
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+            let ordering = match (&l, &r) {
+                (Value::Int(a), Value::Int(b)) => a.partial_cmp(b),
+                (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+                (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+                (Value::Duration(a), Value::Duration(b)) => a.partial_cmp(b),
+                (Value::ByteSize(a), Value::ByteSize(b)) => a.partial_cmp(b),
+                _ => return Err(operand_error(&l, &r)),
+            };
+            let result = ordering.is_some_and(|o| match op {
+                BinOp::Lt => o.is_lt(),
+                BinOp::Le => o.is_le(),
+                BinOp::Gt => o.is_gt(),
+                _ => o.is_ge(),
+            });
+            return Ok(Value::Bool(result));
+        }
+    }
+    let add = op == BinOp::Add;
+    match (&l, &r) {
+        (Value::Int(a), Value::Int(b)) => {
+            let sum = if add {
+                a.checked_add(*b)
+            } else {
+                a.checked_sub(*b)
+            };
+            sum.map(Value::Int)
+                .ok_or_else(|| arithmetic_error("overflows a 64-bit integer"))
+        }
+        (Value::Duration(a), Value::Duration(b)) if add => a
+            .checked_add(*b)
+            .map(Value::Duration)
+            .ok_or_else(|| arithmetic_error("overflows the duration range")),
+        (Value::Duration(a), Value::Duration(b)) => a
+            .checked_sub(*b)
+            .map(Value::Duration)
+            .ok_or_else(|| arithmetic_error("would make a negative duration")),
+        (Value::ByteSize(a), Value::ByteSize(b)) if add => a
+            .checked_add(*b)
+            .map(Value::ByteSize)
+            .ok_or_else(|| arithmetic_error("overflows the byte-size range")),
+        (Value::ByteSize(a), Value::ByteSize(b)) => a
+            .checked_sub(*b)
+            .map(Value::ByteSize)
+            .ok_or_else(|| arithmetic_error("would make a negative byte size")),
+        _ => Err(operand_error(&l, &r)),
+    }
+}
+
+fn eval_assert(env: &EvalEnv, expr: &Expr, message: Option<&Expr>) -> Result<(), EvalError> {
+    let mut recorded = Vec::new();
+    match eval_recorded(env, expr, &mut recorded)? {
+        Value::Bool(true) => Ok(()),
+        Value::Bool(false) => {
+            let reason = match message.map(|m| eval_inner(env, m, false)).transpose()? {
+                Some(Value::String(text)) => format!("assertion failed: {text}"),
+                Some(other) => format!("assertion failed: {other}"),
+                None => "assertion failed".to_string(),
+            };
+            // The last value is the whole condition, which the message already
+            // says is false.
+            if recorded.len() > 1 {
+                recorded.pop();
+            }
+            let values = assertion_labels(recorded);
+            Err(EvalAssertionError { reason, values }.into())
+        }
+        other => Err(EvalTypeError {
+            expected: "bool".to_string(),
+            found: other.type_name().to_string(),
+            span: expr.span(),
+        }
+        .into()),
+    }
+}
+
+/// The value of one subexpression of an `assert` condition.
+struct Recorded {
+    span: miette::SourceSpan,
+    text: String,
+    /// `name = value` for a variable.
+    is_var: bool,
+}
+
+/// Lays out recorded values as labels that do not overlap, which the report
+/// could not draw under the right columns. The outermost values are kept, and
+/// the variables inside each are listed in its label: `Ok(1) where xs = [1]`.
+fn assertion_labels(recorded: Vec<Recorded>) -> Vec<LabeledSpan> {
+    let overlaps = |a: &miette::SourceSpan, b: &miette::SourceSpan| {
+        a.offset() < b.offset() + b.len() && b.offset() < a.offset() + a.len()
+    };
+    let mut kept: Vec<(Recorded, Vec<(usize, String)>)> = Vec::new();
+    // Recorded innermost first, so in reverse each value comes before its parts.
+    for value in recorded.into_iter().rev() {
+        match kept
+            .iter_mut()
+            .find(|(outer, _)| overlaps(&outer.span, &value.span))
+        {
+            Some((_, inner)) if value.is_var => inner.push((value.span.offset(), value.text)),
+            Some(_) => {}
+            None => kept.push((value, Vec::new())),
+        }
+    }
+    kept.sort_by_key(|(value, _)| value.span.offset());
+    kept.into_iter()
+        .map(|(value, mut inner)| {
+            inner.sort();
+            let mut names: Vec<String> = Vec::new();
+            for (_, text) in inner {
+                if !names.contains(&text) {
+                    names.push(text);
+                }
+            }
+            let text = if names.is_empty() {
+                value.text
+            } else {
+                format!("{} where {}", value.text, names.join(", "))
+            };
+            LabeledSpan::new_with_span(Some(text), value.span)
+        })
+        .collect()
+}
+
+/// Evaluates an `assert` condition, recording the value of every non-literal
+/// subexpression. Operands of operators, calls, pipes, lists, field access and
+/// indexing are evaluated first and read back from hidden bindings, so each
+/// part of the condition still runs exactly once.
+fn eval_recorded(
+    env: &EvalEnv,
+    expr: &Expr,
+    values: &mut Vec<Recorded>,
+) -> Result<Value, EvalError> {
+    let mut local = env.clone();
+    let mut operand = |expr: &Expr| record_operand(&mut local, expr, values);
+    let rebuilt = match expr {
+        Expr::BinOp { op, lhs, rhs, span } => Some(Expr::BinOp {
+            op: *op,
+            lhs: Box::new(operand(lhs)?),
+            rhs: Box::new(operand(rhs)?),
+            span: *span,
+        }),
+        Expr::Neg {
+            operand: inner,
+            span,
+        } => Some(Expr::Neg {
+            operand: Box::new(operand(inner)?),
+            span: *span,
+        }),
+        Expr::Call {
+            name,
+            name_span,
+            args,
+            named,
+            span,
+        } => Some(Expr::Call {
+            name: name.clone(),
+            name_span: *name_span,
+            args: args.iter().map(&mut operand).collect::<Result<_, _>>()?,
+            named: named.clone(),
+            span: *span,
+        }),
+        Expr::MethodCall {
+            receiver,
+            name,
+            name_span,
+            args,
+            named,
+            span,
+        } => Some(Expr::MethodCall {
+            receiver: Box::new(operand(receiver)?),
+            name: name.clone(),
+            name_span: *name_span,
+            args: args.iter().map(&mut operand).collect::<Result<_, _>>()?,
+            named: named.clone(),
+            span: *span,
+        }),
+        Expr::Pipe(lhs, rhs, span) => Some(Expr::Pipe(Box::new(operand(lhs)?), rhs.clone(), *span)),
+        Expr::List(items, span) => Some(Expr::List(
+            items.iter().map(&mut operand).collect::<Result<_, _>>()?,
+            *span,
+        )),
+        Expr::Field {
+            target,
+            name,
+            name_span,
+            span,
+        } => Some(Expr::Field {
+            target: Box::new(operand(target)?),
+            name: name.clone(),
+            name_span: *name_span,
+            span: *span,
+        }),
+        Expr::Index {
+            target,
+            index,
+            span,
+        } => Some(Expr::Index {
+            target: Box::new(operand(target)?),
+            index: Box::new(operand(index)?),
+            span: *span,
+        }),
+        _ => None,
+    };
+    let value = eval_inner(&local, rebuilt.as_ref().unwrap_or(expr), false)?;
+    let text = match expr {
+        _ if is_literal(expr) => None,
+        Expr::Var(_, _) if matches!(value, Value::Module { .. }) => None,
+        Expr::Var(name, _) => Some(format!("{name} = {value}")),
+        _ => Some(value.to_string()),
+    };
+    if let Some(text) = text {
+        values.push(Recorded {
+            span: expr.span(),
+            text,
+            is_var: matches!(expr, Expr::Var(..)),
+        });
+    }
+    Ok(value)
+}
+
+/// Records one operand for [`eval_recorded`] and returns an expression that
+/// reads its value back. Literals and names stay as written, which also keeps
+/// a module alias usable as a method receiver; anything else is bound under a
+/// name no identifier can spell.
+fn record_operand(
+    local: &mut EvalEnv,
+    expr: &Expr,
+    values: &mut Vec<Recorded>,
+) -> Result<Expr, EvalError> {
+    let value = eval_recorded(local, expr, values)?;
+    if is_literal(expr) || matches!(expr, Expr::Var(..)) {
+        return Ok(expr.clone());
+    }
+    let name = format!("#{}", local.len());
+    local.insert(name.clone(), value);
+    Ok(Expr::Var(name, expr.span()))
+}
+
+/// A literal, or a list of them; its value is already plain in the source.
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Bool(..)
+        | Expr::Int(..)
+        | Expr::String(..)
+        | Expr::Path(..)
+        | Expr::Duration(..)
+        | Expr::Size(..) => true,
+        Expr::List(items, _) => items.iter().all(is_literal),
+        _ => false,
+    }
+}
+
 fn is_truthy(value: &Value) -> bool {
     match value {
         Value::Null => false,
@@ -297,6 +1574,18 @@ fn try_match(pattern: &Pattern, value: &Value) -> Option<Vec<(String, Value)>> {
             _ => None,
         },
         Pattern::Var(name, _) => Some(vec![(name.clone(), value.clone())]),
+        Pattern::Range { start, end, .. } => match value {
+            Value::Int(n)
+                if RangeValue {
+                    start: *start,
+                    end: *end,
+                }
+                .contains(*n) =>
+            {
+                Some(vec![])
+            }
+            _ => None,
+        },
         Pattern::Ok(inner_pat, _) => match value {
             Value::Ok(inner_val) => try_match(inner_pat, inner_val),
             _ => None,
@@ -321,22 +1610,85 @@ fn try_match(pattern: &Pattern, value: &Value) -> Option<Vec<(String, Value)>> {
 pub fn eval_program(
     env: &mut EvalEnv,
     program: &Program,
+) -> Result<Vec<(String, Value)>, EvalError> {
+    eval_program_with_imports(env, program, &Imports::new())
+}
+
+/// Evaluates `program` with `import` statements bound from already-loaded modules.
+pub fn eval_program_with_imports(
+    env: &mut EvalEnv,
+    program: &Program,
+    imports: &Imports,
 ) -> Result<Vec<(String, Value)>, EvalError> {
     let mut out = Vec::new();
     for stmt in &program.stmts {
         match stmt {
             Stmt::Let { name, expr, .. } => {
-                let v = eval_expr(env, expr)?;
+                let v = eval_stmt_expr(env, expr)?;
                 env.insert(name.clone(), v.clone());
                 out.push((name.clone(), v));
             }
+            Stmt::LetPattern {
+                pattern,
+                expr,
+                else_body,
+                ..
+            } => {
+                let v = eval_stmt_expr(env, expr)?;
+                let bindings = match (try_match(pattern, &v), else_body) {
+                    (Some(bindings), _) => bindings,
+                    // The resolver allows `else` only on single-name patterns;
+                    // the fallback becomes that name's value without matching.
+                    (None, Some(fallback)) => {
+                        let fallback = eval_stmt_expr(env, fallback)?;
+                        pattern
+                            .bound_names()
+                            .into_iter()
+                            .map(|name| (name.to_string(), fallback.clone()))
+                            .collect()
+                    }
+                    (None, None) => {
+                        return Err(EvalLetPatternError {
+                            value: v.to_string(),
+                            span: pattern.span(),
+                        }
+                        .into());
+                    }
+                };
+                for (name, value) in bindings {
+                    env.insert(name.clone(), value.clone());
+                    out.push((name, value));
+                }
+            }
             Stmt::Set { name, expr, .. } => {
-                let v = eval_expr(env, expr)?;
+                let v = eval_stmt_expr(env, expr)?;
                 env.insert(name.clone(), v.clone());
                 out.push((name.clone(), v));
             }
+            Stmt::Import {
+                path,
+                path_span,
+                alias,
+                ..
+            } => {
+                let Some(Value::Record(exports)) = imports.get(path) else {
+                    return Err(EvalUnboundError {
+                        name: path.clone(),
+                        span: *path_span,
+                    }
+                    .into());
+                };
+                let v = Value::Module {
+                    alias: alias.clone(),
+                    exports: exports.clone(),
+                };
+                env.insert(alias.clone(), v.clone());
+                out.push((alias.clone(), v));
+            }
+            Stmt::Export { .. } => {}
+            Stmt::Assert { expr, message, .. } => eval_assert(env, expr, message.as_ref())?,
             Stmt::Expr(expr) => {
-                let v = eval_expr(env, expr)?;
+                let v = eval_stmt_expr(env, expr)?;
                 out.push(("_".to_string(), v));
             }
         }
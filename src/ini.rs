@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use crate::toml::ConfigError;
+use crate::value::Value;
+
+/// Reads an INI file into a record: keys before the first `[section]` go at
+/// the top level, each section becomes a nested record. Lines starting with
+/// `;` or `#` are comments, and `:` may stand for `=`. Unquoted values that
+/// read as ints, floats or `true`/`false` are converted; quoted values stay
+/// strings.
+pub fn parse(text: &str) -> Result<Value, ConfigError> {
+    let mut root: BTreeMap<String, Value> = BTreeMap::new();
+    let mut section: Option<String> = None;
+    let mut offset = 0;
+    for raw in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += raw.len();
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let indent = line.len() - line.trim_start().len();
+        let content = line.trim();
+        let at =
+            |column: usize, message: String| ConfigError::at(text, line_start + column, message);
+        if content.is_empty() || content.starts_with([';', '#']) {
+            continue;
+        }
+        if let Some(header) = content.strip_prefix('[') {
+            let Some(name) = header.strip_suffix(']') else {
+                return Err(at(
+                    line.trim_end().len(),
+                    "expected `]` to close the section".to_string(),
+                ));
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(at(indent, "empty section name".to_string()));
+            }
+            match root.get(name) {
+                // A repeated section continues the earlier one.
+                Some(Value::Record(_)) => {}
+                Some(_) => return Err(at(indent, format!("section `{name}` clashes with a key"))),
+                None => {
+                    root.insert(name.to_string(), Value::Record(BTreeMap::new()));
+                }
+            }
+            section = Some(name.to_string());
+            continue;
+        }
+        let Some(split) = content.find(['=', ':']) else {
+            return Err(at(
+                indent,
+                format!("expected `key = value`, found `{content}`"),
+            ));
+        };
+        let key = content[..split].trim();
+        if key.is_empty() {
+            return Err(at(indent, "missing key before `=`".to_string()));
+        }
+        let value = typed(content[split + 1..].trim());
+        let table = match &section {
+            Some(name) => match root.get_mut(name) {
+                Some(Value::Record(fields)) => fields,
+                _ => unreachable!("sections are records"),
+            },
+            None => &mut root,
+        };
+        if table.contains_key(key) {
+            return Err(at(indent, format!("duplicate key `{key}`")));
+        }
+        table.insert(key.to_string(), value);
+    }
+    Ok(Value::Record(root))
+}
+
+fn typed(value: &str) -> Value {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return Value::String(value[1..value.len() - 1].to_string());
+        }
+    }
+    if let Ok(n) = value.parse::<i64>()
+        && n.to_string() == value
+    {
+        return Value::Int(n);
+    }
+    match value {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    // Only floats that print back unchanged, so `version = 1.10` stays text.
+    match value.parse::<f64>() {
+        Ok(x) if x.is_finite() && x.to_string() == value => Value::Float(x),
+        _ => Value::String(value.to_string()),
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::BTreeMap;
+
+use crate::value::Value;
+
+/// Deepest array/object nesting `parse` accepts, so hostile input cannot
+/// exhaust the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Why JSON text could not be read; `line` and `column` are 1-based, with the
+/// column counted in characters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl JsonError {
+    /// The `{message, line, column}` record `from_json` returns inside `Err`.
+    pub fn into_record(self) -> Value {
+        let mut fields = BTreeMap::new();
+        fields.insert("message".to_string(), Value::String(self.message));
+        fields.insert("line".to_string(), Value::Int(self.line as i64));
+        fields.insert("column".to_string(), Value::Int(self.column as i64));
+        Value::Record(fields)
+    }
+}
+
+/// Reads one JSON document: objects become records, arrays lists, and
+/// numbers `Int` when they are integers that fit in 64 bits, else `Float`.
+pub fn parse(text: &str) -> Result<Value, JsonError> {
+    let mut reader = Reader {
+        text,
+        pos: 0,
+        depth: 0,
+    };
+    reader.skip_whitespace();
+    let value = reader.value()?;
+    reader.skip_whitespace();
+    if reader.pos < text.len() {
+        return Err(reader.error("trailing characters after JSON value"));
+    }
+    Ok(value)
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: impl Into<String>) -> JsonError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> JsonError {
+        let before = &self.text[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        JsonError {
+            message: message.into(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    fn found(&self) -> String {
+        match self.peek() {
+            Some(c) => format!("`{c}`"),
+            None => "end of input".to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, JsonError> {
+        match self.peek() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => self.string().map(Value::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some('a'..='z') => {
+                let start = self.pos;
+                while let Some('a'..='z') = self.peek() {
+                    self.pos += 1;
+                }
+                match &self.text[start..self.pos] {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "null" => Ok(Value::Null),
+                    word => Err(self.error_at(start, format!("unknown literal `{word}`"))),
+                }
+            }
+            _ => Err(self.error(format!("expected a JSON value, found {}", self.found()))),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        read: fn(&mut Self) -> Result<Value, JsonError>,
+    ) -> Result<Value, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("nesting deeper than {MAX_DEPTH} levels")));
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self) -> Result<Value, JsonError> {
+        self.bump();
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(Value::List(items));
+        }
+        loop {
+            self.skip_whitespace();
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Value::List(items));
+                }
+                _ => return Err(self.error(format!("expected `,` or `]`, found {}", self.found()))),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, JsonError> {
+        self.bump();
+        let mut fields = BTreeMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(Value::Record(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error(format!("expected a string key, found {}", self.found())));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if self.peek() != Some(':') {
+                return Err(self.error(format!("expected `:`, found {}", self.found())));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            // Later duplicates win, as in most JSON readers.
+            fields.insert(key, self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Value::Record(fields));
+                }
+                _ => {
+                    return Err(self.error(format!("expected `,` or `}}`, found {}", self.found())));
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        let start = self.pos;
+        self.bump();
+        let mut out = String::new();
+        loop {
+            let at = self.pos;
+            match self.bump() {
+                None => return Err(self.error_at(start, "unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => out.push(self.escape(at)?),
+                Some(c) if c < ' ' => {
+                    return Err(self.error_at(at, "control character in string must be escaped"));
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    /// Reads the escape after a backslash at `at`.
+    fn escape(&mut self, at: usize) -> Result<char, JsonError> {
+        Ok(match self.bump() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.hex4(at)?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high)
+                        .ok_or_else(|| self.error_at(at, "unpaired surrogate in `\\u` escape"));
+                }
+                if !self.text[self.pos..].starts_with("\\u") {
+                    return Err(self.error_at(at, "unpaired surrogate in `\\u` escape"));
+                }
+                self.pos += 2;
+                let low = self.hex4(at)?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error_at(at, "unpaired surrogate in `\\u` escape"));
+                }
+                char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                    .expect("surrogate pair is a valid scalar")
+            }
+            Some(c) => return Err(self.error_at(at, format!("invalid escape `\\{c}`"))),
+            None => return Err(self.error_at(at, "unterminated string")),
+        })
+    }
+
+    fn hex4(&mut self, at: usize) -> Result<u32, JsonError> {
+        let digits = self.text.get(self.pos..self.pos + 4).unwrap_or("");
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.error_at(at, "`\\u` needs four hex digits"));
+        }
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("checked hex digits"))
+    }
+
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let start = self.pos;
+        let digits = |r: &mut Self| {
+            let from = r.pos;
+            while let Some('0'..='9') = r.peek() {
+                r.pos += 1;
+            }
+            r.pos - from
+        };
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        let int_start = self.pos;
+        let int_digits = digits(self);
+        if int_digits == 0 || (int_digits > 1 && self.text[int_start..].starts_with('0')) {
+            return Err(self.error_at(start, "invalid number"));
+        }
+        let mut integer = true;
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            integer = false;
+            if digits(self) == 0 {
+                return Err(
+                    self.error(format!("expected digits after `.`, found {}", self.found()))
+                );
+            }
+        }
+        if let Some('e' | 'E') = self.peek() {
+            self.pos += 1;
+            integer = false;
+            if let Some('+' | '-') = self.peek() {
+                self.pos += 1;
+            }
+            if digits(self) == 0 {
+                return Err(self.error(format!("expected exponent digits, found {}", self.found())));
+            }
+        }
+        let literal = &self.text[start..self.pos];
+        if integer && let Ok(n) = literal.parse::<i64>() {
+            return Ok(Value::Int(n));
+        }
+        match literal.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(Value::Float(x)),
+            _ => Err(self.error_at(start, format!("number `{literal}` is out of range"))),
+        }
+    }
+}
+
+/// Writes `value` as JSON; `pretty` puts each item on its own line, indented
+/// by two spaces. Fails with the type name of the first value JSON cannot hold.
+pub fn to_json(value: &Value, pretty: bool) -> Result<String, &'static str> {
+    let mut out = String::new();
+    write_value(&mut out, value, pretty.then_some(0))?;
+    Ok(out)
+}
+
+/// `level` is the current indentation depth, or `None` for compact output.
+fn write_value(out: &mut String, value: &Value, level: Option<usize>) -> Result<(), &'static str> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(n) => out.push_str(&n.to_string()),
+        // `{:?}` keeps the `.0` so the number reads back as a float.
+        Value::Float(x) if x.is_finite() => out.push_str(&format!("{x:?}")),
+        Value::String(s) => write_string(out, s),
+        Value::Path(p) => write_string(out, &p.to_string_lossy()),
+        Value::DateTime(text) => write_string(out, text),
+        Value::ByteSize(n) => out.push_str(&n.to_string()),
+        Value::List(items) => write_seq(out, '[', ']', items.iter(), level, |out, item, level| {
+            write_value(out, item, level)
+        })?,
+        Value::Record(fields) => write_seq(
+            out,
+            '{',
+            '}',
+            fields.iter(),
+            level,
+            |out, (key, field), level| {
+                write_string(out, key);
+                out.push_str(if level.is_some() { ": " } else { ":" });
+                write_value(out, field, level)
+            },
+        )?,
+        Value::Table(table) => {
+            let rows: Vec<Value> = table.rows.iter().cloned().map(Value::Record).collect();
+            write_value(out, &Value::List(rows), level)?
+        }
+        other => return Err(other.type_name()),
+    }
+    Ok(())
+}
+
+fn write_seq<T>(
+    out: &mut String,
+    open: char,
+    close: char,
+    items: impl ExactSizeIterator<Item = T>,
+    level: Option<usize>,
+    mut write_item: impl FnMut(&mut String, T, Option<usize>) -> Result<(), &'static str>,
+) -> Result<(), &'static str> {
+    out.push(open);
+    let empty = items.len() == 0;
+    let inner = level.map(|level| level + 1);
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if let Some(inner) = inner {
+            out.push('\n');
+            out.push_str(&"  ".repeat(inner));
+        }
+        write_item(out, item, inner)?;
+    }
+    if let (Some(level), false) = (level, empty) {
+        out.push('\n');
+        out.push_str(&"  ".repeat(level));
+    }
+    out.push(close);
+    Ok(())
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
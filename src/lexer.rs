@@ -1,9 +1,13 @@
 use std::iter::Peekable;
 use std::str::CharIndices;
 
+use unicode_normalization::UnicodeNormalization;
+use unicode_xid::UnicodeXID;
+
 use crate::error::ParseError;
-use crate::span::span;
+use crate::span::{Span, span};
 use crate::token::{Token, TokenKind};
+use crate::units::{self, UnitError, UnitLiteral};
 
 pub fn lex(source: &str) -> Result<Vec<Token>, ParseError> {
     let mut lexer = Lexer {
@@ -28,6 +32,11 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
+            if ch == 'p' && self.source[idx + 1..].starts_with('"') {
+                tokens.push(self.lex_path(idx)?);
+                continue;
+            }
+
             if is_ident_start(ch) {
                 tokens.push(self.lex_ident_or_keyword(idx));
                 continue;
@@ -47,13 +56,48 @@ impl<'a> Lexer<'a> {
                 }
                 '-' => {
                     self.chars.next();
-                    self.expect_char('>', idx, "expected '>' after '-'")?;
-                    tokens.push(Token::new(TokenKind::Arrow, span(idx, 2)));
+                    if self.chars.peek().is_some_and(|&(_, c)| c == '>') {
+                        self.chars.next();
+                        tokens.push(Token::new(TokenKind::Arrow, span(idx, 2)));
+                    } else {
+                        tokens.push(Token::new(TokenKind::Minus, span(idx, 1)));
+                    }
+                }
+                '.' => {
+                    self.chars.next();
+                    if self.chars.peek().is_some_and(|&(_, c)| c == '.') {
+                        self.chars.next();
+                        tokens.push(Token::new(TokenKind::DotDot, span(idx, 2)));
+                    } else {
+                        tokens.push(Token::new(TokenKind::Dot, span(idx, 1)));
+                    }
                 }
                 '<' => {
                     self.chars.next();
-                    self.expect_char('-', idx, "expected '-' after '<'")?;
-                    tokens.push(Token::new(TokenKind::LeftArrow, span(idx, 2)));
+                    match self.chars.peek() {
+                        Some(&(_, '-')) => {
+                            self.chars.next();
+                            tokens.push(Token::new(TokenKind::LeftArrow, span(idx, 2)));
+                        }
+                        Some(&(_, '=')) => {
+                            self.chars.next();
+                            tokens.push(Token::new(TokenKind::LessEqual, span(idx, 2)));
+                        }
+                        _ => tokens.push(Token::new(TokenKind::Less, span(idx, 1))),
+                    }
+                }
+                '>' => {
+                    self.chars.next();
+                    if self.chars.peek().is_some_and(|&(_, c)| c == '=') {
+                        self.chars.next();
+                        tokens.push(Token::new(TokenKind::GreaterEqual, span(idx, 2)));
+                    } else {
+                        tokens.push(Token::new(TokenKind::Greater, span(idx, 1)));
+                    }
+                }
+                '+' => {
+                    self.chars.next();
+                    tokens.push(Token::new(TokenKind::Plus, span(idx, 1)));
                 }
                 '=' => {
                     self.chars.next();
@@ -73,6 +117,14 @@ impl<'a> Lexer<'a> {
                     self.chars.next();
                     tokens.push(Token::new(TokenKind::Comma, span(idx, 1)));
                 }
+                ':' => {
+                    self.chars.next();
+                    tokens.push(Token::new(TokenKind::Colon, span(idx, 1)));
+                }
+                ';' => {
+                    self.chars.next();
+                    tokens.push(Token::new(TokenKind::Semicolon, span(idx, 1)));
+                }
                 '(' => {
                     self.chars.next();
                     tokens.push(Token::new(TokenKind::LParen, span(idx, 1)));
@@ -99,6 +151,11 @@ impl<'a> Lexer<'a> {
         }
 
         tokens.push(Token::new(TokenKind::Eof, span(self.source.len(), 0)));
+        let mut prev_end = 0;
+        for token in &mut tokens {
+            token.line_start = self.source[prev_end..token.span.offset()].contains('\n');
+            prev_end = token.span.offset() + token.span.len();
+        }
         Ok(tokens)
     }
 
@@ -112,44 +169,130 @@ impl<'a> Lexer<'a> {
                 break;
             }
         }
-        let text = &self.source[start..end];
-        let kind = match text {
+        // NFC so that precomposed and decomposed spellings name the same binding.
+        let text: String = self.source[start..end].nfc().collect();
+        let kind = match text.as_str() {
             "let" => TokenKind::Let,
             "mut" => TokenKind::Mut,
             "set" => TokenKind::Set,
             "fn" => TokenKind::Fn,
+            "def" => TokenKind::Def,
             "match" => TokenKind::Match,
             "with" => TokenKind::With,
             "when" => TokenKind::When,
             "io" => TokenKind::Io,
+            "for" => TokenKind::For,
+            "try" => TokenKind::Try,
+            "import" => TokenKind::Import,
+            "export" => TokenKind::Export,
+            "assert" => TokenKind::Assert,
             "do" => TokenKind::Do,
             "end" => TokenKind::End,
-            _ => TokenKind::Ident(text.to_string()),
+            "true" => TokenKind::Bool(true),
+            "false" => TokenKind::Bool(false),
+            _ => TokenKind::Ident(text),
         };
         Token::new(kind, span(start, end.saturating_sub(start)))
     }
 
+    /// Decimal, `0x`/`0o`/`0b` prefixed, with optional `_` separators. The token
+    /// holds the magnitude; the parser checks it against the sign in front.
     fn lex_int(&mut self, start: usize) -> Result<Token, ParseError> {
-        let mut end = start;
+        let (radix, radix_name) = match self.source.get(start..start + 2) {
+            Some("0x") => (16, "hexadecimal"),
+            Some("0o") => (8, "octal"),
+            Some("0b") => (2, "binary"),
+            _ => (10, "decimal"),
+        };
+        let digits_start = if radix == 10 {
+            start
+        } else {
+            self.chars.next();
+            self.chars.next();
+            start + 2
+        };
+        let mut end = digits_start;
         while let Some((idx, ch)) = self.chars.peek().cloned() {
-            if ch.is_ascii_digit() {
+            let accepted = if radix == 10 {
+                ch.is_ascii_digit() || ch == '_'
+            } else {
+                is_ident_continue(ch)
+            };
+            if !accepted {
+                break;
+            }
+            if ch != '_' && !ch.is_digit(radix) {
+                return Err(ParseError::new(
+                    format!("invalid digit '{ch}' in {radix_name} literal"),
+                    span(idx, ch.len_utf8()),
+                ));
+            }
+            end = idx + ch.len_utf8();
+            self.chars.next();
+        }
+        let digits: String = self.source[digits_start..end]
+            .chars()
+            .filter(|&c| c != '_')
+            .collect();
+        if digits.is_empty() {
+            return Err(ParseError::new(
+                format!("{radix_name} literal has no digits"),
+                span(start, end - start),
+            ));
+        }
+        // Units are ASCII, so `3件` is a number followed by a name.
+        if radix == 10 && self.chars.peek().is_some_and(|&(_, c)| c.is_ascii_alphabetic()) {
+            return self.lex_unit_suffix(start, end, &digits);
+        }
+
+        let text = &self.source[start..end];
+        let literal = span(start, end - start);
+        match u64::from_str_radix(&digits, radix) {
+            Ok(magnitude) if magnitude <= i64::MIN.unsigned_abs() => {
+                Ok(Token::new(TokenKind::Int(magnitude), literal))
+            }
+            _ => Err(int_out_of_range(text, literal)),
+        }
+    }
+
+    /// `30s`, `250ms`, `10MiB`: decimal `digits` in `start..digits_end` followed
+    /// directly by a unit.
+    fn lex_unit_suffix(
+        &mut self,
+        start: usize,
+        digits_end: usize,
+        digits: &str,
+    ) -> Result<Token, ParseError> {
+        let mut end = digits_end;
+        while let Some((idx, ch)) = self.chars.peek().cloned() {
+            if is_ident_continue(ch) {
                 end = idx + ch.len_utf8();
                 self.chars.next();
             } else {
                 break;
             }
         }
+        let literal_span = span(start, end - start);
+        let suffix = &self.source[digits_end..end];
         let text = &self.source[start..end];
-        let value = text.parse::<i64>().map_err(|_| {
-            ParseError::new(
-                format!("invalid integer literal '{text}'"),
-                span(start, end.saturating_sub(start)),
-            )
-        })?;
-        Ok(Token::new(
-            TokenKind::Int(value),
-            span(start, end.saturating_sub(start)),
-        ))
+        let out_of_range =
+            || ParseError::new(format!("literal '{text}' is out of range"), literal_span);
+        let amount = digits.parse::<u64>().map_err(|_| out_of_range())?;
+        let kind = match units::apply_suffix(amount, suffix) {
+            Ok(UnitLiteral::Duration(d)) => TokenKind::Duration(d),
+            Ok(UnitLiteral::Size(bytes)) => TokenKind::Size(bytes),
+            Err(UnitError::Overflow) => return Err(out_of_range()),
+            Err(UnitError::Unknown) => {
+                return Err(ParseError::new(
+                    format!(
+                        "unknown unit '{suffix}' (expected one of {})",
+                        units::suffix_list()
+                    ),
+                    span(digits_end, end - digits_end),
+                ));
+            }
+        };
+        Ok(Token::new(kind, literal_span))
     }
 
     fn lex_string(&mut self, start: usize) -> Result<Token, ParseError> {
@@ -157,7 +300,7 @@ impl<'a> Lexer<'a> {
         let mut content = String::new();
         let mut end = start + 1;
 
-        while let Some((idx, ch)) = self.chars.next() {
+        for (idx, ch) in self.chars.by_ref() {
             if ch == '"' {
                 end = idx + 1;
                 return Ok(Token::new(
@@ -175,6 +318,19 @@ impl<'a> Lexer<'a> {
         ))
     }
 
+    /// `p"./logs"`: a string literal prefixed with `p`.
+    fn lex_path(&mut self, start: usize) -> Result<Token, ParseError> {
+        self.chars.next();
+        let string = self.lex_string(start + 1)?;
+        let TokenKind::String(path) = string.kind else {
+            unreachable!("lex_string yields a string token")
+        };
+        Ok(Token::new(
+            TokenKind::Path(path),
+            span(start, string.span.len() + 1),
+        ))
+    }
+
     fn expect_char(
         &mut self,
         expected: char,
@@ -192,10 +348,22 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// An integer literal that does not fit in an `i64`, even with a `-` in front.
+pub(crate) fn int_out_of_range(text: &str, literal: Span) -> ParseError {
+    ParseError::new(format!("integer literal '{text}' is out of range"), literal).with_help(
+        format!(
+            "integers are 64-bit: {} to {} ({:#x})",
+            i64::MIN,
+            i64::MAX,
+            i64::MAX
+        ),
+    )
+}
+
 fn is_ident_start(ch: char) -> bool {
-    ch.is_ascii_alphabetic() || ch == '_'
+    ch == '_' || ch.is_xid_start()
 }
 
 fn is_ident_continue(ch: char) -> bool {
-    ch.is_ascii_alphanumeric() || ch == '_'
+    ch.is_xid_continue()
 }
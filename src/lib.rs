@@ -1,13 +1,26 @@
 pub mod ast;
 pub mod builtin;
 pub mod command;
+pub mod csv;
+pub mod effect;
 pub mod error;
 pub mod eval;
+pub mod ini;
+pub mod json;
 pub mod lexer;
+pub mod module;
 pub mod parser;
+pub mod pattern;
+pub mod pretty;
 pub mod repl;
 pub mod resolver;
 pub mod runtime;
 pub mod span;
+pub mod table;
+pub mod template;
+pub mod text;
 pub mod token;
+pub mod toml;
+pub mod typeck;
+pub mod units;
 pub mod value;
@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use miette::{NamedSource, Report};
+
+use crate::ast::{Program, Stmt};
+use crate::effect::Latent;
+use crate::error::{ImportCycleError, ModuleError, ModuleFileError, ModuleNotFoundError};
+use crate::eval::{EvalEnv, eval_program_with_imports};
+use crate::parser::{StatementMode, parse_program_with};
+use crate::resolver::Resolver;
+use crate::span::Span;
+use crate::typeck::TypeChecker;
+use crate::value::{ModuleSource, Value};
+
+/// What importers may see of a module: its exported names and their latent effects.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModuleInterface {
+    pub exports: BTreeMap<String, Latent>,
+}
+
+/// A fully evaluated module file.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub path: PathBuf,
+    pub interface: ModuleInterface,
+    /// Record of exported values, bound to the `import` alias.
+    pub value: Value,
+}
+
+/// Module values keyed by the path string written in `import`.
+pub type Imports = HashMap<String, Value>;
+
+/// Loads module files once each and detects import cycles.
+#[derive(Debug, Default)]
+pub struct ModuleLoader {
+    cache: HashMap<PathBuf, Rc<Module>>,
+    loading: Vec<PathBuf>,
+}
+
+impl ModuleLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every module `program` imports, relative to `base_dir`, and
+    /// registers their interfaces with `resolver`.
+    pub fn link(
+        &mut self,
+        program: &Program,
+        base_dir: &Path,
+        resolver: &mut Resolver,
+    ) -> Result<Imports, ModuleError> {
+        let mut imports = Imports::new();
+        for stmt in &program.stmts {
+            if let Stmt::Import {
+                path, path_span, ..
+            } = stmt
+            {
+                let module = self.load(&base_dir.join(path), *path_span)?;
+                resolver.register_module(path.clone(), module.interface.clone());
+                imports.insert(path.clone(), module.value.clone());
+            }
+        }
+        Ok(imports)
+    }
+
+    fn load(&mut self, path: &Path, span: Span) -> Result<Rc<Module>, ModuleError> {
+        let not_found = |reason: std::io::Error| ModuleNotFoundError {
+            path: path.display().to_string(),
+            reason: reason.to_string(),
+            span,
+        };
+        let path = path.canonicalize().map_err(not_found)?;
+        if let Some(start) = self.loading.iter().position(|p| *p == path) {
+            let chain = self.loading[start..]
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(ImportCycleError { chain, span }.into());
+        }
+        if let Some(module) = self.cache.get(&path) {
+            return Ok(Rc::clone(module));
+        }
+        let source = std::fs::read_to_string(&path).map_err(not_found)?;
+
+        self.loading.push(path.clone());
+        let result = self.evaluate(&path, &source);
+        self.loading.pop();
+
+        let (interface, value) = result.map_err(|report| ModuleFileError {
+            path: path.display().to_string(),
+            span,
+            errors: vec![
+                report
+                    .with_source_code(NamedSource::new(path.display().to_string(), source.clone())),
+            ],
+        })?;
+        let module = Rc::new(Module {
+            path: path.clone(),
+            interface,
+            value,
+        });
+        self.cache.insert(path, Rc::clone(&module));
+        Ok(module)
+    }
+
+    /// Runs a module file through the whole pipeline and collects its exports.
+    fn evaluate(&mut self, path: &Path, source: &str) -> Result<(ModuleInterface, Value), Report> {
+        // Module files are written one statement per line.
+        let program = parse_program_with(source, StatementMode::Newline).map_err(Report::new)?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        let mut resolver = Resolver::new();
+        let imports = self
+            .link(&program, base_dir, &mut resolver)
+            .map_err(Report::new)?;
+        resolver.resolve_program(&program).map_err(Report::new)?;
+        TypeChecker::new()
+            .check_program(&program)
+            .map_err(Report::new)?;
+        let mut env = EvalEnv::new();
+        eval_program_with_imports(&mut env, &program, &imports).map_err(Report::new)?;
+
+        let interface = resolver.interface();
+        // Exported functions keep the file's text so their runtime errors can
+        // point into it when called from the importer.
+        let module_source = Rc::new(ModuleSource {
+            path: path.display().to_string(),
+            text: source.to_string(),
+        });
+        let fields = interface
+            .exports
+            .keys()
+            .filter_map(|name| {
+                let mut value = env.get(name)?.clone();
+                if let Value::Function(func) = &mut value {
+                    func.module = Some(Rc::clone(&module_source));
+                }
+                Some((name.clone(), value))
+            })
+            .collect();
+        Ok((interface, Value::Record(fields)))
+    }
+}
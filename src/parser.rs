@@ -1,38 +1,126 @@
-use crate::ast::{BinOp, Expr, MatchArm, Pattern, Program, Stmt, WithBinding};
+use crate::ast::{
+    BinOp, Expr, MatchArm, NamedArg, Pattern, Program, RescueClause, Stmt, TypeAnn, WithBinding,
+};
 use crate::error::ParseError;
-use crate::lexer::lex;
-use crate::span::covering;
+use crate::lexer::{int_out_of_range, lex};
+use crate::span::{Span, covering};
 use crate::token::{Token, TokenKind};
 
 pub fn parse_program(source: &str) -> Result<Program, ParseError> {
+    parse_program_with(source, StatementMode::default())
+}
+
+pub fn parse_program_with(source: &str, mode: StatementMode) -> Result<Program, ParseError> {
     let tokens = lex(source)?;
-    let mut parser = Parser::new(tokens);
+    let mut parser = Parser::new(tokens).with_mode(mode);
     parser.parse_program()
 }
 
+/// How consecutive statements are told apart. `;` separates them in either mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatementMode {
+    /// Statements may follow each other on one line (`let x = 1 let y = 2`).
+    #[default]
+    Juxtaposed,
+    /// A newline or `;` ends a statement. An expression continues onto the next
+    /// line only inside `(`/`[` or after a trailing `|>`.
+    Newline,
+}
+
 #[derive(Debug, Clone)]
 pub struct Parser {
     tokens: Vec<Token>,
     pos: usize,
+    mode: StatementMode,
+    /// Open `(`/`[` around the current position; newlines inside them never end anything.
+    depth: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, pos: 0 }
+        Self {
+            tokens,
+            pos: 0,
+            mode: StatementMode::default(),
+            depth: 0,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: StatementMode) -> Self {
+        self.mode = mode;
+        self
     }
 
     pub fn parse_program(&mut self) -> Result<Program, ParseError> {
         let mut stmts = Vec::new();
-        while !self.is_eof() {
+        loop {
+            self.skip_semicolons();
+            if self.is_eof() {
+                break;
+            }
             stmts.push(self.parse_stmt()?);
+            self.expect_separator(&TokenKind::Eof)?;
         }
         Ok(Program::new(stmts))
     }
 
+    fn skip_semicolons(&mut self) {
+        while self.matches(&TokenKind::Semicolon) {
+            self.bump();
+        }
+    }
+
+    /// In newline mode, a statement (or block item) must be followed by `;`,
+    /// a newline or `closing`; anything else on the same line is misplaced.
+    fn expect_separator(&self, closing: &TokenKind) -> Result<(), ParseError> {
+        let next = self.peek();
+        if self.mode == StatementMode::Juxtaposed
+            || self.depth > 0
+            || next.line_start
+            || self.matches(&TokenKind::Semicolon)
+            || self.matches(closing)
+        {
+            return Ok(());
+        }
+        Err(ParseError::new(
+            format!(
+                "expected `;` or a newline before {}",
+                token_label(&next.kind)
+            ),
+            next.span,
+        )
+        .with_help("each statement goes on its own line, or is separated with `;`"))
+    }
+
+    /// Whether an operator at the next token continues the current expression.
+    /// In newline mode, one that starts a line begins a new statement instead.
+    fn continues_expr(&self) -> bool {
+        self.mode == StatementMode::Juxtaposed || self.depth > 0 || !self.peek().line_start
+    }
+
+    /// Rejects an operand on the line after its operator `op`; only `|>` may trail.
+    fn expect_operand_on_same_line(&self, op: BinOp) -> Result<(), ParseError> {
+        if self.continues_expr() {
+            return Ok(());
+        }
+        Err(ParseError::new(
+            format!(
+                "`{}` cannot continue an expression onto the next line",
+                op.symbol()
+            ),
+            self.peek().span,
+        )
+        .with_help("only a trailing `|>` or an open `(`/`[` continues an expression"))
+    }
+
     fn parse_stmt(&mut self) -> Result<Stmt, ParseError> {
         match self.peek_kind() {
             TokenKind::Let => self.parse_let_stmt(),
+            TokenKind::Def => self.parse_def_stmt(),
             TokenKind::Set => self.parse_set_stmt(),
+            TokenKind::Import => self.parse_import_stmt(),
+            TokenKind::Export => self.parse_export_stmt(),
+            TokenKind::Assert => self.parse_assert_stmt(),
             _ => Ok(Stmt::Expr(self.parse_expr()?)),
         }
     }
@@ -45,7 +133,11 @@ impl Parser {
         } else {
             false
         };
+        if !mutable && self.starts_let_pattern() {
+            return self.parse_let_pattern(let_token.span);
+        }
         let (name, name_span) = self.expect_ident()?;
+        let ty = self.parse_type_annotation()?;
         self.expect(TokenKind::Equal, "'=' after let binding")?;
         let expr = self.parse_expr()?;
         let span = covering(&let_token.span, &expr.span());
@@ -53,11 +145,104 @@ impl Parser {
             name,
             name_span,
             mutable,
+            ty,
+            expr,
+            span,
+        })
+    }
+
+    /// `def name(a: T, b) -> R do body end` binds `name` like
+    /// `let name = fn (a: T) -> fn b -> body end end`, with `R` annotating what
+    /// the innermost function returns.
+    fn parse_def_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let def_token = self.bump();
+        let (name, name_span) = self.expect_ident()?;
+        self.expect(TokenKind::LParen, "'(' after function name")?;
+        self.depth += 1;
+        let params = self.parse_def_params();
+        self.depth -= 1;
+        let params = params?;
+        let ret_ty = if self.matches(&TokenKind::Arrow) {
+            self.bump();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        self.expect(TokenKind::Do, "`do` before function body")?;
+        let body = self.parse_expr()?;
+        let end = self.expect(TokenKind::End, "`end` to close function")?;
+        let span = covering(&def_token.span, &end.span);
+        let mut expr = body;
+        let mut ret_ty = ret_ty;
+        for (param, param_span, param_ty) in params.into_iter().rev() {
+            expr = Expr::Fn {
+                param,
+                param_span,
+                param_ty,
+                ret_ty: ret_ty.take(),
+                body: Box::new(expr),
+                span,
+            };
+        }
+        Ok(Stmt::Let {
+            name,
+            name_span,
+            mutable: false,
+            ty: None,
             expr,
             span,
         })
     }
 
+    /// Parses `a: T, b)` after the `(` of a `def`; there is at least one.
+    fn parse_def_params(
+        &mut self,
+    ) -> Result<Vec<(String, miette::SourceSpan, Option<TypeAnn>)>, ParseError> {
+        let mut params = Vec::new();
+        loop {
+            let (param, param_span) = self.expect_ident()?;
+            let param_ty = self.parse_type_annotation()?;
+            params.push((param, param_span, param_ty));
+            if self.matches(&TokenKind::Comma) {
+                self.bump();
+                continue;
+            }
+            self.expect(TokenKind::RParen, "',' or ')' after function parameter")?;
+            return Ok(params);
+        }
+    }
+
+    /// A `let` target other than a plain name: `[..]`, `Ok(..)`, `Err(..)` or a literal.
+    fn starts_let_pattern(&self) -> bool {
+        match self.peek_kind() {
+            TokenKind::LBracket | TokenKind::Int(_) | TokenKind::String(_) => true,
+            TokenKind::Ident(name) => {
+                (name == "Ok" || name == "Err")
+                    && matches!(self.peek_nth_kind(1), TokenKind::LParen)
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_let_pattern(&mut self, let_span: miette::SourceSpan) -> Result<Stmt, ParseError> {
+        let pattern = self.parse_pattern()?;
+        self.expect(TokenKind::Equal, "'=' after let pattern")?;
+        let expr = self.parse_expr()?;
+        let else_body = if self.matches_ident("else") {
+            self.bump();
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let last = else_body.as_ref().unwrap_or(&expr).span();
+        Ok(Stmt::LetPattern {
+            pattern,
+            expr,
+            else_body,
+            span: covering(&let_span, &last),
+        })
+    }
+
     fn parse_set_stmt(&mut self) -> Result<Stmt, ParseError> {
         let set_token = self.bump();
         let (name, name_span) = self.expect_ident()?;
@@ -72,9 +257,66 @@ impl Parser {
         })
     }
 
+    fn parse_import_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let import_token = self.bump();
+        let path_token = self.bump();
+        let TokenKind::String(path) = path_token.kind else {
+            return Err(ParseError::new(
+                format!(
+                    "expected module path string, found {}",
+                    token_label(&path_token.kind)
+                ),
+                path_token.span,
+            ));
+        };
+        if !self.matches_ident("as") {
+            return Err(self.expected_error("`as` after import path"));
+        }
+        self.bump();
+        let (alias, alias_span) = self.expect_ident()?;
+        Ok(Stmt::Import {
+            path,
+            path_span: path_token.span,
+            alias,
+            alias_span,
+            span: covering(&import_token.span, &alias_span),
+        })
+    }
+
+    fn parse_export_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let export_token = self.bump();
+        let mut names = vec![self.expect_ident()?];
+        while self.matches(&TokenKind::Comma) {
+            self.bump();
+            names.push(self.expect_ident()?);
+        }
+        let last = names.last().map_or(export_token.span, |(_, span)| *span);
+        Ok(Stmt::Export {
+            names,
+            span: covering(&export_token.span, &last),
+        })
+    }
+
+    fn parse_assert_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let assert_token = self.bump();
+        let expr = self.parse_expr()?;
+        let message = if self.matches(&TokenKind::Comma) {
+            self.bump();
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let last = message.as_ref().unwrap_or(&expr).span();
+        Ok(Stmt::Assert {
+            expr,
+            message,
+            span: covering(&assert_token.span, &last),
+        })
+    }
+
     fn parse_expr(&mut self) -> Result<Expr, ParseError> {
         let mut lhs = self.parse_comparison()?;
-        while self.matches(&TokenKind::PipeGreater) {
+        while self.matches(&TokenKind::PipeGreater) && self.continues_expr() {
             self.bump();
             let rhs = self.parse_comparison()?;
             let span = covering(&lhs.span(), &rhs.span());
@@ -84,15 +326,20 @@ impl Parser {
     }
 
     fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
-        let lhs = self.parse_primary()?;
+        let lhs = self.parse_additive()?;
         let op = match self.peek_kind() {
             TokenKind::EqualEqual => Some(BinOp::Eq),
             TokenKind::NotEqual => Some(BinOp::NotEq),
+            TokenKind::Less => Some(BinOp::Lt),
+            TokenKind::LessEqual => Some(BinOp::Le),
+            TokenKind::Greater => Some(BinOp::Gt),
+            TokenKind::GreaterEqual => Some(BinOp::Ge),
             _ => None,
         };
-        if let Some(op) = op {
+        if let Some(op) = op.filter(|_| self.continues_expr()) {
             self.bump();
-            let rhs = self.parse_primary()?;
+            self.expect_operand_on_same_line(op)?;
+            let rhs = self.parse_additive()?;
             let span = covering(&lhs.span(), &rhs.span());
             Ok(Expr::BinOp {
                 op,
@@ -105,20 +352,195 @@ impl Parser {
         }
     }
 
+    fn parse_additive(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.parse_range()?;
+        while self.continues_expr() {
+            let op = match self.peek_kind() {
+                TokenKind::Plus => BinOp::Add,
+                // Outside brackets, a `-` opening a line starts a new operand,
+                // such as the next match arm's negative pattern.
+                TokenKind::Minus if self.depth > 0 || !self.peek().line_start => BinOp::Sub,
+                _ => break,
+            };
+            self.bump();
+            self.expect_operand_on_same_line(op)?;
+            let rhs = self.parse_range()?;
+            let span = covering(&lhs.span(), &rhs.span());
+            lhs = Expr::BinOp {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                span,
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn parse_range(&mut self) -> Result<Expr, ParseError> {
+        let start = self.parse_unary()?;
+        if !self.matches(&TokenKind::DotDot) || !self.continues_expr() {
+            return Ok(start);
+        }
+        let spaced = !self.touches_previous();
+        let dots = self.bump();
+        let end = self.parse_range_end(spaced)?;
+        let last = end.as_ref().map_or(dots.span, |end| end.span());
+        Ok(Expr::Range {
+            span: covering(&start.span(), &last),
+            start: Box::new(start),
+            end,
+        })
+    }
+
+    /// `0..` is unbounded unless an operand directly follows the dots, or the
+    /// dots are spaced on both sides as in `1 .. 4`; `0.. x` leaves `x` to
+    /// start the next operand (e.g. the next match arm's pattern).
+    fn parse_range_end(&mut self, spaced: bool) -> Result<Option<Box<Expr>>, ParseError> {
+        let starts_operand = matches!(
+            self.peek_kind(),
+            TokenKind::Int(_) | TokenKind::Ident(_) | TokenKind::LParen | TokenKind::Minus
+        );
+        let spaced_end = spaced && !self.peek().line_start;
+        if starts_operand && (self.touches_previous() || spaced_end) {
+            Ok(Some(Box::new(self.parse_unary()?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Prefix `-`. An integer literal right after it is folded into a negative
+    /// literal, which is how `-9223372036854775808` is written; postfix forms
+    /// then apply to that literal: `-2.inc()` is `inc(-2)`.
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if !self.matches(&TokenKind::Minus) {
+            return self.parse_primary();
+        }
+        let minus = self.bump();
+        if let TokenKind::Int(magnitude) = *self.peek_kind() {
+            let int = self.bump();
+            let value = int_literal(magnitude, true, int.span)?;
+            return self.parse_postfix(Expr::Int(value, covering(&minus.span, &int.span)));
+        }
+        let operand = self.parse_unary()?;
+        Ok(Expr::Neg {
+            span: covering(&minus.span, &operand.span()),
+            operand: Box::new(operand),
+        })
+    }
+
     fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let atom = self.parse_atom()?;
+        self.parse_postfix(atom)
+    }
+
+    /// Indexing, field access and method calls after `expr`.
+    fn parse_postfix(&mut self, mut expr: Expr) -> Result<Expr, ParseError> {
+        loop {
+            // Postfix `[` must touch the expression: `xs[0]` indexes, while `xs [0]`
+            // starts a new list (e.g. the next match arm's pattern).
+            if self.matches(&TokenKind::LBracket) && self.touches_previous() {
+                expr = self.parse_index(expr)?;
+            } else if self.matches(&TokenKind::Dot) && self.continues_expr() {
+                self.bump();
+                let (name, name_span) = self.expect_ident()?;
+                if self.matches(&TokenKind::LParen) {
+                    let (args, named, end) = self.parse_call_args()?;
+                    expr = Expr::MethodCall {
+                        span: covering(&expr.span(), &end),
+                        receiver: Box::new(expr),
+                        name,
+                        name_span,
+                        args,
+                        named,
+                    };
+                    continue;
+                }
+                expr = Expr::Field {
+                    span: covering(&expr.span(), &name_span),
+                    target: Box::new(expr),
+                    name,
+                    name_span,
+                };
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_index(&mut self, target: Expr) -> Result<Expr, ParseError> {
+        self.bump(); // consume LBracket
+        self.depth += 1;
+        let result = self.parse_index_rest(target);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_index_rest(&mut self, target: Expr) -> Result<Expr, ParseError> {
+        if self.matches(&TokenKind::DotDot) {
+            self.bump();
+            let end_expr = if self.matches(&TokenKind::RBracket) {
+                None
+            } else {
+                Some(Box::new(self.parse_expr()?))
+            };
+            let end = self.expect(TokenKind::RBracket, "']' to close slice")?;
+            return Ok(Expr::Slice {
+                span: covering(&target.span(), &end.span),
+                target: Box::new(target),
+                start: None,
+                end: end_expr,
+            });
+        }
+        let index = self.parse_expr()?;
+        let end = self.expect(TokenKind::RBracket, "']' to close index")?;
+        let span = covering(&target.span(), &end.span);
+        Ok(match index {
+            Expr::Range {
+                start,
+                end: range_end,
+                ..
+            } => Expr::Slice {
+                target: Box::new(target),
+                start: Some(start),
+                end: range_end,
+                span,
+            },
+            index => Expr::Index {
+                target: Box::new(target),
+                index: Box::new(index),
+                span,
+            },
+        })
+    }
+
+    fn parse_atom(&mut self) -> Result<Expr, ParseError> {
         let token = self.bump();
         match token.kind {
-            TokenKind::Int(v) => Ok(Expr::Int(v, token.span)),
+            TokenKind::Bool(b) => Ok(Expr::Bool(b, token.span)),
+            TokenKind::Int(v) => Ok(Expr::Int(int_literal(v, false, token.span)?, token.span)),
+            TokenKind::Duration(d) => Ok(Expr::Duration(d, token.span)),
+            TokenKind::Size(bytes) => Ok(Expr::Size(bytes, token.span)),
             TokenKind::String(v) => Ok(Expr::String(v, token.span)),
-            TokenKind::Ident(name) if self.matches(&TokenKind::LParen) => {
+            TokenKind::Path(v) => Ok(Expr::Path(v, token.span)),
+            TokenKind::Ident(name) if self.matches(&TokenKind::LParen) && self.continues_expr() => {
                 self.parse_call_expr(name, token.span)
             }
             TokenKind::Ident(name) => Ok(Expr::Var(name, token.span)),
             TokenKind::LBracket => self.parse_list(token.span),
+            TokenKind::LParen => {
+                self.depth += 1;
+                let inner = self.parse_expr();
+                self.depth -= 1;
+                let inner = inner?;
+                self.expect(TokenKind::RParen, "')' to close parenthesized expression")?;
+                Ok(inner)
+            }
             TokenKind::Fn => self.parse_fn_expr(token.span),
             TokenKind::Match => self.parse_match_expr(token.span),
             TokenKind::With => self.parse_with_expr(token.span),
             TokenKind::Io => self.parse_io_expr(token.span),
+            TokenKind::For => self.parse_for_expr(token.span),
+            TokenKind::Try => self.parse_try_expr(token.span),
             _ => Err(ParseError::new(
                 format!("expected expression, found {}", token_label(&token.kind)),
                 token.span,
@@ -127,18 +549,72 @@ impl Parser {
     }
 
     fn parse_fn_expr(&mut self, fn_span: miette::SourceSpan) -> Result<Expr, ParseError> {
-        let (param, param_span) = self.expect_ident()?;
+        let (param, param_span, param_ty, ret_ty) = if self.matches(&TokenKind::LParen) {
+            self.bump();
+            let (param, param_span) = self.expect_ident()?;
+            let param_ty = self.parse_type_annotation()?;
+            self.expect(TokenKind::RParen, "')' after function parameter")?;
+            let ret_ty = self.parse_type_annotation()?;
+            (param, param_span, param_ty, ret_ty)
+        } else {
+            let (param, param_span) = self.expect_ident()?;
+            (param, param_span, None, None)
+        };
         self.expect(TokenKind::Arrow, "'->' after function parameter")?;
         let body = self.parse_expr()?;
         let end = self.expect(TokenKind::End, "`end` to close function")?;
         Ok(Expr::Fn {
             param,
             param_span,
+            param_ty,
+            ret_ty,
             body: Box::new(body),
             span: covering(&fn_span, &end.span),
         })
     }
 
+    /// Parses an optional `: Type` suffix.
+    fn parse_type_annotation(&mut self) -> Result<Option<TypeAnn>, ParseError> {
+        if !self.matches(&TokenKind::Colon) {
+            return Ok(None);
+        }
+        self.bump();
+        self.parse_type().map(Some)
+    }
+
+    fn parse_type(&mut self) -> Result<TypeAnn, ParseError> {
+        let token = self.bump();
+        let TokenKind::Ident(name) = token.kind else {
+            return Err(ParseError::new(
+                format!("expected type, found {}", token_label(&token.kind)),
+                token.span,
+            ));
+        };
+        if !self.matches(&TokenKind::LBracket) {
+            return Ok(TypeAnn {
+                name,
+                args: Vec::new(),
+                span: token.span,
+            });
+        }
+        self.bump();
+        let mut args = Vec::new();
+        loop {
+            args.push(self.parse_type()?);
+            if self.matches(&TokenKind::Comma) {
+                self.bump();
+                continue;
+            }
+            break;
+        }
+        let end = self.expect(TokenKind::RBracket, "']' to close type arguments")?;
+        Ok(TypeAnn {
+            name,
+            args,
+            span: covering(&token.span, &end.span),
+        })
+    }
+
     fn parse_match_expr(&mut self, match_span: miette::SourceSpan) -> Result<Expr, ParseError> {
         let subject = self.parse_expr()?;
         self.expect(TokenKind::Do, "`do` after match subject")?;
@@ -164,7 +640,7 @@ impl Parser {
         if arms.is_empty() {
             return Err(ParseError::new(
                 "match expression must have at least one arm",
-                self.peek().span.clone(),
+                self.peek().span,
             ));
         }
         let end = self.expect(TokenKind::End, "`end` to close match")?;
@@ -178,7 +654,18 @@ impl Parser {
     fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
         let token = self.bump();
         match token.kind {
-            TokenKind::Int(v) => Ok(Pattern::Int(v, token.span)),
+            TokenKind::Int(v) => {
+                let start = int_literal(v, false, token.span)?;
+                self.finish_int_pattern(start, token.span)
+            }
+            TokenKind::Minus if matches!(self.peek_kind(), TokenKind::Int(_)) => {
+                let int = self.bump();
+                let TokenKind::Int(v) = int.kind else {
+                    unreachable!("peeked an integer literal")
+                };
+                let start = int_literal(v, true, int.span)?;
+                self.finish_int_pattern(start, covering(&token.span, &int.span))
+            }
             TokenKind::String(v) => Ok(Pattern::String(v, token.span)),
             TokenKind::Ident(ref name) if name == "_" => Ok(Pattern::Wildcard(token.span)),
             TokenKind::Ident(ref name)
@@ -204,6 +691,46 @@ impl Parser {
         }
     }
 
+    /// An integer pattern, or a range pattern when followed by `..`.
+    fn finish_int_pattern(
+        &mut self,
+        start: i64,
+        start_span: miette::SourceSpan,
+    ) -> Result<Pattern, ParseError> {
+        if !self.matches(&TokenKind::DotDot) {
+            return Ok(Pattern::Int(start, start_span));
+        }
+        let dots = self.bump();
+        let bounded = self.touches_previous()
+            && matches!(self.peek_kind(), TokenKind::Int(_) | TokenKind::Minus);
+        if !bounded {
+            return Ok(Pattern::Range {
+                start,
+                end: None,
+                span: covering(&start_span, &dots.span),
+            });
+        }
+        let negative = self.matches(&TokenKind::Minus);
+        if negative {
+            self.bump();
+        }
+        let end_token = self.bump();
+        let TokenKind::Int(end) = end_token.kind else {
+            return Err(ParseError::new(
+                format!(
+                    "expected integer literal, found {}",
+                    token_label(&end_token.kind)
+                ),
+                end_token.span,
+            ));
+        };
+        Ok(Pattern::Range {
+            start,
+            end: Some(int_literal(end, negative, end_token.span)?),
+            span: covering(&start_span, &end_token.span),
+        })
+    }
+
     fn parse_list_pattern(&mut self, start_span: miette::SourceSpan) -> Result<Pattern, ParseError> {
         let mut items = Vec::new();
         if self.matches(&TokenKind::RBracket) {
@@ -238,13 +765,12 @@ impl Parser {
         if bindings.is_empty() {
             return Err(ParseError::new(
                 "with expression must have at least one binding",
-                self.peek().span.clone(),
+                self.peek().span,
             ));
         }
         self.expect(TokenKind::Do, "`do` after with bindings")?;
         let body = self.parse_expr()?;
-        let else_kw = self.peek().clone();
-        if !matches!(else_kw.kind, TokenKind::Ident(ref s) if s == "else") {
+        if !self.matches_ident("else") {
             return Err(self.expected_error("`else` clause in with expression"));
         }
         self.bump();
@@ -258,12 +784,108 @@ impl Parser {
         })
     }
 
+    fn parse_for_expr(&mut self, for_span: miette::SourceSpan) -> Result<Expr, ParseError> {
+        let (var, var_span) = self.expect_ident()?;
+        if !self.matches_ident("in") {
+            return Err(self.expected_error("`in` after for variable"));
+        }
+        self.bump();
+        let iter = self.parse_expr()?;
+        let guard = if self.matches(&TokenKind::When) {
+            self.bump();
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
+        self.expect(TokenKind::Do, "`do` after for source")?;
+        let mut body = Vec::new();
+        loop {
+            self.skip_semicolons();
+            if self.matches(&TokenKind::End) || self.is_eof() {
+                break;
+            }
+            body.push(if self.matches(&TokenKind::Set) {
+                self.parse_set_item()?
+            } else {
+                self.parse_expr()?
+            });
+            self.expect_separator(&TokenKind::End)?;
+        }
+        if body.is_empty() {
+            return Err(ParseError::new(
+                "for body must have at least one expression",
+                self.peek().span,
+            ));
+        }
+        let end = self.expect(TokenKind::End, "`end` to close for loop")?;
+        Ok(Expr::For {
+            var,
+            var_span,
+            iter: Box::new(iter),
+            guard,
+            body,
+            span: covering(&for_span, &end.span),
+        })
+    }
+
+    /// `set name = expr` inside a `for` body.
+    fn parse_set_item(&mut self) -> Result<Expr, ParseError> {
+        let Stmt::Set {
+            name,
+            name_span,
+            expr,
+            span,
+        } = self.parse_set_stmt()?
+        else {
+            unreachable!("parse_set_stmt always yields Stmt::Set")
+        };
+        Ok(Expr::Set {
+            name,
+            name_span,
+            expr: Box::new(expr),
+            span,
+        })
+    }
+
+    fn parse_try_expr(&mut self, try_span: miette::SourceSpan) -> Result<Expr, ParseError> {
+        self.expect(TokenKind::Do, "`do` after `try`")?;
+        let body = self.parse_expr()?;
+        let rescue = if self.matches_ident("rescue") {
+            let rescue_token = self.bump();
+            let (name, name_span) = self.expect_ident()?;
+            self.expect(TokenKind::Arrow, "'->' after rescue binding")?;
+            let handler = self.parse_expr()?;
+            let span = covering(&rescue_token.span, &handler.span());
+            Some(Box::new(RescueClause {
+                name,
+                name_span,
+                body: handler,
+                span,
+            }))
+        } else {
+            None
+        };
+        let end = self.expect(TokenKind::End, "`end` to close try")?;
+        Ok(Expr::Try {
+            body: Box::new(body),
+            rescue,
+            span: covering(&try_span, &end.span),
+        })
+    }
+
     fn parse_io_expr(&mut self, io_span: miette::SourceSpan) -> Result<Expr, ParseError> {
         self.expect(TokenKind::Do, "`do` after `io`")?;
         let body = self.parse_expr()?;
+        let ensure = if self.matches_ident("ensure") {
+            self.bump();
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
         let end = self.expect(TokenKind::End, "`end` to close io block")?;
         Ok(Expr::Io {
             body: Box::new(body),
+            ensure,
             span: covering(&io_span, &end.span),
         })
     }
@@ -273,11 +895,56 @@ impl Parser {
         name: String,
         name_span: miette::SourceSpan,
     ) -> Result<Expr, ParseError> {
+        let (args, named, end) = self.parse_call_args()?;
+        Ok(Expr::Call {
+            name,
+            name_span,
+            args,
+            named,
+            span: covering(&name_span, &end),
+        })
+    }
+
+    /// Parses `(a, b, ..., name: value, ...)`; returns the positional and named
+    /// arguments and the span of `)`.
+    fn parse_call_args(
+        &mut self,
+    ) -> Result<(Vec<Expr>, Vec<NamedArg>, miette::SourceSpan), ParseError> {
         self.bump(); // consume LParen
+        self.depth += 1;
+        let result = self.parse_call_args_rest();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_call_args_rest(
+        &mut self,
+    ) -> Result<(Vec<Expr>, Vec<NamedArg>, miette::SourceSpan), ParseError> {
         let mut args = Vec::new();
+        let mut named = Vec::new();
         if !self.matches(&TokenKind::RParen) {
             loop {
-                args.push(self.parse_expr()?);
+                if matches!(self.peek_kind(), TokenKind::Ident(_))
+                    && matches!(self.peek_nth_kind(1), TokenKind::Colon)
+                {
+                    let (name, name_span) = self.expect_ident()?;
+                    self.bump(); // consume Colon
+                    let value = self.parse_expr()?;
+                    let span = covering(&name_span, &value.span());
+                    named.push(NamedArg {
+                        name,
+                        name_span,
+                        value,
+                        span,
+                    });
+                } else if let Some(last) = named.last() {
+                    return Err(ParseError::new(
+                        format!("positional argument after named argument `{}`", last.name),
+                        self.peek().span,
+                    ));
+                } else {
+                    args.push(self.parse_expr()?);
+                }
                 if self.matches(&TokenKind::Comma) {
                     self.bump();
                     continue;
@@ -286,15 +953,17 @@ impl Parser {
             }
         }
         let end = self.expect(TokenKind::RParen, "')' to close function call")?;
-        Ok(Expr::Call {
-            name,
-            name_span,
-            args,
-            span: covering(&name_span, &end.span),
-        })
+        Ok((args, named, end.span))
     }
 
     fn parse_list(&mut self, start_span: miette::SourceSpan) -> Result<Expr, ParseError> {
+        self.depth += 1;
+        let result = self.parse_list_rest(start_span);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_list_rest(&mut self, start_span: miette::SourceSpan) -> Result<Expr, ParseError> {
         let mut items = Vec::new();
         if self.matches(&TokenKind::RBracket) {
             let end = self.bump();
@@ -335,6 +1004,25 @@ impl Parser {
         std::mem::discriminant(self.peek_kind()) == std::mem::discriminant(kind)
     }
 
+    fn matches_ident(&self, word: &str) -> bool {
+        matches!(self.peek_kind(), TokenKind::Ident(name) if name == word)
+    }
+
+    fn peek_nth_kind(&self, n: usize) -> &TokenKind {
+        self.tokens
+            .get(self.pos + n)
+            .map(|token| &token.kind)
+            .unwrap_or(&TokenKind::Eof)
+    }
+
+    /// Whether the next token starts right where the previous one ended.
+    fn touches_previous(&self) -> bool {
+        let Some(prev) = self.pos.checked_sub(1).and_then(|i| self.tokens.get(i)) else {
+            return false;
+        };
+        prev.span.offset() + prev.span.len() == self.peek().span.offset()
+    }
+
     fn peek(&self) -> &Token {
         self.tokens
             .get(self.pos)
@@ -360,33 +1048,64 @@ impl Parser {
     fn expected_error(&self, expected: &str) -> ParseError {
         ParseError::new(
             format!("expected {expected}, found {}", token_label(self.peek_kind())),
-            self.peek().span.clone(),
+            self.peek().span,
         )
     }
 }
 
+/// The value of an integer literal with the given magnitude, negated when a
+/// prefix `-` belongs to it.
+fn int_literal(magnitude: u64, negative: bool, literal: Span) -> Result<i64, ParseError> {
+    let value = if negative {
+        0i64.checked_sub_unsigned(magnitude)
+    } else {
+        i64::try_from(magnitude).ok()
+    };
+    value.ok_or_else(|| int_out_of_range(&magnitude.to_string(), literal))
+}
+
 fn token_label(kind: &TokenKind) -> &'static str {
     match kind {
         TokenKind::Let => "`let`",
         TokenKind::Mut => "`mut`",
         TokenKind::Set => "`set`",
         TokenKind::Fn => "`fn`",
+        TokenKind::Def => "`def`",
         TokenKind::Match => "`match`",
         TokenKind::With => "`with`",
         TokenKind::When => "`when`",
         TokenKind::Io => "`io`",
+        TokenKind::For => "`for`",
+        TokenKind::Try => "`try`",
+        TokenKind::Import => "`import`",
+        TokenKind::Export => "`export`",
+        TokenKind::Assert => "`assert`",
         TokenKind::Do => "`do`",
         TokenKind::End => "`end`",
         TokenKind::Ident(_) => "identifier",
+        TokenKind::Bool(_) => "boolean literal",
         TokenKind::Int(_) => "integer literal",
+        TokenKind::Duration(_) => "duration literal",
+        TokenKind::Size(_) => "byte-size literal",
         TokenKind::String(_) => "string literal",
+        TokenKind::Path(_) => "path literal",
         TokenKind::PipeGreater => "`|>`",
         TokenKind::Arrow => "`->`",
+        TokenKind::Minus => "`-`",
+        TokenKind::Plus => "`+`",
+        TokenKind::Dot => "`.`",
+        TokenKind::DotDot => "`..`",
         TokenKind::LeftArrow => "`<-`",
         TokenKind::Equal => "`=`",
         TokenKind::EqualEqual => "`==`",
         TokenKind::NotEqual => "`!=`",
+        TokenKind::Less => "`<`",
+        TokenKind::LessEqual => "`<=`",
+        TokenKind::Greater => "`>`",
+        TokenKind::GreaterEqual => "`>=`",
         TokenKind::Comma => "`,`",
+        TokenKind::Semicolon => "`;`",
+        TokenKind::Colon => "`:`",
         TokenKind::LParen => "`(`",
         TokenKind::RParen => "`)`",
         TokenKind::LBracket => "`[`",
@@ -0,0 +1,82 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use regex::{Captures, Regex};
+
+use crate::value::Value;
+
+/// Patterns kept before the cache starts over.
+const CACHE_LIMIT: usize = 64;
+
+thread_local! {
+    /// Compiled patterns, so a `regex_*` call inside a loop does not
+    /// recompile the same pattern for every line.
+    static CACHE: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
+}
+
+/// Compiles `pattern`, or explains why it is not a valid regular expression.
+pub fn compile(pattern: &str) -> Result<Regex, String> {
+    if let Some(re) = CACHE.with(|cache| cache.borrow().get(pattern).cloned()) {
+        return Ok(re);
+    }
+    let re = Regex::new(pattern).map_err(|err| describe(&err))?;
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(pattern.to_string(), re.clone());
+    });
+    Ok(re)
+}
+
+/// The last line of the regex crate's report (`unclosed group`, ...); the
+/// lines before it repeat the pattern, which the diagnostic already shows.
+fn describe(err: &regex::Error) -> String {
+    let text = err.to_string();
+    let last = text.lines().last().unwrap_or_default();
+    last.strip_prefix("error: ").unwrap_or(last).to_string()
+}
+
+/// One match: a record of the named groups (`null` for a group that did not
+/// take part) when the pattern has any, otherwise the matched text.
+fn capture_value(re: &Regex, caps: &Captures) -> Value {
+    let mut names = re.capture_names().flatten().peekable();
+    if names.peek().is_none() {
+        return Value::String(caps[0].to_string());
+    }
+    let fields: BTreeMap<String, Value> = names
+        .map(|name| {
+            let value = caps
+                .name(name)
+                .map_or(Value::Null, |m| Value::String(m.as_str().to_string()));
+            (name.to_string(), value)
+        })
+        .collect();
+    Value::Record(fields)
+}
+
+/// The first match in `text`, or `null`.
+pub fn first_match(re: &Regex, text: &str) -> Value {
+    re.captures(text)
+        .map_or(Value::Null, |caps| capture_value(re, &caps))
+}
+
+/// Every non-overlapping match, left to right.
+pub fn find_all(re: &Regex, text: &str) -> Value {
+    Value::List(
+        re.captures_iter(text)
+            .map(|caps| capture_value(re, &caps))
+            .collect(),
+    )
+}
+
+/// Replaces every match; `$1` and `$name` in `replacement` refer to groups.
+pub fn replace(re: &Regex, text: &str, replacement: &str) -> String {
+    re.replace_all(text, replacement).into_owned()
+}
+
+/// Pieces between matches, keeping empty ones.
+pub fn split(re: &Regex, text: &str) -> Vec<String> {
+    re.split(text).map(str::to_string).collect()
+}
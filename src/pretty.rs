@@ -0,0 +1,265 @@
+use std::borrow::Cow;
+use std::io::IsTerminal;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::table::Table;
+use crate::value::{Value, record_key};
+
+/// Layout settings for [`pretty`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrettyOptions {
+    /// Columns a value may take on one line before it is broken up.
+    pub width: usize,
+    /// Spaces per nesting level.
+    pub indent: usize,
+    /// Longest string shown in full, in characters; `None` shows everything.
+    pub max_chars: Option<usize>,
+    /// Most list items or record fields shown; `None` shows everything.
+    pub max_items: Option<usize>,
+    /// Print multi-line strings (e.g. a command's stderr) as indented raw text
+    /// instead of one escaped line.
+    pub verbatim: bool,
+    /// Show a list of records sharing the same fields as a table.
+    pub tables: bool,
+    /// Style table headers with ANSI escapes.
+    pub color: bool,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            indent: 2,
+            max_chars: Some(200),
+            max_items: Some(50),
+            verbatim: false,
+            tables: true,
+            color: false,
+        }
+    }
+}
+
+impl PrettyOptions {
+    /// Defaults, with color when stdout is a terminal and `NO_COLOR` is unset.
+    pub fn for_terminal() -> Self {
+        Self {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ..Self::default()
+        }
+    }
+
+    /// Applies a REPL `:set <key> <value>` command, e.g. `items 20` or `verbatim on`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let limit = || match value {
+            "off" => Ok(None),
+            n => n
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("expected a number or `off`, found `{n}`")),
+        };
+        let flag = || match value {
+            "on" => Ok(true),
+            "off" => Ok(false),
+            _ => Err(format!("expected `on` or `off`, found `{value}`")),
+        };
+        let number = || {
+            value
+                .parse()
+                .map_err(|_| format!("expected a number, found `{value}`"))
+        };
+        match key {
+            "width" => self.width = number()?,
+            "indent" => self.indent = number()?,
+            "chars" => self.max_chars = limit()?,
+            "items" => self.max_items = limit()?,
+            "verbatim" => self.verbatim = flag()?,
+            "tables" => self.tables = flag()?,
+            "color" => self.color = flag()?,
+            _ => {
+                return Err(format!(
+                    "unknown setting `{key}` (expected width, indent, chars, items, verbatim, tables or color)"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders `value` for people: on one line when it fits in `options.width`,
+/// otherwise with one list item or record field per indented line.
+pub fn pretty(value: &Value, options: &PrettyOptions) -> String {
+    let mut out = String::new();
+    Printer { options }.write(&mut out, value, 0);
+    out
+}
+
+/// The table `value` is shown as at the top level, if any: a `table()` result,
+/// or a list of records with the same fields when `options.tables` is on.
+pub fn tabular<'v>(value: &'v Value, options: &PrettyOptions) -> Option<Cow<'v, Table>> {
+    match value {
+        Value::Table(table) => Some(Cow::Borrowed(table)),
+        Value::List(items) if options.tables => Table::infer(items).map(Cow::Owned),
+        _ => None,
+    }
+}
+
+struct Printer<'a> {
+    options: &'a PrettyOptions,
+}
+
+impl Printer<'_> {
+    fn write(&self, out: &mut String, value: &Value, level: usize) {
+        let table = match value {
+            Value::Table(table) => Some(Cow::Borrowed(table)),
+            _ if level == 0 => tabular(value, self.options),
+            _ => None,
+        };
+        if let Some(table) = table {
+            let text = table.render(self.options.max_items, self.options.color);
+            for (i, line) in text.lines().enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                    self.pad(out, level);
+                }
+                out.push_str(line);
+            }
+            return;
+        }
+        let flat = self.flat(value);
+        let column = level * self.options.indent;
+        if column + flat.width() <= self.options.width && !self.needs_block(value) {
+            out.push_str(&flat);
+            return;
+        }
+        match value {
+            Value::List(items) => {
+                let (shown, hidden) = self.split(items.len());
+                out.push_str("[\n");
+                for item in &items[..shown] {
+                    self.pad(out, level + 1);
+                    self.write(out, item, level + 1);
+                    out.push_str(",\n");
+                }
+                self.write_hidden(out, hidden, level + 1);
+                self.pad(out, level);
+                out.push(']');
+            }
+            Value::Record(fields) => {
+                let (shown, hidden) = self.split(fields.len());
+                out.push_str("{\n");
+                for (key, field) in fields.iter().take(shown) {
+                    self.pad(out, level + 1);
+                    out.push_str(&record_key(key));
+                    out.push_str(": ");
+                    self.write(out, field, level + 1);
+                    out.push_str(",\n");
+                }
+                self.write_hidden(out, hidden, level + 1);
+                self.pad(out, level);
+                out.push('}');
+            }
+            Value::Ok(inner) | Value::Err(inner) => {
+                out.push_str(if matches!(value, Value::Ok(_)) {
+                    "Ok("
+                } else {
+                    "Err("
+                });
+                self.write(out, inner, level);
+                out.push(')');
+            }
+            Value::String(s) if self.options.verbatim && s.contains('\n') => {
+                let (text, hidden) = self.truncate(s);
+                out.push_str("\"\"\"\n");
+                for line in text.lines() {
+                    self.pad(out, level + 1);
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                self.pad(out, level + 1);
+                out.push_str("\"\"\"");
+                if hidden > 0 {
+                    out.push_str(&format!("…(+{hidden} chars)"));
+                }
+            }
+            _ => out.push_str(&flat),
+        }
+    }
+
+    /// One-line form with truncation applied.
+    fn flat(&self, value: &Value) -> String {
+        match value {
+            Value::String(s) => {
+                let (text, hidden) = self.truncate(s);
+                if hidden > 0 {
+                    format!("{text:?}…(+{hidden} chars)")
+                } else {
+                    format!("{text:?}")
+                }
+            }
+            Value::List(items) => {
+                let (shown, hidden) = self.split(items.len());
+                let mut parts: Vec<String> = items[..shown].iter().map(|v| self.flat(v)).collect();
+                parts.extend(hidden_note(hidden));
+                format!("[{}]", parts.join(", "))
+            }
+            Value::Record(fields) => {
+                let (shown, hidden) = self.split(fields.len());
+                let mut parts: Vec<String> = fields
+                    .iter()
+                    .take(shown)
+                    .map(|(key, v)| format!("{}: {}", record_key(key), self.flat(v)))
+                    .collect();
+                parts.extend(hidden_note(hidden));
+                format!("{{{}}}", parts.join(", "))
+            }
+            Value::Ok(inner) => format!("Ok({})", self.flat(inner)),
+            Value::Err(inner) => format!("Err({})", self.flat(inner)),
+            other => other.to_string(),
+        }
+    }
+
+    /// Whether a verbatim multi-line string forces the block layout.
+    fn needs_block(&self, value: &Value) -> bool {
+        match value {
+            Value::String(s) => self.options.verbatim && s.contains('\n'),
+            Value::List(items) => items.iter().any(|v| self.needs_block(v)),
+            Value::Record(fields) => fields.values().any(|v| self.needs_block(v)),
+            Value::Ok(inner) | Value::Err(inner) => self.needs_block(inner),
+            _ => false,
+        }
+    }
+
+    /// How many of `len` items to show, and how many are left out.
+    fn split(&self, len: usize) -> (usize, usize) {
+        let shown = self.options.max_items.map_or(len, |max| len.min(max));
+        (shown, len - shown)
+    }
+
+    /// The first `max_chars` characters of `s`, and how many are left out.
+    fn truncate<'s>(&self, s: &'s str) -> (&'s str, usize) {
+        match self.options.max_chars {
+            Some(max) => match s.char_indices().nth(max) {
+                Some((cut, _)) => (&s[..cut], s[cut..].chars().count()),
+                None => (s, 0),
+            },
+            None => (s, 0),
+        }
+    }
+
+    fn write_hidden(&self, out: &mut String, hidden: usize, level: usize) {
+        if let Some(note) = hidden_note(hidden) {
+            self.pad(out, level);
+            out.push_str(&note);
+            out.push('\n');
+        }
+    }
+
+    fn pad(&self, out: &mut String, level: usize) {
+        out.extend(std::iter::repeat_n(' ', level * self.options.indent));
+    }
+}
+
+fn hidden_note(hidden: usize) -> Option<String> {
+    (hidden > 0).then(|| format!("… {hidden} more"))
+}
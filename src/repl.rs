@@ -2,14 +2,47 @@ use miette::{IntoDiagnostic, NamedSource, Report, Result};
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
 
-use crate::eval::{eval_program, EvalEnv};
+use std::path::Path;
+
+use crate::ast::Program;
+use crate::error::AmbiguousMethodCallWarning;
+use crate::eval::{eval_program_with_imports, EvalEnv};
+use crate::module::ModuleLoader;
 use crate::parser::parse_program;
+use crate::pretty::{pretty, tabular, PrettyOptions};
 use crate::resolver::Resolver;
+use crate::typeck::TypeChecker;
+
+/// Resolves and type checks one line against the session. Both passes run on
+/// copies that replace the session state only when the whole line is
+/// accepted, so a rejected line leaves behind no names that were never
+/// evaluated.
+pub fn check_line(
+    resolver: &mut Resolver,
+    checker: &mut TypeChecker,
+    program: &Program,
+    mut on_warning: impl FnMut(AmbiguousMethodCallWarning),
+) -> Result<()> {
+    let mut staged_resolver = resolver.clone();
+    let resolved = staged_resolver.resolve_program(program);
+    for warning in staged_resolver.take_warnings() {
+        on_warning(warning);
+    }
+    resolved?;
+    let mut staged_checker = checker.clone();
+    staged_checker.check_program(program)?;
+    *resolver = staged_resolver;
+    *checker = staged_checker;
+    Ok(())
+}
 
 pub async fn run() -> Result<()> {
     let mut rl = DefaultEditor::new().into_diagnostic()?;
     let mut resolver = Resolver::new();
+    let mut checker = TypeChecker::new();
+    let mut loader = ModuleLoader::new();
     let mut env: EvalEnv = EvalEnv::new();
+    let mut display = PrettyOptions::for_terminal();
     loop {
         match rl.readline("mictylish> ") {
             Ok(line) => {
@@ -21,19 +54,58 @@ pub async fn run() -> Result<()> {
                     break;
                 }
                 let _ = rl.add_history_entry(input);
+                if let Some(setting) = input.strip_prefix(":set") {
+                    let mut words = setting.split_whitespace();
+                    match (words.next(), words.next(), words.next()) {
+                        (Some(key), Some(value), None) => {
+                            if let Err(message) = display.set(key, value) {
+                                eprintln!(":set: {message}");
+                            }
+                        }
+                        _ => eprintln!(
+                            ":set: usage is `:set <width|indent|chars|items|verbatim|tables|color> <value>`"
+                        ),
+                    }
+                    continue;
+                }
+                // One line per input, so statements on it may follow each other.
                 match parse_program(input) {
                     Ok(program) => {
-                        if let Err(err) = resolver.resolve_program(&program) {
+                        let imports = match loader.link(&program, Path::new("."), &mut resolver) {
+                            Ok(imports) => imports,
+                            Err(err) => {
+                                eprintln!(
+                                    "{:?}",
+                                    Report::new(err).with_source_code(NamedSource::new(
+                                        "repl",
+                                        input.to_string(),
+                                    ))
+                                );
+                                continue;
+                            }
+                        };
+                        let checked = check_line(&mut resolver, &mut checker, &program, |warning| {
                             eprintln!(
                                 "{:?}",
-                                Report::new(err)
+                                Report::new(warning)
                                     .with_source_code(NamedSource::new("repl", input.to_string()))
                             );
+                        });
+                        if let Err(err) = checked {
+                            eprintln!(
+                                "{:?}",
+                                err.with_source_code(NamedSource::new("repl", input.to_string()))
+                            );
                         } else {
-                            match eval_program(&mut env, &program) {
+                            match eval_program_with_imports(&mut env, &program, &imports) {
                                 Ok(bindings) => {
                                     for (name, value) in bindings {
-                                        println!("{name} = {value}");
+                                        let text = pretty(&value, &display);
+                                        if tabular(&value, &display).is_some() {
+                                            println!("{name} =\n{text}");
+                                        } else {
+                                            println!("{name} = {text}");
+                                        }
                                     }
                                 }
                                 Err(err) => {
@@ -1,27 +1,114 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::ast::{Expr, MatchArm, Pattern, Program, Stmt, WithBinding};
+use miette::LabeledSpan;
+
+use crate::ast::{Expr, MatchArm, NamedArg, Pattern, Program, Stmt, WithBinding};
+use crate::builtin::{self, Signature};
+use crate::effect::{Applied, Closure, Effect, Latent};
 use crate::error::{
-    InvalidPipeRhsError, NameError, ResolveError, SetNotMutableError, SetUndefinedError,
-    UndefinedNameError,
+    AmbiguousMethodCallWarning, DuplicateNamedArgError, InvalidPipeRhsError, InvalidRegexError,
+    IoOutsideBoundaryError, LetElseBindingError, NameError, NotExportedError, RefutableLetError,
+    ResolveError, SetNotMutableError, SetOutsideLoopError, SetUndefinedError, UndefinedNameError,
+    UnknownModuleError, UnknownNamedArgError,
 };
+use crate::module::ModuleInterface;
+use crate::pattern;
 use crate::span::Span;
 
 #[derive(Debug, Clone)]
-struct Binding {
+pub(crate) struct Binding {
     span: Span,
     mutable: bool,
+    /// What applying the bound value does, if it is or holds a function.
+    latent: Latent,
+    /// Set when the name is an `import` alias.
+    module: Option<ModuleInterface>,
+}
+
+/// Effects inferred for one expression.
+#[derive(Debug, Clone, Default)]
+struct ExprEffect {
+    /// Effect of evaluating the expression itself.
+    perform: Effect,
+    /// What applying the resulting value does, if it is or holds a function.
+    latent: Latent,
+}
+
+/// What `receiver.name(args)` calls, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MethodTarget {
+    /// `alias.name(args)` where `name` is exported by the imported module.
+    ModuleMember,
+    /// `name(receiver)(args...)` for a function bound in scope.
+    Local,
+    /// `name(receiver, args...)` for a builtin.
+    Builtin,
+}
+
+impl MethodTarget {
+    fn describe(self) -> &'static str {
+        match self {
+            MethodTarget::ModuleMember => "module member",
+            MethodTarget::Local => "function in scope",
+            MethodTarget::Builtin => "builtin",
+        }
+    }
+}
+
+impl ExprEffect {
+    fn join(self, other: ExprEffect) -> Self {
+        Self {
+            perform: self.perform.join(other.perform),
+            latent: self.latent.join(other.latent),
+        }
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Resolver {
     scopes: Vec<HashMap<String, Binding>>,
+    /// Interfaces of loaded modules, keyed by import path.
+    modules: HashMap<String, ModuleInterface>,
+    /// Names listed in `export` statements.
+    exports: BTreeMap<String, Span>,
+    warnings: Vec<AmbiguousMethodCallWarning>,
+    /// How many closure bodies are being checked for an application.
+    depth: usize,
 }
 
+/// How deeply applications are followed before the result is assumed to
+/// perform io, e.g. for a function applied to itself.
+const MAX_APPLY_DEPTH: usize = 32;
+
 impl Resolver {
     pub fn new() -> Self {
         Self {
             scopes: vec![HashMap::new()],
+            modules: HashMap::new(),
+            exports: BTreeMap::new(),
+            warnings: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Drains the non-fatal diagnostics collected so far.
+    pub fn take_warnings(&mut self) -> Vec<AmbiguousMethodCallWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Makes a loaded module available to `import` statements using `path`.
+    pub fn register_module(&mut self, path: impl Into<String>, interface: ModuleInterface) {
+        self.modules.insert(path.into(), interface);
+    }
+
+    /// Exported names of the resolved program, with their latent effects.
+    pub fn interface(&self) -> ModuleInterface {
+        ModuleInterface {
+            exports: self
+                .exports
+                .keys()
+                .map(|name| (name.clone(), self.latent_of(name).imported()))
+                .collect(),
         }
     }
 
@@ -63,7 +150,15 @@ impl Resolver {
             });
         }
         if let Some(current) = self.scopes.last_mut() {
-            current.insert(name, Binding { span, mutable });
+            current.insert(
+                name,
+                Binding {
+                    span,
+                    mutable,
+                    latent: Latent::default(),
+                    module: None,
+                },
+            );
         }
         Ok(())
     }
@@ -76,15 +171,137 @@ impl Resolver {
         self.scopes.iter().rev().find_map(|scope| scope.get(name))
     }
 
+    fn lookup_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+
+    fn set_latent(&mut self, name: &str, latent: Latent) {
+        if let Some(binding) = self.lookup_mut(name) {
+            binding.latent = latent;
+        }
+    }
+
+    fn latent_of(&self, name: &str) -> Latent {
+        self.lookup(name)
+            .map(|binding| binding.latent.clone())
+            .unwrap_or_default()
+    }
+
     fn is_pipe_prelude_target(name: &str) -> bool {
         matches!(name, "identity" | "id")
     }
 
-    fn check_pipe_rhs(&self, expr: &Expr) -> Result<(), ResolveError> {
+    fn is_io_builtin(name: &str) -> bool {
+        builtin::signature(name).is_some_and(|sig| sig.io)
+    }
+
+    fn is_builtin(name: &str) -> bool {
+        builtin::signature(name).is_some()
+    }
+
+    /// Builtins that apply their function arguments.
+    fn is_higher_order_builtin(name: &str) -> bool {
+        builtin::signature(name).is_some_and(|sig| sig.higher_order)
+    }
+
+    /// Builtins that run the `map` stages of a seq argument.
+    fn is_forcing_builtin(name: &str) -> bool {
+        builtin::signature(name).is_some_and(|sig| sig.forces)
+    }
+
+    /// Checks a builtin call against its signature. Calls to unknown names are
+    /// left to evaluation.
+    fn check_call_args(
+        name: &str,
+        args: &[Expr],
+        named: &[NamedArg],
+        span: Span,
+    ) -> Result<(), ResolveError> {
+        let Some(sig) = builtin::signature(name) else {
+            return Ok(());
+        };
+        Self::check_named_args(name, sig, named)?;
+        let required = sig.positional.len();
+        let arity_ok = if sig.variadic {
+            args.len() >= required
+        } else {
+            args.len() == required
+        };
+        if !arity_ok {
+            return Err(sig.arity_error(args.len(), span).into());
+        }
+        if let Some(index) = sig.literal_regex_arg
+            && let Some(Expr::String(pattern, span)) = args.get(index)
+            && let Err(message) = pattern::compile(pattern)
+        {
+            return Err(InvalidRegexError {
+                message,
+                span: *span,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn check_named_args(
+        callee: &str,
+        sig: &Signature,
+        named: &[NamedArg],
+    ) -> Result<(), ResolveError> {
+        let mut seen: HashMap<&str, Span> = HashMap::new();
+        for arg in named {
+            if sig.named_param(&arg.name).is_none() {
+                return Err(UnknownNamedArgError {
+                    callee: callee.to_string(),
+                    name: arg.name.clone(),
+                    hint: format!("signature: {}", sig.display()),
+                    span: arg.name_span,
+                }
+                .into());
+            }
+            if let Some(first) = seen.insert(&arg.name, arg.name_span) {
+                return Err(DuplicateNamedArgError {
+                    name: arg.name.clone(),
+                    first,
+                    span: arg.name_span,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the effect of applying the pipeline target to an input whose
+    /// latent effect is `input`, and the latent effect of the result.
+    fn check_pipe_rhs(
+        &mut self,
+        expr: &Expr,
+        input: &Latent,
+    ) -> Result<(Effect, Latent), ResolveError> {
         match expr {
+            Expr::Field {
+                target,
+                name,
+                span,
+                ..
+            } => {
+                let latent = self.infer_expr(expr)?.latent;
+                let qualified = match target.as_ref() {
+                    Expr::Var(module, _) => format!("{module}.{name}"),
+                    _ => name.clone(),
+                };
+                let (effect, result) = self.apply(&latent, input)?;
+                Ok((effect.through(qualified, *span), result))
+            }
             Expr::Var(name, span) => {
-                if Self::is_pipe_prelude_target(name) || self.is_defined(name) {
-                    Ok(())
+                if self.is_defined(name) {
+                    let (effect, result) = self.apply(&self.latent_of(name), input)?;
+                    Ok((effect.through(name.clone(), *span), result))
+                } else if Self::is_pipe_prelude_target(name) {
+                    Ok((Effect::Pure, input.clone()))
                 } else {
                     Err(UndefinedNameError {
                         name: name.clone(),
@@ -102,25 +319,135 @@ impl Resolver {
         param: &str,
         param_span: Span,
         body: &Expr,
-    ) -> Result<(), ResolveError> {
+    ) -> Result<ExprEffect, ResolveError> {
+        let closure = Closure {
+            param: param.to_string(),
+            param_span,
+            body: body.clone(),
+            scopes: self.scopes.clone(),
+            imported: false,
+        };
+        // Checks the names in the body; its effect depends on the argument, so
+        // it is worked out again wherever the function is applied.
         self.push_scope();
-        let result = (|| -> Result<(), ResolveError> {
+        let result = (|| -> Result<ExprEffect, ResolveError> {
             self.define(param.to_string(), param_span)?;
-            self.check_expr(body)?;
-            Ok(())
+            self.infer_expr(body)
         })();
         self.pop_scope();
-        result
+        result?;
+        // Creating a function is pure; its body's effect happens on application.
+        Ok(ExprEffect {
+            perform: Effect::Pure,
+            latent: Latent::closure(closure),
+        })
+    }
+
+    /// Returns the effect of applying a value whose latent effect is `callee`
+    /// to an argument whose latent effect is `arg`, and the latent effect of
+    /// the result.
+    fn apply(&mut self, callee: &Latent, arg: &Latent) -> Result<(Effect, Latent), ResolveError> {
+        let mut effect = Effect::Pure;
+        let mut result = Latent::default();
+        for applied in callee.iter() {
+            let (performed, returned) = match applied {
+                Applied::Closure(closure) if closure.imported => {
+                    // Steps inside the module cannot be shown against this
+                    // source, so the chain ends at the call.
+                    let (effect, result) = self.apply_closure(closure, arg)?;
+                    let effect = match effect {
+                        Effect::Pure => Effect::Pure,
+                        Effect::Io(_) => Effect::Io(Vec::new()),
+                    };
+                    (effect, result.imported())
+                }
+                Applied::Closure(closure) => self.apply_closure(closure, arg)?,
+                Applied::Unknown => (Effect::Io(Vec::new()), Latent::unknown()),
+            };
+            effect = effect.join(performed);
+            result = result.join(returned);
+        }
+        Ok((effect, result))
+    }
+
+    /// Checks a closure's body in its defining scope, with the parameter bound
+    /// to the argument's latent effect.
+    fn apply_closure(
+        &mut self,
+        closure: &Closure,
+        arg: &Latent,
+    ) -> Result<(Effect, Latent), ResolveError> {
+        if self.depth >= MAX_APPLY_DEPTH {
+            return Ok((Effect::Io(Vec::new()), Latent::unknown()));
+        }
+        let caller = std::mem::replace(&mut self.scopes, closure.scopes.clone());
+        let warnings = self.warnings.len();
+        self.depth += 1;
+        self.push_scope();
+        let result = (|| -> Result<ExprEffect, ResolveError> {
+            self.define(closure.param.clone(), closure.param_span)?;
+            self.set_latent(&closure.param, arg.clone());
+            self.infer_expr(&closure.body)
+        })();
+        self.depth -= 1;
+        self.scopes = caller;
+        // The body was already checked where it was written.
+        self.warnings.truncate(warnings);
+        let body = result?;
+        Ok((body.perform, body.latent))
     }
 
-    /// Checks that every [`Expr::Var`] refers to a name already in scope.
+    /// Checks that every [`Expr::Var`] refers to a name already in scope, and
+    /// that no side effect escapes an `io do ... end` boundary.
     /// Pipeline RHS allows `identity` / `id` without a prior `let`.
     pub fn check_expr(&mut self, expr: &Expr) -> Result<(), ResolveError> {
+        let effect = self.infer_stmt_expr(expr)?;
+        Self::require_pure(effect.perform)
+    }
+
+    fn require_pure(effect: Effect) -> Result<(), ResolveError> {
+        match effect {
+            Effect::Pure => Ok(()),
+            Effect::Io(chain) => {
+                let first = chain.first().expect("io effect has at least one step");
+                let via = chain
+                    .windows(2)
+                    .map(|pair| {
+                        LabeledSpan::new_with_span(
+                            Some(format!("'{}' calls '{}' here", pair[0].name, pair[1].name)),
+                            pair[1].span,
+                        )
+                    })
+                    .collect();
+                Err(IoOutsideBoundaryError {
+                    name: first.name.clone(),
+                    chain: chain
+                        .iter()
+                        .map(|step| step.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" -> "),
+                    span: first.span,
+                    via,
+                }
+                .into())
+            }
+        }
+    }
+
+    fn infer_expr(&mut self, expr: &Expr) -> Result<ExprEffect, ResolveError> {
         match expr {
-            Expr::String(_, _) | Expr::Int(_, _) => Ok(()),
+            Expr::String(_, _)
+            | Expr::Path(_, _)
+            | Expr::Bool(_, _)
+            | Expr::Int(_, _)
+            | Expr::Duration(_, _)
+            | Expr::Size(_, _) => Ok(ExprEffect::default()),
             Expr::Var(name, span) => {
                 if self.is_defined(name) {
-                    Ok(())
+                    Ok(ExprEffect {
+                        perform: Effect::Pure,
+                        latent: self.latent_of(name),
+                    })
                 } else {
                     Err(UndefinedNameError {
                         name: name.clone(),
@@ -130,10 +457,11 @@ impl Resolver {
                 }
             }
             Expr::List(items, _) => {
+                let mut effect = ExprEffect::default();
                 for item in items {
-                    self.check_expr(item)?;
+                    effect = effect.join(self.infer_expr(item)?);
                 }
-                Ok(())
+                Ok(effect)
             }
             Expr::Fn {
                 param,
@@ -141,24 +469,95 @@ impl Resolver {
                 body,
                 ..
             } => self.check_fn_expr(param, *param_span, body),
-            Expr::Io { body, .. } => self.check_expr(body),
-            Expr::Call { args, .. } => {
+            Expr::Io { body, ensure, .. } => {
+                // The boundary discharges whatever the body and cleanup perform.
+                let body = self.infer_expr(body)?;
+                if let Some(ensure) = ensure {
+                    self.infer_expr(ensure)?;
+                }
+                Ok(ExprEffect {
+                    perform: Effect::Pure,
+                    latent: body.latent,
+                })
+            }
+            Expr::Call {
+                name,
+                args,
+                named,
+                span,
+                ..
+            } => {
+                Self::check_call_args(name, args, named, *span)?;
+                let mut perform = if Self::is_io_builtin(name) {
+                    Effect::io(name.clone(), *span)
+                } else {
+                    Effect::Pure
+                };
+                // Builtins such as `get` and `take` hand back parts of their
+                // arguments, so the result may hold any function passed in.
+                // A seq built by `map` carries the mapped function's effect the
+                // same way, and performs it wherever it is forced.
+                for arg in named {
+                    perform = perform.join(self.infer_expr(&arg.value)?.perform);
+                }
+                let mut latents = Vec::with_capacity(args.len());
                 for arg in args {
-                    self.check_expr(arg)?;
+                    let effect = self.infer_expr(arg)?;
+                    perform = perform.join(effect.perform);
+                    latents.push(effect.latent);
                 }
-                Ok(())
+                let mut latent = latents
+                    .iter()
+                    .cloned()
+                    .fold(Latent::default(), Latent::join);
+                for (i, arg) in args.iter().enumerate() {
+                    // A higher-order builtin applies a function argument to items
+                    // of the others; a forced seq applies its stages to integers.
+                    let input = if Self::is_higher_order_builtin(name) {
+                        latents
+                            .iter()
+                            .enumerate()
+                            .filter(|(j, _)| *j != i)
+                            .fold(Latent::default(), |acc, (_, l)| acc.join(l.clone()))
+                    } else if Self::is_forcing_builtin(name) {
+                        Latent::default()
+                    } else {
+                        continue;
+                    };
+                    let (applied, result) = self.apply(&latents[i], &input)?;
+                    let applied = match arg {
+                        Expr::Var(fname, fspan) => applied.through(fname.clone(), *fspan),
+                        _ => applied,
+                    };
+                    perform = perform.join(applied.through(name.clone(), *span));
+                    latent = latent.join(result);
+                }
+                Ok(ExprEffect { perform, latent })
             }
+            Expr::Neg { operand, .. } => Ok(ExprEffect {
+                perform: self.infer_expr(operand)?.perform,
+                latent: Latent::default(),
+            }),
             Expr::BinOp { lhs, rhs, .. } => {
-                self.check_expr(lhs)?;
-                self.check_expr(rhs)?;
-                Ok(())
+                let perform = self
+                    .infer_expr(lhs)?
+                    .perform
+                    .join(self.infer_expr(rhs)?.perform);
+                Ok(ExprEffect {
+                    perform,
+                    latent: Latent::default(),
+                })
             }
             Expr::Match { subject, arms, .. } => {
-                self.check_expr(subject)?;
+                let subject = self.infer_expr(subject)?;
+                let mut effect = ExprEffect {
+                    perform: subject.perform,
+                    latent: Latent::default(),
+                };
                 for arm in arms {
-                    self.check_match_arm(arm)?;
+                    effect = effect.join(self.check_match_arm(arm, &subject.latent)?);
                 }
-                Ok(())
+                Ok(effect)
             }
             Expr::With {
                 bindings,
@@ -167,42 +566,270 @@ impl Resolver {
                 ..
             } => self.check_with_expr(bindings, body, else_body),
             Expr::Pipe(lhs, rhs, _) => {
-                self.check_expr(lhs)?;
-                self.check_pipe_rhs(rhs)?;
-                Ok(())
+                let input = self.infer_expr(lhs)?;
+                let (applied, latent) = self.check_pipe_rhs(rhs, &input.latent)?;
+                Ok(ExprEffect {
+                    perform: input.perform.join(applied),
+                    latent,
+                })
             }
+            Expr::Range { start, end, .. } => {
+                let mut perform = self.infer_expr(start)?.perform;
+                if let Some(end) = end {
+                    perform = perform.join(self.infer_expr(end)?.perform);
+                }
+                Ok(ExprEffect {
+                    perform,
+                    latent: Latent::default(),
+                })
+            }
+            Expr::Field {
+                target,
+                name,
+                name_span,
+                ..
+            } => {
+                if let Expr::Var(alias, _) = target.as_ref()
+                    && let Some(module) = self.lookup(alias).and_then(|b| b.module.as_ref())
+                {
+                    let Some(latent) = module.exports.get(name) else {
+                        return Err(NotExportedError {
+                            module: alias.clone(),
+                            name: name.clone(),
+                            span: *name_span,
+                        }
+                        .into());
+                    };
+                    return Ok(ExprEffect {
+                        perform: Effect::Pure,
+                        latent: latent.clone(),
+                    });
+                }
+                // A field of any other record may itself be a function.
+                self.infer_expr(target)
+            }
+            Expr::MethodCall {
+                receiver,
+                name,
+                name_span,
+                args,
+                named,
+                span,
+            } => self.check_method_call(receiver, name, *name_span, args, named, *span),
+            Expr::Index { target, index, .. } => {
+                let target = self.infer_expr(target)?;
+                Ok(ExprEffect {
+                    perform: target.perform.join(self.infer_expr(index)?.perform),
+                    latent: target.latent,
+                })
+            }
+            Expr::Slice {
+                target, start, end, ..
+            } => {
+                let target = self.infer_expr(target)?;
+                let mut perform = target.perform;
+                for bound in [start, end].into_iter().flatten() {
+                    perform = perform.join(self.infer_expr(bound)?.perform);
+                }
+                Ok(ExprEffect {
+                    perform,
+                    latent: target.latent,
+                })
+            }
+            Expr::For {
+                var,
+                var_span,
+                iter,
+                guard,
+                body,
+                ..
+            } => self.check_for_expr(var, *var_span, iter, guard.as_deref(), body, false),
+            Expr::Set { span, .. } => Err(SetOutsideLoopError { span: *span }.into()),
+            Expr::Try { body, rescue, .. } => {
+                let mut effect = self.infer_expr(body)?;
+                if let Some(rescue) = rescue {
+                    self.push_scope();
+                    let handler = (|| -> Result<ExprEffect, ResolveError> {
+                        self.define(rescue.name.clone(), rescue.name_span)?;
+                        self.infer_expr(&rescue.body)
+                    })();
+                    self.pop_scope();
+                    effect = effect.join(handler?);
+                }
+                Ok(effect)
+            }
+        }
+    }
+
+    /// Picks the reading of `receiver.name(...)`, warning if several apply.
+    fn method_target(
+        &mut self,
+        receiver: &Expr,
+        name: &str,
+        name_span: Span,
+    ) -> Result<MethodTarget, ResolveError> {
+        let module = match receiver {
+            Expr::Var(alias, _) => self
+                .lookup(alias)
+                .and_then(|b| b.module.as_ref())
+                .map(|module| (alias.clone(), module.exports.contains_key(name))),
+            _ => None,
+        };
+        let candidates: Vec<MethodTarget> = [
+            (MethodTarget::ModuleMember, matches!(module, Some((_, true)))),
+            (MethodTarget::Local, self.is_defined(name)),
+            (MethodTarget::Builtin, Self::is_builtin(name)),
+        ]
+        .into_iter()
+        .filter_map(|(target, applies)| applies.then_some(target))
+        .collect();
+        match candidates.as_slice() {
+            [] => Err(match module {
+                Some((alias, _)) => NotExportedError {
+                    module: alias,
+                    name: name.to_string(),
+                    span: name_span,
+                }
+                .into(),
+                None => UndefinedNameError {
+                    name: name.to_string(),
+                    span: name_span,
+                }
+                .into(),
+            }),
+            [picked, rest @ ..] => {
+                if let Some(other) = rest.first() {
+                    self.warnings.push(AmbiguousMethodCallWarning {
+                        name: name.to_string(),
+                        picked: picked.describe().to_string(),
+                        other: other.describe().to_string(),
+                        span: name_span,
+                    });
+                }
+                Ok(*picked)
+            }
+        }
+    }
+
+    fn check_method_call(
+        &mut self,
+        receiver: &Expr,
+        name: &str,
+        name_span: Span,
+        args: &[Expr],
+        named: &[NamedArg],
+        span: Span,
+    ) -> Result<ExprEffect, ResolveError> {
+        let target = self.method_target(receiver, name, name_span)?;
+        if target != MethodTarget::Builtin
+            && let Some(arg) = named.first()
+        {
+            return Err(UnknownNamedArgError {
+                callee: name.to_string(),
+                name: arg.name.clone(),
+                hint: "only builtins accept named arguments".to_string(),
+                span: arg.name_span,
+            }
+            .into());
+        }
+        let (applied, callee) = match target {
+            MethodTarget::Builtin => {
+                let mut call_args = vec![receiver.clone()];
+                call_args.extend(args.iter().cloned());
+                return self.infer_expr(&Expr::Call {
+                    name: name.to_string(),
+                    name_span,
+                    args: call_args,
+                    named: named.to_vec(),
+                    span,
+                });
+            }
+            MethodTarget::ModuleMember => {
+                let Expr::Var(alias, _) = receiver else {
+                    unreachable!("module members are only reached through an alias")
+                };
+                let field = Expr::Field {
+                    target: Box::new(receiver.clone()),
+                    name: name.to_string(),
+                    name_span,
+                    span,
+                };
+                (format!("{alias}.{name}"), self.infer_expr(&field)?.latent)
+            }
+            MethodTarget::Local => (name.to_string(), self.latent_of(name)),
+        };
+        // `alias.name(a, b)` applies the member to each argument in turn, and
+        // `x.name(a, b)` applies `name` to `x` first.
+        let mut inputs = Vec::new();
+        if target == MethodTarget::Local {
+            inputs.push(receiver);
+        } else {
+            self.infer_expr(receiver)?;
         }
+        inputs.extend(args);
+        let mut perform = Effect::Pure;
+        let mut applications = Effect::Pure;
+        let mut latent = callee;
+        for input in inputs {
+            let input = self.infer_expr(input)?;
+            perform = perform.join(input.perform);
+            let (effect, result) = self.apply(&latent, &input.latent)?;
+            applications = applications.join(effect);
+            latent = result;
+        }
+        Ok(ExprEffect {
+            perform: perform.join(applications.through(applied, span)),
+            latent,
+        })
     }
 
-    fn check_match_arm(&mut self, arm: &MatchArm) -> Result<(), ResolveError> {
+    fn check_match_arm(
+        &mut self,
+        arm: &MatchArm,
+        subject: &Latent,
+    ) -> Result<ExprEffect, ResolveError> {
         self.push_scope();
-        let result = (|| -> Result<(), ResolveError> {
-            self.define_pattern_bindings(&arm.pattern)?;
+        let result = (|| -> Result<ExprEffect, ResolveError> {
+            self.define_pattern_bindings(&arm.pattern, subject)?;
+            let mut perform = Effect::Pure;
             if let Some(guard) = &arm.guard {
-                self.check_expr(guard)?;
+                perform = self.infer_expr(guard)?.perform;
+            }
+            Ok(ExprEffect {
+                perform,
+                latent: Latent::default(),
             }
-            self.check_expr(&arm.body)?;
-            Ok(())
+            .join(self.infer_expr(&arm.body)?))
         })();
         self.pop_scope();
         result
     }
 
-    fn define_pattern_bindings(&mut self, pat: &Pattern) -> Result<(), ResolveError> {
+    /// Defines the names bound by `pat`. Each may be any part of the subject,
+    /// so each gets the subject's latent effect.
+    fn define_pattern_bindings(
+        &mut self,
+        pat: &Pattern,
+        latent: &Latent,
+    ) -> Result<(), ResolveError> {
         match pat {
-            Pattern::Wildcard(_) | Pattern::Int(_, _) | Pattern::String(_, _) => Ok(()),
+            Pattern::Wildcard(_)
+            | Pattern::Int(_, _)
+            | Pattern::String(_, _)
+            | Pattern::Range { .. } => Ok(()),
             Pattern::Var(name, span) => {
                 self.define(name.clone(), *span)?;
+                self.set_latent(name, latent.clone());
                 Ok(())
             }
             Pattern::List(items, _) => {
                 for item in items {
-                    self.define_pattern_bindings(item)?;
+                    self.define_pattern_bindings(item, latent)?;
                 }
                 Ok(())
             }
             Pattern::Ok(inner, _) | Pattern::Err(inner, _) => {
-                self.define_pattern_bindings(inner)
+                self.define_pattern_bindings(inner, latent)
             }
         }
     }
@@ -212,25 +839,120 @@ impl Resolver {
         bindings: &[WithBinding],
         body: &Expr,
         else_body: &Expr,
-    ) -> Result<(), ResolveError> {
+    ) -> Result<ExprEffect, ResolveError> {
         self.push_scope();
-        let result = (|| -> Result<(), ResolveError> {
+        let result = (|| -> Result<ExprEffect, ResolveError> {
+            let mut perform = Effect::Pure;
             for wb in bindings {
-                self.check_expr(&wb.expr)?;
-                self.define_pattern_bindings(&wb.pattern)?;
+                let effect = self.infer_expr(&wb.expr)?;
+                perform = perform.join(effect.perform);
+                self.define_pattern_bindings(&wb.pattern, &effect.latent)?;
             }
-            self.check_expr(body)?;
-            Ok(())
+            Ok(ExprEffect {
+                perform,
+                latent: Latent::default(),
+            }
+            .join(self.infer_expr(body)?))
         })();
         self.pop_scope();
-        result?;
-        self.check_expr(else_body)?;
-        Ok(())
+        Ok(result?.join(self.infer_expr(else_body)?))
+    }
+
+    /// Checks that `name` is a mutable binding and records the assigned latent effect.
+    fn assign(&mut self, name: &str, name_span: Span, latent: Latent) -> Result<(), ResolveError> {
+        match self.lookup(name) {
+            Some(binding) if binding.mutable => {
+                // Keep the binding impure if any assigned function was.
+                let latent = binding.latent.clone().join(latent);
+                self.set_latent(name, latent);
+                Ok(())
+            }
+            Some(_) => Err(SetNotMutableError {
+                name: name.to_string(),
+                span: name_span,
+            }
+            .into()),
+            None => Err(SetUndefinedError {
+                name: name.to_string(),
+                span: name_span,
+            }
+            .into()),
+        }
+    }
+
+    /// Like `infer_expr`, but a `for` here may `set` outer `let mut` bindings.
+    fn infer_stmt_expr(&mut self, expr: &Expr) -> Result<ExprEffect, ResolveError> {
+        match expr {
+            Expr::For {
+                var,
+                var_span,
+                iter,
+                guard,
+                body,
+                ..
+            } => self.check_for_expr(var, *var_span, iter, guard.as_deref(), body, true),
+            _ => self.infer_expr(expr),
+        }
+    }
+
+    fn check_for_expr(
+        &mut self,
+        var: &str,
+        var_span: Span,
+        iter: &Expr,
+        guard: Option<&Expr>,
+        body: &[Expr],
+        allow_set: bool,
+    ) -> Result<ExprEffect, ResolveError> {
+        let iter_span = iter.span();
+        let iter = self.infer_expr(iter)?;
+        // Looping forces a seq, running the functions it was mapped with.
+        let (forced, _) = self.apply(&iter.latent, &Latent::default())?;
+        let mut perform = iter.perform.join(forced.through("for", iter_span));
+        self.push_scope();
+        let result = (|| -> Result<Effect, ResolveError> {
+            self.define(var.to_string(), var_span)?;
+            self.set_latent(var, iter.latent);
+            let mut perform = Effect::Pure;
+            if let Some(guard) = guard {
+                perform = self.infer_expr(guard)?.perform;
+            }
+            for item in body {
+                let effect = match item {
+                    Expr::Set {
+                        name,
+                        name_span,
+                        expr,
+                        ..
+                    } if allow_set => {
+                        let effect = self.infer_expr(expr)?;
+                        self.assign(name, *name_span, effect.latent)?;
+                        effect.perform
+                    }
+                    Expr::For { .. } if allow_set => self.infer_stmt_expr(item)?.perform,
+                    _ => self.infer_expr(item)?.perform,
+                };
+                perform = perform.join(effect);
+            }
+            Ok(perform)
+        })();
+        self.pop_scope();
+        perform = perform.join(result?);
+        Ok(ExprEffect {
+            perform,
+            latent: Latent::default(),
+        })
     }
 
     fn resolve_stmt(&mut self, stmt: &Stmt) -> Result<(), ResolveError> {
         match stmt {
             Stmt::Expr(expr) => self.check_expr(expr),
+            Stmt::Assert { expr, message, .. } => {
+                self.check_expr(expr)?;
+                message
+                    .as_ref()
+                    .map_or(Ok(()), |message| self.check_expr(message))
+            }
             Stmt::Let {
                 name,
                 name_span,
@@ -238,34 +960,91 @@ impl Resolver {
                 expr,
                 ..
             } => {
-                self.check_expr(expr)?;
+                let effect = self.infer_stmt_expr(expr)?;
+                Self::require_pure(effect.perform)?;
                 if *mutable {
                     self.define_mut(name.clone(), *name_span)?;
                 } else {
                     self.define(name.clone(), *name_span)?;
                 }
+                self.set_latent(name, effect.latent);
                 Ok(())
             }
-            Stmt::Set {
-                name,
-                name_span,
+            Stmt::LetPattern {
+                pattern,
                 expr,
+                else_body,
                 ..
             } => {
-                self.check_expr(expr)?;
-                match self.lookup(name) {
-                    Some(binding) if binding.mutable => Ok(()),
-                    Some(_) => Err(SetNotMutableError {
-                        name: name.clone(),
-                        span: *name_span,
+                if else_body.is_none() && pattern.is_refutable() {
+                    return Err(RefutableLetError {
+                        span: pattern.span(),
                     }
-                    .into()),
-                    None => Err(SetUndefinedError {
-                        name: name.clone(),
-                        span: *name_span,
+                    .into());
+                }
+                if else_body.is_some() {
+                    let found = pattern.bound_names().len();
+                    if found != 1 {
+                        return Err(LetElseBindingError {
+                            found,
+                            span: pattern.span(),
+                        }
+                        .into());
+                    }
+                }
+                let subject = self.infer_stmt_expr(expr)?;
+                Self::require_pure(subject.perform)?;
+                let mut latent = subject.latent;
+                if let Some(fallback) = else_body {
+                    let fallback = self.infer_stmt_expr(fallback)?;
+                    Self::require_pure(fallback.perform)?;
+                    latent = latent.join(fallback.latent);
+                }
+                self.define_pattern_bindings(pattern, &latent)
+            }
+            Stmt::Import {
+                path,
+                path_span,
+                alias,
+                alias_span,
+                ..
+            } => {
+                let Some(interface) = self.modules.get(path).cloned() else {
+                    return Err(UnknownModuleError {
+                        path: path.clone(),
+                        span: *path_span,
                     }
-                    .into()),
+                    .into());
+                };
+                // The alias is an ordinary binding, so it can never hide a local name.
+                self.define(alias.clone(), *alias_span)?;
+                if let Some(binding) = self.lookup_mut(alias) {
+                    binding.module = Some(interface);
                 }
+                Ok(())
+            }
+            Stmt::Export { names, .. } => {
+                for (name, span) in names {
+                    if !self.is_defined(name) {
+                        return Err(UndefinedNameError {
+                            name: name.clone(),
+                            span: *span,
+                        }
+                        .into());
+                    }
+                    self.exports.insert(name.clone(), *span);
+                }
+                Ok(())
+            }
+            Stmt::Set {
+                name,
+                name_span,
+                expr,
+                ..
+            } => {
+                let effect = self.infer_stmt_expr(expr)?;
+                Self::require_pure(effect.perform)?;
+                self.assign(name, *name_span, effect.latent)
             }
         }
     }
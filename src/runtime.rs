@@ -1,7 +1,77 @@
-use std::process::{Command, Output};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use crate::command::CommandSpec;
 
 pub fn run_command(spec: &CommandSpec) -> std::io::Result<Output> {
-    Command::new(spec.program()).args(spec.args()).output()
+    let mut command = Command::new(spec.program());
+    command.args(spec.args());
+    if let Some(cwd) = spec.cwd() {
+        command.current_dir(cwd);
+    }
+    command.envs(spec.env().iter().map(|(k, v)| (k, v)));
+    if spec.stdin().is_none() && spec.timeout().is_none() {
+        return command.output();
+    }
+
+    command
+        .stdin(if spec.stdin().is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    if let (Some(input), Some(mut pipe)) = (spec.stdin(), child.stdin.take()) {
+        let input = input.to_string();
+        // Written from a thread so a child that never reads cannot block us.
+        thread::spawn(move || {
+            let _ = pipe.write_all(input.as_bytes());
+        });
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let status = match spec.timeout() {
+        Some(timeout) => wait_with_timeout(&mut child, timeout)?,
+        None => child.wait()?,
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> std::io::Result<std::process::ExitStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("timed out after {} ms", timeout.as_millis()),
+            ));
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
 }
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::value::Value;
+
+/// Default cap on a column's width, in terminal cells.
+pub const DEFAULT_MAX_WIDTH: usize = 40;
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Records laid out as rows under named columns; built by `table()` or by the
+/// REPL for a list of records that share the same fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<BTreeMap<String, Value>>,
+    /// Widest a column may grow, in terminal cells, before cells are cut with `…`.
+    pub max_width: usize,
+}
+
+impl Table {
+    /// Rows with every field that appears in any of them as a column.
+    pub fn new(rows: Vec<BTreeMap<String, Value>>) -> Self {
+        let mut columns: Vec<String> = Vec::new();
+        for row in &rows {
+            for key in row.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+        columns.sort();
+        Self {
+            columns,
+            rows,
+            max_width: DEFAULT_MAX_WIDTH,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<String>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// The table a list renders as without asking: a non-empty list of
+    /// records that all have the same fields.
+    pub fn infer(items: &[Value]) -> Option<Self> {
+        let mut rows = Vec::with_capacity(items.len());
+        for item in items {
+            let Value::Record(fields) = item else {
+                return None;
+            };
+            rows.push(fields.clone());
+        }
+        let first = rows.first()?;
+        if first.is_empty() || rows.iter().any(|row| !row.keys().eq(first.keys())) {
+            return None;
+        }
+        Some(Self::new(rows))
+    }
+
+    /// Renders the header, a rule and then `limit` rows (all when `None`);
+    /// `color` styles the header and rule with ANSI escapes.
+    pub fn render(&self, limit: Option<usize>, color: bool) -> String {
+        let shown = limit.map_or(self.rows.len(), |max| self.rows.len().min(max));
+        let cells: Vec<Vec<(String, bool)>> = self.rows[..shown]
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .map(|column| match row.get(column) {
+                        Some(value) => (
+                            truncate(&cell_text(value), self.max_width),
+                            is_numeric(value),
+                        ),
+                        None => (String::new(), false),
+                    })
+                    .collect()
+            })
+            .collect();
+        let headers: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| truncate(column, self.max_width))
+            .collect();
+        let widths: Vec<usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                cells
+                    .iter()
+                    .map(|row| row[i].0.width())
+                    .chain([header.width()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        // A column of numbers right-aligns its header too.
+        let numeric: Vec<bool> = (0..headers.len())
+            .map(|i| !cells.is_empty() && cells.iter().all(|row| row[i].1))
+            .collect();
+
+        let mut lines = Vec::with_capacity(shown + 3);
+        let header: Vec<String> = headers
+            .iter()
+            .zip(&widths)
+            .zip(&numeric)
+            .map(|((header, &width), &right)| pad(header, width, right))
+            .collect();
+        lines.push(paint(header.join("  ").trim_end(), BOLD, color));
+        let rule: Vec<String> = widths.iter().map(|&width| "─".repeat(width)).collect();
+        lines.push(paint(&rule.join("  "), DIM, color));
+        for row in &cells {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|((text, numeric), &width)| pad(text, width, *numeric))
+                .collect();
+            lines.push(line.join("  ").trim_end().to_string());
+        }
+        if shown < self.rows.len() {
+            lines.push(format!("… {} more rows", self.rows.len() - shown));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Cell contents: strings without quotes, control characters escaped so a
+/// row stays on one line.
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s
+            .chars()
+            .map(|c| {
+                if c.is_control() {
+                    c.escape_default().to_string()
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect(),
+        other => other.to_string(),
+    }
+}
+
+fn is_numeric(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Int(_) | Value::Float(_) | Value::Duration(_) | Value::ByteSize(_)
+    )
+}
+
+/// Cuts `text` to at most `max` terminal cells, ending in `…` when shortened.
+fn truncate(text: &str, max: usize) -> String {
+    if text.width() <= max {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > max {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+/// Pads to `width` cells; numbers are right-aligned.
+fn pad(text: &str, width: usize, right: bool) -> String {
+    let fill = " ".repeat(width.saturating_sub(text.width()));
+    if right {
+        format!("{fill}{text}")
+    } else {
+        format!("{text}{fill}")
+    }
+}
+
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{style}{text}{RESET}")
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+
+use crate::value::Value;
+
+/// A line pattern for `parse`: literal text with `{name}` or `{name:type}`
+/// placeholders. `{{` and `}}` stand for literal braces, a run of spaces or
+/// tabs matches any run of whitespace, and `{_}` matches without keeping the
+/// text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Literal(String),
+    Space,
+    Field { name: String, kind: FieldKind },
+}
+
+/// What a placeholder's text is converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Str,
+    Int,
+    Float,
+    Bool,
+}
+
+impl FieldKind {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "str" => Self::Str,
+            "int" => Self::Int,
+            "float" => Self::Float,
+            "bool" => Self::Bool,
+            _ => return None,
+        })
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Str => "a string",
+            Self::Int => "an int",
+            Self::Float => "a float",
+            Self::Bool => "a bool",
+        }
+    }
+
+    fn convert(self, text: &str) -> Option<Value> {
+        match self {
+            Self::Str => Some(Value::String(text.to_string())),
+            Self::Int => text.parse().ok().map(Value::Int),
+            Self::Float => text
+                .parse::<f64>()
+                .ok()
+                .filter(|x| x.is_finite())
+                .map(Value::Float),
+            Self::Bool => match text {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Why a line did not fit the template; `column` counts characters from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub message: String,
+    pub column: usize,
+}
+
+impl Mismatch {
+    /// The `{message, line, column, text}` record `parse` returns inside `Err`
+    /// for the line numbered `line` (from 1) with contents `text`.
+    pub fn into_record(self, line: usize, text: String) -> Value {
+        let mut fields = BTreeMap::new();
+        fields.insert("message".to_string(), Value::String(self.message));
+        fields.insert("line".to_string(), Value::Int(line as i64));
+        fields.insert("column".to_string(), Value::Int(self.column as i64));
+        fields.insert("text".to_string(), Value::String(text));
+        Value::Record(fields)
+    }
+}
+
+impl Template {
+    /// Reads a template, rejecting unknown types, repeated names and two
+    /// placeholders with nothing between them to tell where one ends.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+                '}' => return Err("unmatched `}`; write `}}` for a literal brace".to_string()),
+                ' ' | '\t' => {
+                    while chars.next_if(|c| matches!(c, ' ' | '\t')).is_some() {}
+                    flush(&mut pieces, &mut literal);
+                    pieces.push(Piece::Space);
+                }
+                '{' => {
+                    let mut inner = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => inner.push(c),
+                            None => return Err(format!("unclosed placeholder `{{{inner}`")),
+                        }
+                    }
+                    let (name, kind) = placeholder(&inner)?;
+                    flush(&mut pieces, &mut literal);
+                    if let Some(Piece::Field { name: before, .. }) = pieces.last() {
+                        return Err(format!(
+                            "`{{{before}}}` and `{{{name}}}` need literal text between them"
+                        ));
+                    }
+                    let repeated = pieces.iter().any(|piece| match piece {
+                        Piece::Field { name: seen, .. } => *seen == name,
+                        _ => false,
+                    });
+                    if repeated && name != "_" {
+                        return Err(format!("placeholder `{{{name}}}` appears twice"));
+                    }
+                    pieces.push(Piece::Field { name, kind });
+                }
+                c => literal.push(c),
+            }
+        }
+        flush(&mut pieces, &mut literal);
+        // Lines are matched without trailing whitespace, so a space at the end
+        // of the template has nothing to match.
+        if pieces.last() == Some(&Piece::Space) {
+            pieces.pop();
+        }
+        Ok(Self { pieces })
+    }
+
+    /// Matches a whole line (trailing whitespace aside), trying each place a
+    /// placeholder could end until the rest of the line fits. On failure,
+    /// reports text a typed placeholder could not convert if there was any,
+    /// since a longer untyped placeholder only hides it; otherwise the point
+    /// the best attempt got to.
+    pub fn match_line(&self, line: &str) -> Result<Value, Mismatch> {
+        let line = line.trim_end();
+        let mut matcher = Matcher {
+            pieces: &self.pieces,
+            line,
+            fields: Vec::new(),
+            furthest: None,
+            unconverted: None,
+        };
+        if matcher.at(0, 0) {
+            let fields: BTreeMap<String, Value> = matcher
+                .fields
+                .into_iter()
+                .filter(|(name, _)| name != "_")
+                .collect();
+            return Ok(Value::Record(fields));
+        }
+        let (pos, message) = matcher
+            .unconverted
+            .or(matcher.furthest)
+            .expect("a failed match records why");
+        Err(Mismatch {
+            message,
+            column: line[..pos].chars().count() + 1,
+        })
+    }
+}
+
+/// Splits the inside of `{...}` into a name and a type, `str` by default.
+fn placeholder(inner: &str) -> Result<(String, FieldKind), String> {
+    let (name, kind) = match inner.split_once(':') {
+        Some((name, kind)) => {
+            let kind = FieldKind::from_name(kind.trim()).ok_or_else(|| {
+                format!(
+                    "unknown type `{}` in `{{{inner}}}`; expected int, float, str or bool",
+                    kind.trim()
+                )
+            })?;
+            (name.trim(), kind)
+        }
+        None => (inner.trim(), FieldKind::Str),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("invalid placeholder name in `{{{inner}}}`"));
+    }
+    Ok((name.to_string(), kind))
+}
+
+fn flush(pieces: &mut Vec<Piece>, literal: &mut String) {
+    if !literal.is_empty() {
+        pieces.push(Piece::Literal(std::mem::take(literal)));
+    }
+}
+
+struct Matcher<'a> {
+    pieces: &'a [Piece],
+    line: &'a str,
+    fields: Vec<(String, Value)>,
+    /// The failure furthest into the line, as the likeliest explanation.
+    furthest: Option<(usize, String)>,
+    /// The furthest text a typed placeholder could not convert.
+    unconverted: Option<(usize, String)>,
+}
+
+impl Matcher<'_> {
+    fn fail(&mut self, pos: usize, message: String) -> bool {
+        keep_furthest(&mut self.furthest, pos, message)
+    }
+
+    /// Whether `pieces[index..]` matches `line[pos..]` exactly.
+    fn at(&mut self, index: usize, pos: usize) -> bool {
+        let rest = &self.line[pos..];
+        match self.pieces.get(index) {
+            None if rest.is_empty() => true,
+            None => self.fail(pos, format!("unexpected `{rest}` after the template")),
+            Some(Piece::Literal(text)) => {
+                if rest.starts_with(text.as_str()) {
+                    self.at(index + 1, pos + text.len())
+                } else {
+                    self.fail(pos, format!("expected `{text}`"))
+                }
+            }
+            Some(Piece::Space) => {
+                let trimmed = rest.trim_start();
+                if trimmed.len() == rest.len() {
+                    return self.fail(pos, "expected whitespace".to_string());
+                }
+                self.at(index + 1, pos + rest.len() - trimmed.len())
+            }
+            Some(Piece::Field { name, kind }) => {
+                let ends: Vec<usize> = match self.pieces.get(index + 1) {
+                    None => vec![rest.len()],
+                    Some(Piece::Literal(text)) => (0..=rest.len())
+                        .filter(|&i| {
+                            rest.is_char_boundary(i) && rest[i..].starts_with(text.as_str())
+                        })
+                        .collect(),
+                    // Where a run of whitespace starts, so the field keeps none of it.
+                    Some(Piece::Space) => {
+                        let mut ends = Vec::new();
+                        let mut after_space = true;
+                        for (i, c) in rest.char_indices() {
+                            if c.is_whitespace() && !after_space {
+                                ends.push(i);
+                            }
+                            after_space = c.is_whitespace();
+                        }
+                        ends
+                    }
+                    Some(Piece::Field { .. }) => unreachable!("fields are never adjacent"),
+                };
+                if ends.is_empty() {
+                    let next = match &self.pieces[index + 1] {
+                        Piece::Literal(text) => format!("`{text}`"),
+                        _ => "whitespace".to_string(),
+                    };
+                    return self.fail(
+                        self.line.len(),
+                        format!("expected {next} after `{{{name}}}`"),
+                    );
+                }
+                for end in ends {
+                    let text = &rest[..end];
+                    let Some(value) = kind.convert(text) else {
+                        keep_furthest(
+                            &mut self.unconverted,
+                            pos,
+                            format!("`{text}` is not {} for `{{{name}}}`", kind.describe()),
+                        );
+                        continue;
+                    };
+                    self.fields.push((name.clone(), value));
+                    if self.at(index + 1, pos + end) {
+                        return true;
+                    }
+                    self.fields.pop();
+                }
+                false
+            }
+        }
+    }
+}
+
+/// Records `message` at `pos` unless `slot` already holds a failure at or
+/// after it; always `false`, so a failed step can return it.
+fn keep_furthest(slot: &mut Option<(usize, String)>, pos: usize, message: String) -> bool {
+    if slot.as_ref().is_none_or(|(at, _)| pos > *at) {
+        *slot = Some((pos, message));
+    }
+    false
+}
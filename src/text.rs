@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use crate::toml::ConfigError;
+use crate::value::Value;
+
+/// Lines without their `\n` / `\r\n`; a final line break does not add an
+/// empty last line.
+pub fn lines(text: &str) -> Vec<String> {
+    text.lines().map(str::to_string).collect()
+}
+
+/// Pieces between each `sep`, keeping empty ones (`"a,,b"` gives three).
+pub fn split(text: &str, sep: &str) -> Vec<String> {
+    text.split(sep).map(str::to_string).collect()
+}
+
+/// Runs of non-whitespace.
+pub fn words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+/// Reads `KEY=VALUE` lines as printed by `env` or kept in `.env` files.
+///
+/// Blank lines and `#` comments are skipped, an `export ` prefix is allowed,
+/// and one layer of matching quotes around a value is removed. A line without
+/// `=` continues the previous value, as `env` prints multi-line values. Later
+/// assignments win, as in a shell.
+pub fn parse_kv(text: &str) -> Result<Value, ConfigError> {
+    let mut fields: BTreeMap<String, Value> = BTreeMap::new();
+    let mut last: Option<String> = None;
+    let mut offset = 0;
+    for raw in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += raw.len();
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let content = line.trim_start();
+        let indent = line.len() - content.len();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let assignment = content.strip_prefix("export ").unwrap_or(content);
+        let Some((key, value)) = assignment.split_once('=') else {
+            match last.as_ref().and_then(|key| fields.get_mut(key)) {
+                Some(Value::String(value)) => {
+                    value.push('\n');
+                    value.push_str(line);
+                    continue;
+                }
+                _ => {
+                    return Err(ConfigError::at(
+                        text,
+                        line_start + indent,
+                        format!("expected `KEY=VALUE`, found `{content}`"),
+                    ));
+                }
+            }
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(ConfigError::at(
+                text,
+                line_start + indent,
+                format!("invalid key `{key}`"),
+            ));
+        }
+        fields.insert(
+            key.to_string(),
+            Value::String(unquote(value.trim()).to_string()),
+        );
+        last = Some(key.to_string());
+    }
+    Ok(Value::Record(fields))
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+/// Reads logfmt lines (`level=info msg="disk full" retry`) into one record
+/// per non-blank line. Values stay strings; a key without `=` is `true`.
+pub fn parse_logfmt(text: &str) -> Result<Value, ConfigError> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    for raw in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += raw.len();
+        let line = raw.trim_end_matches(['\n', '\r']);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = |at: usize, message: String| ConfigError::at(text, line_start + at, message);
+        let mut fields = BTreeMap::new();
+        let mut chars = line.char_indices().peekable();
+        loop {
+            while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+            let Some(&(key_start, _)) = chars.peek() else {
+                break;
+            };
+            while chars
+                .next_if(|(_, c)| !c.is_whitespace() && *c != '=')
+                .is_some()
+            {}
+            let key_end = chars.peek().map_or(line.len(), |&(i, _)| i);
+            let key = &line[key_start..key_end];
+            if key.is_empty() {
+                return Err(error(key_start, "expected a key before `=`".to_string()));
+            }
+            if chars.next_if(|&(_, c)| c == '=').is_none() {
+                fields.insert(key.to_string(), Value::Bool(true));
+                continue;
+            }
+            let value = match chars.peek() {
+                Some(&(quote_start, '"')) => {
+                    chars.next();
+                    let mut value = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break value,
+                            Some((_, '\\')) if chars.peek().is_some() => match chars.next() {
+                                Some((_, 'n')) => value.push('\n'),
+                                Some((_, 't')) => value.push('\t'),
+                                Some((_, c)) => value.push(c),
+                                None => unreachable!("peeked"),
+                            },
+                            Some((_, c)) => value.push(c),
+                            None => {
+                                return Err(error(
+                                    quote_start,
+                                    "unterminated quoted value".to_string(),
+                                ));
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    let start = chars.peek().map_or(line.len(), |&(i, _)| i);
+                    while chars.next_if(|(_, c)| !c.is_whitespace()).is_some() {}
+                    let end = chars.peek().map_or(line.len(), |&(i, _)| i);
+                    line[start..end].to_string()
+                }
+            };
+            fields.insert(key.to_string(), Value::String(value));
+        }
+        records.push(Value::Record(fields));
+    }
+    Ok(Value::List(records))
+}
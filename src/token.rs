@@ -1,14 +1,22 @@
+use std::time::Duration;
+
 use crate::span::Span;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: Span,
+    /// Whether a newline separates this token from the previous one.
+    pub line_start: bool,
 }
 
 impl Token {
     pub fn new(kind: TokenKind, span: Span) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            line_start: false,
+        }
     }
 }
 
@@ -18,22 +26,45 @@ pub enum TokenKind {
     Mut,
     Set,
     Fn,
+    Def,
     Match,
     With,
     When,
     Io,
+    For,
+    Try,
+    Import,
+    Export,
+    Assert,
     Do,
     End,
     Ident(String),
-    Int(i64),
+    /// `true` / `false`
+    Bool(bool),
+    /// Magnitude of an integer literal, up to `2^63` so that a prefix `-` can
+    /// reach `i64::MIN`; the parser rejects `2^63` without one.
+    Int(u64),
+    Duration(Duration),
+    Size(u64),
     String(String),
+    Path(String),
     PipeGreater,
     Arrow,
+    Minus,
+    Plus,
+    Dot,
+    DotDot,
     LeftArrow,
     Equal,
     EqualEqual,
     NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
     Comma,
+    Semicolon,
+    Colon,
     LParen,
     RParen,
     LBracket,
@@ -0,0 +1,849 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::value::Value;
+
+/// Why a config file could not be read; `line` and `column` are 1-based, with
+/// the column counted in characters. Shared by TOML and INI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ConfigError {
+    pub fn at(text: &str, pos: usize, message: impl Into<String>) -> Self {
+        let before = &text[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            message: message.into(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// The `{message, line, column}` record `from_toml` / `from_ini` return
+    /// inside `Err`.
+    pub fn into_record(self) -> Value {
+        let mut fields = BTreeMap::new();
+        fields.insert("message".to_string(), Value::String(self.message));
+        fields.insert("line".to_string(), Value::Int(self.line as i64));
+        fields.insert("column".to_string(), Value::Int(self.column as i64));
+        Value::Record(fields)
+    }
+}
+
+/// Reads a TOML document into a record. Dates and times become
+/// `Value::DateTime` holding their text as written.
+pub fn parse(text: &str) -> Result<Value, ConfigError> {
+    let mut reader = Reader {
+        text,
+        pos: 0,
+        root: BTreeMap::new(),
+        current: Vec::new(),
+        headers: HashSet::new(),
+        dotted: HashSet::new(),
+        frozen: HashSet::new(),
+        arrays: HashSet::new(),
+    };
+    reader.document()?;
+    Ok(Value::Record(reader.root))
+}
+
+/// One step of a key path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Segment {
+    name: String,
+    /// Which entry of an array of tables, when `name` is one.
+    index: Option<usize>,
+}
+
+type TablePath = Vec<Segment>;
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+    root: BTreeMap<String, Value>,
+    /// Path of the table the latest header opened.
+    current: TablePath,
+    /// Tables opened by a `[header]`.
+    headers: HashSet<TablePath>,
+    /// Tables created by dotted keys, which a later header may not reopen.
+    dotted: HashSet<TablePath>,
+    /// Inline tables and arrays, which cannot be extended.
+    frozen: HashSet<TablePath>,
+    /// Arrays created by `[[header]]`.
+    arrays: HashSet<TablePath>,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: impl Into<String>) -> ConfigError {
+        ConfigError::at(self.text, self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> ConfigError {
+        ConfigError::at(self.text, pos, message)
+    }
+
+    fn found(&self) -> String {
+        match self.peek() {
+            Some('\n') | Some('\r') => "end of line".to_string(),
+            Some(c) => format!("`{c}`"),
+            None => "end of input".to_string(),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ConfigError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{c}`, found {}", self.found())))
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(' ' | '\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips spaces, newlines and comments, e.g. between array items.
+    fn skip_blank(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\n') => self.pos += 1,
+                Some('\r') if self.rest().starts_with("\r\n") => self.pos += 2,
+                Some('#') => self.skip_comment(),
+                _ => return,
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '\n' || c == '\r' {
+                return;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// After a header or key/value: optional comment, then a line break.
+    fn end_of_line(&mut self) -> Result<(), ConfigError> {
+        self.skip_spaces();
+        if self.peek() == Some('#') {
+            self.skip_comment();
+        }
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some('\r') if self.rest().starts_with("\r\n") => {
+                self.pos += 2;
+                Ok(())
+            }
+            _ => Err(self.error(format!("expected end of line, found {}", self.found()))),
+        }
+    }
+
+    fn document(&mut self) -> Result<(), ConfigError> {
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => return Ok(()),
+                Some('[') => self.header()?,
+                Some(_) => {
+                    let (keys, key_pos) = self.key()?;
+                    self.skip_spaces();
+                    self.expect('=')?;
+                    self.skip_spaces();
+                    let value = self.value()?;
+                    let base = self.current.clone();
+                    self.insert(&base, &keys, key_pos, value)?;
+                }
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    fn header(&mut self) -> Result<(), ConfigError> {
+        let start = self.pos;
+        let array = self.rest().starts_with("[[");
+        self.pos += if array { 2 } else { 1 };
+        self.skip_spaces();
+        let (keys, _) = self.key()?;
+        self.skip_spaces();
+        self.expect(']')?;
+        if array {
+            self.expect(']')?;
+        }
+        let (last, parents) = keys.split_last().expect("keys are never empty");
+        let parent = self.open(&[], parents, start, false)?;
+        let mut path = parent.clone();
+        path.push(Segment {
+            name: last.clone(),
+            index: None,
+        });
+        let reopenable = !self.headers.contains(&path)
+            && !self.dotted.contains(&path)
+            && !self.frozen.contains(&path);
+        let is_array = self.arrays.contains(&path);
+        let table = table_mut(&mut self.root, &parent);
+        match table.get_mut(last) {
+            None if array => {
+                table.insert(
+                    last.clone(),
+                    Value::List(vec![Value::Record(BTreeMap::new())]),
+                );
+                self.arrays.insert(path.clone());
+                path.last_mut().expect("just pushed").index = Some(0);
+            }
+            None => {
+                table.insert(last.clone(), Value::Record(BTreeMap::new()));
+                self.headers.insert(path.clone());
+            }
+            Some(Value::List(items)) if array && is_array => {
+                items.push(Value::Record(BTreeMap::new()));
+                path.last_mut().expect("just pushed").index = Some(items.len() - 1);
+            }
+            Some(Value::Record(_)) if !array && reopenable => {
+                self.headers.insert(path.clone());
+            }
+            Some(_) => {
+                return Err(
+                    self.error_at(start, format!("`{}` is already defined", keys.join(".")))
+                );
+            }
+        }
+        self.current = path;
+        Ok(())
+    }
+
+    /// Walks `keys` down from the table at `base`, creating missing tables,
+    /// and returns the path reached. `dotted` says the keys come from a
+    /// key/value line rather than a header.
+    fn open(
+        &mut self,
+        base: &[Segment],
+        keys: &[String],
+        pos: usize,
+        dotted: bool,
+    ) -> Result<TablePath, ConfigError> {
+        let mut path = base.to_vec();
+        let mut table = table_mut(&mut self.root, base);
+        for key in keys {
+            path.push(Segment {
+                name: key.clone(),
+                index: None,
+            });
+            if !table.contains_key(key) {
+                table.insert(key.clone(), Value::Record(BTreeMap::new()));
+                if dotted {
+                    self.dotted.insert(path.clone());
+                }
+            } else if self.frozen.contains(&path) {
+                return Err(ConfigError::at(
+                    self.text,
+                    pos,
+                    format!("`{key}` cannot be extended"),
+                ));
+            } else if dotted && self.headers.contains(&path) {
+                return Err(ConfigError::at(
+                    self.text,
+                    pos,
+                    format!("`{key}` is a table opened by a header"),
+                ));
+            }
+            let is_array = self.arrays.contains(&path);
+            table = match table.get_mut(key) {
+                Some(Value::Record(inner)) => inner,
+                Some(Value::List(items)) if is_array => {
+                    path.last_mut().expect("just pushed").index = Some(items.len() - 1);
+                    match items.last_mut() {
+                        Some(Value::Record(inner)) => inner,
+                        _ => unreachable!("arrays of tables hold records"),
+                    }
+                }
+                _ => {
+                    return Err(ConfigError::at(
+                        self.text,
+                        pos,
+                        format!("`{key}` is not a table"),
+                    ));
+                }
+            };
+        }
+        Ok(path)
+    }
+
+    fn insert(
+        &mut self,
+        base: &[Segment],
+        keys: &[String],
+        pos: usize,
+        value: Value,
+    ) -> Result<(), ConfigError> {
+        let (last, parents) = keys.split_last().expect("keys are never empty");
+        let mut path = self.open(base, parents, pos, true)?;
+        let table = table_mut(&mut self.root, &path);
+        if table.contains_key(last) {
+            return Err(ConfigError::at(
+                self.text,
+                pos,
+                format!("duplicate key `{}`", keys.join(".")),
+            ));
+        }
+        let frozen = matches!(value, Value::Record(_) | Value::List(_));
+        table.insert(last.clone(), value);
+        if frozen {
+            path.push(Segment {
+                name: last.clone(),
+                index: None,
+            });
+            self.frozen.insert(path);
+        }
+        Ok(())
+    }
+
+    /// A dotted key such as `server."host name".port`, with its position.
+    fn key(&mut self) -> Result<(Vec<String>, usize), ConfigError> {
+        let start = self.pos;
+        let mut keys = Vec::new();
+        loop {
+            self.skip_spaces();
+            let key = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let from = self.pos;
+                    while let Some('A'..='Z' | 'a'..='z' | '0'..='9' | '_' | '-') = self.peek() {
+                        self.pos += 1;
+                    }
+                    if from == self.pos {
+                        return Err(self.error(format!("expected a key, found {}", self.found())));
+                    }
+                    self.text[from..self.pos].to_string()
+                }
+            };
+            keys.push(key);
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return Ok((keys, start));
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ConfigError> {
+        let rest = self.rest();
+        match self.peek() {
+            Some('"') if rest.starts_with("\"\"\"") => self.multiline_basic().map(Value::String),
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') if rest.starts_with("'''") => self.multiline_literal().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            _ if rest.starts_with("true") => {
+                self.pos += 4;
+                Ok(Value::Bool(true))
+            }
+            _ if rest.starts_with("false") => {
+                self.pos += 5;
+                Ok(Value::Bool(false))
+            }
+            Some('0'..='9' | '+' | '-' | 'i' | 'n') => match self.datetime() {
+                Some(len) => {
+                    let text = self.text[self.pos..self.pos + len].to_string();
+                    self.pos += len;
+                    Ok(Value::DateTime(text))
+                }
+                None => self.number(),
+            },
+            _ => Err(self.error(format!("expected a value, found {}", self.found()))),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, ConfigError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let at = self.pos;
+            match self.bump() {
+                None | Some('\n') => return Err(self.error_at(start, "unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => out.push(self.escape(at)?),
+                Some(c) if c.is_control() && c != '\t' => {
+                    return Err(self.error_at(at, "control character in string must be escaped"));
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn multiline_basic(&mut self) -> Result<String, ConfigError> {
+        let start = self.pos;
+        self.pos += 3;
+        self.skip_first_newline();
+        let mut out = String::new();
+        loop {
+            if self.rest().starts_with("\"\"\"") {
+                // Up to two quotes may sit right before the closing delimiter.
+                let quotes = self.rest().chars().take_while(|&c| c == '"').count().min(5);
+                out.extend(std::iter::repeat_n('"', quotes - 3));
+                self.pos += quotes;
+                return Ok(out);
+            }
+            let at = self.pos;
+            match self.bump() {
+                None => return Err(self.error_at(start, "unterminated string")),
+                Some('\\') => {
+                    // A backslash at the end of a line trims the break and
+                    // the next line's indentation.
+                    let after = self.rest().trim_start_matches([' ', '\t']);
+                    if after.starts_with('\n') || after.starts_with("\r\n") {
+                        self.skip_blank_lines();
+                    } else {
+                        out.push(self.escape(at)?);
+                    }
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn skip_blank_lines(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_first_newline(&mut self) {
+        if self.rest().starts_with('\n') {
+            self.pos += 1;
+        } else if self.rest().starts_with("\r\n") {
+            self.pos += 2;
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, ConfigError> {
+        let start = self.pos;
+        self.pos += 1;
+        let from = self.pos;
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error_at(start, "unterminated string")),
+                Some('\'') => return Ok(self.text[from..self.pos - 1].to_string()),
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn multiline_literal(&mut self) -> Result<String, ConfigError> {
+        let start = self.pos;
+        self.pos += 3;
+        self.skip_first_newline();
+        match self.rest().find("'''") {
+            Some(end) => {
+                let quotes = self.rest()[end..]
+                    .chars()
+                    .take_while(|&c| c == '\'')
+                    .count()
+                    .min(5);
+                let text = self.rest()[..end + quotes - 3].to_string();
+                self.pos += end + quotes;
+                Ok(text)
+            }
+            None => Err(self.error_at(start, "unterminated string")),
+        }
+    }
+
+    /// Reads the escape after a backslash at `at`.
+    fn escape(&mut self, at: usize) -> Result<char, ConfigError> {
+        let hex = |reader: &mut Self, len: usize| {
+            let digits = reader.text.get(reader.pos..reader.pos + len).unwrap_or("");
+            let code = u32::from_str_radix(digits, 16)
+                .ok()
+                .filter(|_| digits.len() == len && digits.chars().all(|c| c.is_ascii_hexdigit()));
+            reader.pos += digits.len();
+            code.and_then(char::from_u32)
+                .ok_or_else(|| reader.error_at(at, "invalid unicode escape"))
+        };
+        Ok(match self.bump() {
+            Some('b') => '\u{8}',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('f') => '\u{c}',
+            Some('r') => '\r',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('u') => hex(self, 4)?,
+            Some('U') => hex(self, 8)?,
+            Some(c) => return Err(self.error_at(at, format!("invalid escape `\\{c}`"))),
+            None => return Err(self.error_at(at, "unterminated string")),
+        })
+    }
+
+    fn array(&mut self) -> Result<Value, ConfigError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::List(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                _ => return Err(self.error(format!("expected `,` or `]`, found {}", self.found()))),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, ConfigError> {
+        self.pos += 1;
+        // Read into a scratch document so dotted keys get the usual checks.
+        let mut inner = Reader {
+            text: self.text,
+            pos: self.pos,
+            root: BTreeMap::new(),
+            current: Vec::new(),
+            headers: HashSet::new(),
+            dotted: HashSet::new(),
+            frozen: HashSet::new(),
+            arrays: HashSet::new(),
+        };
+        inner.skip_spaces();
+        if inner.peek() == Some('}') {
+            self.pos = inner.pos + 1;
+            return Ok(Value::Record(inner.root));
+        }
+        loop {
+            let (keys, key_pos) = inner.key()?;
+            inner.skip_spaces();
+            inner.expect('=')?;
+            inner.skip_spaces();
+            let value = inner.value()?;
+            inner.insert(&[], &keys, key_pos, value)?;
+            inner.skip_spaces();
+            match inner.peek() {
+                Some(',') => {
+                    inner.pos += 1;
+                    inner.skip_spaces();
+                }
+                Some('}') => {
+                    self.pos = inner.pos + 1;
+                    return Ok(Value::Record(inner.root));
+                }
+                _ => {
+                    return Err(
+                        inner.error(format!("expected `,` or `}}`, found {}", inner.found()))
+                    );
+                }
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ConfigError> {
+        let start = self.pos;
+        while let Some('0'..='9' | 'A'..='Z' | 'a'..='z' | '_' | '+' | '-' | '.') = self.peek() {
+            self.pos += 1;
+        }
+        let literal = &self.text[start..self.pos];
+        let invalid = || ConfigError::at(self.text, start, format!("invalid number `{literal}`"));
+        let unsigned = literal.trim_start_matches(['+', '-']);
+        let negative = literal.starts_with('-');
+        match unsigned {
+            "inf" => {
+                return Ok(Value::Float(if negative {
+                    f64::NEG_INFINITY
+                } else {
+                    f64::INFINITY
+                }));
+            }
+            "nan" => return Ok(Value::Float(f64::NAN)),
+            _ => {}
+        }
+        // Underscores must sit between digits.
+        let bytes = literal.as_bytes();
+        let bad_underscore = bytes.iter().enumerate().any(|(i, &b)| {
+            b == b'_'
+                && !(i > 0
+                    && bytes[i - 1].is_ascii_alphanumeric()
+                    && bytes.get(i + 1).is_some_and(u8::is_ascii_alphanumeric))
+        });
+        if bad_underscore {
+            return Err(invalid());
+        }
+        let digits = unsigned.replace('_', "");
+        for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+            if let Some(body) = digits.strip_prefix(prefix) {
+                if literal.len() != unsigned.len() {
+                    return Err(invalid());
+                }
+                return i64::from_str_radix(body, radix)
+                    .map(Value::Int)
+                    .map_err(|_| invalid());
+            }
+        }
+        if digits.len() > 1 && digits.starts_with('0') && digits.as_bytes()[1].is_ascii_digit() {
+            return Err(invalid());
+        }
+        let signed = if negative {
+            format!("-{digits}")
+        } else {
+            digits.clone()
+        };
+        if digits.chars().all(|c| c.is_ascii_digit()) {
+            return signed.parse().map(Value::Int).map_err(|_| invalid());
+        }
+        let well_formed = !digits.starts_with('.')
+            && !digits.contains(".e")
+            && !digits.contains(".E")
+            && !digits.ends_with('.');
+        match signed.parse::<f64>() {
+            Ok(x) if well_formed => Ok(Value::Float(x)),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Length of a date, time or date-time starting here, if there is one.
+    fn datetime(&self) -> Option<usize> {
+        let bytes = self.rest().as_bytes();
+        let digits = |from: usize, n: usize| {
+            bytes.len() >= from + n && bytes[from..from + n].iter().all(u8::is_ascii_digit)
+        };
+        let number = |from: usize, n: usize| -> u32 {
+            std::str::from_utf8(&bytes[from..from + n])
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(99)
+        };
+        let time = |from: usize| -> Option<usize> {
+            if !(digits(from, 2)
+                && bytes.get(from + 2) == Some(&b':')
+                && digits(from + 3, 2)
+                && bytes.get(from + 5) == Some(&b':')
+                && digits(from + 6, 2))
+            {
+                return None;
+            }
+            if number(from, 2) > 23 || number(from + 3, 2) > 59 || number(from + 6, 2) > 60 {
+                return None;
+            }
+            let mut end = from + 8;
+            if bytes.get(end) == Some(&b'.') && digits(end + 1, 1) {
+                end += 1;
+                while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                    end += 1;
+                }
+            }
+            Some(end)
+        };
+        if let Some(end) = time(0) {
+            return Some(end);
+        }
+        if !(digits(0, 4)
+            && bytes.get(4) == Some(&b'-')
+            && digits(5, 2)
+            && bytes.get(7) == Some(&b'-')
+            && digits(8, 2))
+        {
+            return None;
+        }
+        let (month, day) = (number(5, 2), number(8, 2));
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let Some(end) = (match bytes.get(10) {
+            Some(b'T' | b't' | b' ') => time(11),
+            _ => None,
+        }) else {
+            return Some(10);
+        };
+        match bytes.get(end) {
+            Some(b'Z' | b'z') => Some(end + 1),
+            Some(b'+' | b'-')
+                if digits(end + 1, 2)
+                    && bytes.get(end + 3) == Some(&b':')
+                    && digits(end + 4, 2) =>
+            {
+                Some(end + 6)
+            }
+            _ => Some(end),
+        }
+    }
+}
+
+/// The table at `path`, which [`Reader::open`] has already created.
+fn table_mut<'t>(
+    root: &'t mut BTreeMap<String, Value>,
+    path: &[Segment],
+) -> &'t mut BTreeMap<String, Value> {
+    let mut table = root;
+    for segment in path {
+        table = match (table.get_mut(&segment.name), segment.index) {
+            (Some(Value::Record(inner)), None) => inner,
+            (Some(Value::List(items)), Some(index)) => match items.get_mut(index) {
+                Some(Value::Record(inner)) => inner,
+                _ => unreachable!("arrays of tables hold records"),
+            },
+            _ => unreachable!("path was opened before"),
+        };
+    }
+    table
+}
+
+/// Writes a record as a TOML document: plain keys first, then one
+/// `[table]` or `[[array]]` section per nested record or list of records.
+/// Fails with the type name of the first value TOML cannot hold.
+pub fn to_toml(value: &Value) -> Result<String, &'static str> {
+    let Value::Record(fields) = value else {
+        return Err(value.type_name());
+    };
+    let mut out = String::new();
+    write_table(&mut out, &mut Vec::new(), fields)?;
+    Ok(out)
+}
+
+fn is_table_array(value: &Value) -> bool {
+    matches!(value, Value::List(items)
+        if !items.is_empty() && items.iter().all(|item| matches!(item, Value::Record(_))))
+}
+
+fn write_table(
+    out: &mut String,
+    path: &mut Vec<String>,
+    fields: &BTreeMap<String, Value>,
+) -> Result<(), &'static str> {
+    for (key, value) in fields {
+        if !matches!(value, Value::Record(_)) && !is_table_array(value) {
+            out.push_str(&toml_key(key));
+            out.push_str(" = ");
+            write_inline(out, value)?;
+            out.push('\n');
+        }
+    }
+    for (key, value) in fields {
+        path.push(toml_key(key));
+        match value {
+            Value::Record(inner) => {
+                let plain = inner
+                    .values()
+                    .any(|v| !matches!(v, Value::Record(_)) && !is_table_array(v));
+                // Tables holding only sub-tables need no header of their own.
+                if plain || inner.is_empty() {
+                    section(out, &format!("[{}]", path.join(".")));
+                }
+                write_table(out, path, inner)?;
+            }
+            Value::List(items) if is_table_array(value) => {
+                for item in items {
+                    let Value::Record(inner) = item else {
+                        unreachable!("checked by is_table_array")
+                    };
+                    section(out, &format!("[[{}]]", path.join(".")));
+                    write_table(out, path, inner)?;
+                }
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+fn section(out: &mut String, header: &str) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(header);
+    out.push('\n');
+}
+
+fn write_inline(out: &mut String, value: &Value) -> Result<(), &'static str> {
+    match value {
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(n) => out.push_str(&n.to_string()),
+        Value::Float(x) if x.is_nan() => out.push_str("nan"),
+        Value::Float(x) if x.is_infinite() => out.push_str(if *x > 0.0 { "inf" } else { "-inf" }),
+        // `{:?}` keeps the `.0` so the number reads back as a float.
+        Value::Float(x) => out.push_str(&format!("{x:?}")),
+        Value::String(s) => write_string(out, s),
+        Value::Path(p) => write_string(out, &p.to_string_lossy()),
+        Value::ByteSize(n) => out.push_str(&n.to_string()),
+        Value::DateTime(text) => out.push_str(text),
+        Value::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_inline(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Record(fields) => {
+            out.push('{');
+            for (i, (key, field)) in fields.iter().enumerate() {
+                out.push_str(if i > 0 { ", " } else { " " });
+                out.push_str(&toml_key(key));
+                out.push_str(" = ");
+                write_inline(out, field)?;
+            }
+            out.push_str(if fields.is_empty() { "}" } else { " }" });
+        }
+        other => return Err(other.type_name()),
+    }
+    Ok(())
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        let mut out = String::new();
+        write_string(&mut out, key);
+        out
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
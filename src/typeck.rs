@@ -0,0 +1,775 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::ast::{BinOp, Expr, Pattern, Program, Stmt, TypeAnn};
+use crate::error::{
+    NegateTypeError, OperandTypeError, TypeArityError, TypeError, TypeMismatchError,
+    UnknownTypeError,
+};
+use crate::span::Span;
+
+/// Static type used by [`TypeChecker`]. Unannotated code is inferred locally;
+/// anything that cannot be inferred is [`Type::Unknown`] and checked at runtime only.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// Not statically known; compatible with every type. Written `Any`.
+    Unknown,
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+    Path,
+    Bytes,
+    Duration,
+    ByteSize,
+    DateTime,
+    Record,
+    Range,
+    Table,
+    List(Box<Type>),
+    Result(Box<Type>, Box<Type>),
+    Fn(Box<Type>, Box<Type>),
+}
+
+impl Type {
+    pub fn from_ann(ann: &TypeAnn) -> Result<Type, TypeError> {
+        let arity = |expected: usize| -> Result<(), TypeError> {
+            if ann.args.len() == expected {
+                Ok(())
+            } else {
+                Err(TypeArityError {
+                    name: ann.name.clone(),
+                    expected,
+                    found: ann.args.len(),
+                    span: ann.span,
+                }
+                .into())
+            }
+        };
+        let arg = |i: usize| Type::from_ann(&ann.args[i]).map(Box::new);
+        let ty = match ann.name.as_str() {
+            "Any" => arity(0).map(|_| Type::Unknown)?,
+            "Null" => arity(0).map(|_| Type::Null)?,
+            "Bool" => arity(0).map(|_| Type::Bool)?,
+            "Int" => arity(0).map(|_| Type::Int)?,
+            "Float" => arity(0).map(|_| Type::Float)?,
+            "String" => arity(0).map(|_| Type::String)?,
+            "Path" => arity(0).map(|_| Type::Path)?,
+            "Bytes" => arity(0).map(|_| Type::Bytes)?,
+            "Duration" => arity(0).map(|_| Type::Duration)?,
+            "ByteSize" => arity(0).map(|_| Type::ByteSize)?,
+            "DateTime" => arity(0).map(|_| Type::DateTime)?,
+            "Record" => arity(0).map(|_| Type::Record)?,
+            "Range" => arity(0).map(|_| Type::Range)?,
+            "Table" => arity(0).map(|_| Type::Table)?,
+            "List" => {
+                arity(1)?;
+                Type::List(arg(0)?)
+            }
+            "Result" => {
+                arity(2)?;
+                Type::Result(arg(0)?, arg(1)?)
+            }
+            "Fn" => {
+                arity(2)?;
+                Type::Fn(arg(0)?, arg(1)?)
+            }
+            _ => {
+                return Err(UnknownTypeError {
+                    name: ann.name.clone(),
+                    span: ann.span,
+                }
+                .into());
+            }
+        };
+        Ok(ty)
+    }
+
+    /// Whether a value of type `actual` may be used where `self` is expected.
+    pub fn accepts(&self, actual: &Type) -> bool {
+        match (self, actual) {
+            (Type::Unknown, _) | (_, Type::Unknown) => true,
+            (Type::List(a), Type::List(b)) => a.accepts(b),
+            (Type::Result(a, e), Type::Result(b, f)) => a.accepts(b) && e.accepts(f),
+            // Parameters are contravariant: the given function must take
+            // anything the expected one may be passed.
+            (Type::Fn(a, r), Type::Fn(b, s)) => b.accepts(a) && r.accepts(s),
+            (a, b) => a == b,
+        }
+    }
+
+    /// Least type covering both branches; disagreement degrades to `Unknown`.
+    fn join(self, other: Type) -> Type {
+        match (self, other) {
+            (Type::List(a), Type::List(b)) => Type::List(Box::new(a.join(*b))),
+            (Type::Result(a, e), Type::Result(b, f)) => {
+                Type::Result(Box::new(a.join(*b)), Box::new(e.join(*f)))
+            }
+            (Type::Fn(a, r), Type::Fn(b, s)) => {
+                Type::Fn(Box::new(a.join(*b)), Box::new(r.join(*s)))
+            }
+            (a, b) if a == b => a,
+            _ => Type::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Unknown => write!(f, "Any"),
+            Type::Null => write!(f, "Null"),
+            Type::Bool => write!(f, "Bool"),
+            Type::Int => write!(f, "Int"),
+            Type::Float => write!(f, "Float"),
+            Type::String => write!(f, "String"),
+            Type::Path => write!(f, "Path"),
+            Type::Bytes => write!(f, "Bytes"),
+            Type::Duration => write!(f, "Duration"),
+            Type::ByteSize => write!(f, "ByteSize"),
+            Type::DateTime => write!(f, "DateTime"),
+            Type::Record => write!(f, "Record"),
+            Type::Range => write!(f, "Range"),
+            Type::Table => write!(f, "Table"),
+            Type::List(t) => write!(f, "List[{t}]"),
+            Type::Result(t, e) => write!(f, "Result[{t}, {e}]"),
+            Type::Fn(a, r) => write!(f, "Fn[{a}, {r}]"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct VarInfo {
+    ty: Type,
+    /// Annotation that fixed `ty`, if any.
+    ann: Option<Span>,
+    /// Parameter annotation when the value is an annotated function.
+    param_ann: Option<Span>,
+}
+
+/// Checking pass run between [`crate::resolver::Resolver`] and evaluation.
+/// Like the resolver it keeps its scopes across REPL lines.
+#[derive(Debug, Clone)]
+pub struct TypeChecker {
+    scopes: Vec<HashMap<String, VarInfo>>,
+    /// `import` aliases; their members are not typed yet.
+    modules: HashSet<String>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            modules: HashSet::new(),
+        }
+    }
+
+    /// Static type of a name bound so far, if known to the checker.
+    pub fn type_of(&self, name: &str) -> Option<&Type> {
+        self.lookup(name).map(|info| &info.ty)
+    }
+
+    pub fn check_program(&mut self, program: &Program) -> Result<(), TypeError> {
+        for stmt in &program.stmts {
+            self.check_stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Option<&VarInfo> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn bind(&mut self, name: &str, info: VarInfo) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), info);
+        }
+    }
+
+    fn bind_type(&mut self, name: &str, ty: Type) {
+        self.bind(
+            name,
+            VarInfo {
+                ty,
+                ann: None,
+                param_ann: None,
+            },
+        );
+    }
+
+    fn scoped<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, TypeError>,
+    ) -> Result<T, TypeError> {
+        self.scopes.push(HashMap::new());
+        let result = f(self);
+        self.scopes.pop();
+        result
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) -> Result<(), TypeError> {
+        match stmt {
+            Stmt::Expr(expr) => self.infer(expr).map(|_| ()),
+            Stmt::Assert { expr, message, .. } => {
+                self.infer(expr)?;
+                if let Some(message) = message {
+                    self.infer(message)?;
+                }
+                Ok(())
+            }
+            Stmt::Import { alias, .. } => {
+                self.modules.insert(alias.clone());
+                self.bind(
+                    alias,
+                    VarInfo {
+                        ty: Type::Unknown,
+                        ann: None,
+                        param_ann: None,
+                    },
+                );
+                Ok(())
+            }
+            Stmt::Export { .. } => Ok(()),
+            Stmt::Let {
+                name,
+                mutable,
+                ty,
+                expr,
+                ..
+            } => {
+                let param_ann = self.param_ann_of(expr);
+                let info = match ty {
+                    Some(ann) => {
+                        let declared = Type::from_ann(ann)?;
+                        self.check(expr, &declared, ann.span)?;
+                        VarInfo {
+                            ty: declared,
+                            ann: Some(ann.span),
+                            param_ann: param_ann.or(Some(ann.span)),
+                        }
+                    }
+                    None => {
+                        let inferred = self.infer(expr)?;
+                        VarInfo {
+                            // An unannotated `let mut` may later hold any type.
+                            ty: if *mutable { Type::Unknown } else { inferred },
+                            ann: None,
+                            param_ann,
+                        }
+                    }
+                };
+                self.bind(name, info);
+                Ok(())
+            }
+            Stmt::LetPattern {
+                pattern,
+                expr,
+                else_body,
+                ..
+            } => {
+                let ty = self.infer(expr)?;
+                self.bind_pattern(pattern, &ty);
+                // The fallback is bound to the pattern's single name as is.
+                if let Some(fallback) = else_body
+                    && let [name] = pattern.bound_names()[..]
+                {
+                    let matched = self.lookup(name).map_or(Type::Unknown, |info| info.ty.clone());
+                    let ty = matched.join(self.infer(fallback)?);
+                    self.bind_type(name, ty);
+                }
+                Ok(())
+            }
+            Stmt::Set { name, expr, .. } => self.check_set(name, expr),
+        }
+    }
+
+    /// An assignment must agree with the binding's annotation, if any.
+    fn check_set(&mut self, name: &str, expr: &Expr) -> Result<(), TypeError> {
+        match self.lookup(name).cloned() {
+            Some(VarInfo {
+                ty, ann: Some(ann), ..
+            }) => self.check(expr, &ty, ann),
+            _ => self.infer(expr).map(|_| ()),
+        }
+    }
+
+    fn param_ann_of(&self, expr: &Expr) -> Option<Span> {
+        match expr {
+            Expr::Fn { param_ty, .. } => param_ty.as_ref().map(|ann| ann.span),
+            Expr::Var(name, _) => self.lookup(name).and_then(|info| info.param_ann),
+            _ => None,
+        }
+    }
+
+    /// Checks `expr` against `expected`, descending into branches so that the
+    /// offending sub-expression is the one labelled.
+    fn check(&mut self, expr: &Expr, expected: &Type, ann: Span) -> Result<(), TypeError> {
+        match (expr, expected) {
+            (Expr::Io { body, ensure, .. }, _) => {
+                self.check(body, expected, ann)?;
+                if let Some(ensure) = ensure {
+                    self.infer(ensure)?;
+                }
+                Ok(())
+            }
+            (Expr::Match { subject, arms, .. }, _) => {
+                let subject_ty = self.infer(subject)?;
+                for arm in arms {
+                    self.scoped(|this| {
+                        this.bind_pattern(&arm.pattern, &subject_ty);
+                        if let Some(guard) = &arm.guard {
+                            this.infer(guard)?;
+                        }
+                        this.check(&arm.body, expected, ann)
+                    })?;
+                }
+                Ok(())
+            }
+            (
+                Expr::With {
+                    bindings,
+                    body,
+                    else_body,
+                    ..
+                },
+                _,
+            ) => {
+                self.scoped(|this| {
+                    for wb in bindings {
+                        let ty = this.infer(&wb.expr)?;
+                        this.bind_pattern(&wb.pattern, &ty);
+                    }
+                    this.check(body, expected, ann)
+                })?;
+                self.check(else_body, expected, ann)
+            }
+            (Expr::List(items, _), Type::List(item_ty)) => {
+                for item in items {
+                    self.check(item, item_ty, ann)?;
+                }
+                Ok(())
+            }
+            (Expr::Call { name, args, .. }, Type::Result(ok_ty, err_ty))
+                if (name == "ok" || name == "err") && args.len() == 1 =>
+            {
+                let inner = if name == "ok" { ok_ty } else { err_ty };
+                self.check(&args[0], inner, ann)
+            }
+            _ => {
+                let found = self.infer(expr)?;
+                if expected.accepts(&found) {
+                    Ok(())
+                } else {
+                    Err(mismatch(expected, &found, ann, expr.span()))
+                }
+            }
+        }
+    }
+
+    fn infer(&mut self, expr: &Expr) -> Result<Type, TypeError> {
+        match expr {
+            Expr::Bool(_, _) => Ok(Type::Bool),
+            Expr::Int(_, _) => Ok(Type::Int),
+            Expr::Duration(_, _) => Ok(Type::Duration),
+            Expr::Size(_, _) => Ok(Type::ByteSize),
+            Expr::String(_, _) => Ok(Type::String),
+            Expr::Path(_, _) => Ok(Type::Path),
+            Expr::Var(name, _) => Ok(self
+                .lookup(name)
+                .map(|info| info.ty.clone())
+                .unwrap_or(Type::Unknown)),
+            Expr::List(items, _) => {
+                let mut item_ty: Option<Type> = None;
+                for item in items {
+                    let ty = self.infer(item)?;
+                    item_ty = Some(match item_ty {
+                        Some(prev) => prev.join(ty),
+                        None => ty,
+                    });
+                }
+                Ok(Type::List(Box::new(item_ty.unwrap_or(Type::Unknown))))
+            }
+            Expr::Fn {
+                param,
+                param_ty,
+                ret_ty,
+                body,
+                ..
+            } => {
+                let param_type = match param_ty {
+                    Some(ann) => Type::from_ann(ann)?,
+                    None => Type::Unknown,
+                };
+                let ret_type = self.scoped(|this| {
+                    this.bind(
+                        param,
+                        VarInfo {
+                            ty: param_type.clone(),
+                            ann: param_ty.as_ref().map(|ann| ann.span),
+                            param_ann: None,
+                        },
+                    );
+                    match ret_ty {
+                        Some(ann) => {
+                            let declared = Type::from_ann(ann)?;
+                            this.check(body, &declared, ann.span)?;
+                            Ok(declared)
+                        }
+                        None => this.infer(body),
+                    }
+                })?;
+                Ok(Type::Fn(Box::new(param_type), Box::new(ret_type)))
+            }
+            Expr::Io { body, ensure, .. } => {
+                let ty = self.infer(body)?;
+                if let Some(ensure) = ensure {
+                    self.infer(ensure)?;
+                }
+                Ok(ty)
+            }
+            Expr::Call {
+                name, args, named, ..
+            } => {
+                let mut arg_types = Vec::with_capacity(args.len());
+                for arg in args {
+                    arg_types.push(self.infer(arg)?);
+                }
+                for arg in named {
+                    self.infer(&arg.value)?;
+                }
+                Ok(builtin_return_type(name, arg_types))
+            }
+            Expr::MethodCall {
+                receiver,
+                name,
+                name_span,
+                args,
+                named,
+                ..
+            } => {
+                let receiver_ty = self.infer(receiver)?;
+                let mut arg_types = Vec::with_capacity(args.len() + 1);
+                for arg in args {
+                    arg_types.push(self.infer(arg)?);
+                }
+                for arg in named {
+                    self.infer(&arg.value)?;
+                }
+                if matches!(receiver.as_ref(), Expr::Var(alias, _) if self.modules.contains(alias))
+                {
+                    return Ok(Type::Unknown);
+                }
+                match self.lookup(name).cloned() {
+                    Some(VarInfo {
+                        ty: Type::Fn(param, ret),
+                        param_ann,
+                        ..
+                    }) => {
+                        if !param.accepts(&receiver_ty) {
+                            return Err(mismatch(
+                                &param,
+                                &receiver_ty,
+                                param_ann.unwrap_or(*name_span),
+                                receiver.span(),
+                            ));
+                        }
+                        Ok(if args.is_empty() { *ret } else { Type::Unknown })
+                    }
+                    Some(_) => Ok(Type::Unknown),
+                    None => {
+                        arg_types.insert(0, receiver_ty);
+                        Ok(builtin_return_type(name, arg_types))
+                    }
+                }
+            }
+            Expr::BinOp { op, lhs, rhs, span } => {
+                let left = self.infer(lhs)?;
+                let right = self.infer(rhs)?;
+                binop_type(*op, left, right, *span)
+            }
+            Expr::Neg { operand, span } => match self.infer(operand)? {
+                ty @ (Type::Int | Type::Unknown) => Ok(ty),
+                found => Err(NegateTypeError {
+                    found: found.to_string(),
+                    span: *span,
+                }
+                .into()),
+            },
+            Expr::Match { subject, arms, .. } => {
+                let subject_ty = self.infer(subject)?;
+                let mut result: Option<Type> = None;
+                for arm in arms {
+                    let ty = self.scoped(|this| {
+                        this.bind_pattern(&arm.pattern, &subject_ty);
+                        if let Some(guard) = &arm.guard {
+                            this.infer(guard)?;
+                        }
+                        this.infer(&arm.body)
+                    })?;
+                    result = Some(match result {
+                        Some(prev) => prev.join(ty),
+                        None => ty,
+                    });
+                }
+                Ok(result.unwrap_or(Type::Unknown))
+            }
+            Expr::With {
+                bindings,
+                body,
+                else_body,
+                ..
+            } => {
+                let body_ty = self.scoped(|this| {
+                    for wb in bindings {
+                        let ty = this.infer(&wb.expr)?;
+                        this.bind_pattern(&wb.pattern, &ty);
+                    }
+                    this.infer(body)
+                })?;
+                Ok(body_ty.join(self.infer(else_body)?))
+            }
+            Expr::Pipe(lhs, rhs, _) => {
+                let input = self.infer(lhs)?;
+                let Expr::Var(name, span) = rhs.as_ref() else {
+                    return Ok(Type::Unknown);
+                };
+                match self.lookup(name).cloned() {
+                    Some(VarInfo {
+                        ty: Type::Fn(param, ret),
+                        param_ann,
+                        ..
+                    }) => {
+                        if !param.accepts(&input) {
+                            return Err(mismatch(
+                                &param,
+                                &input,
+                                param_ann.unwrap_or(*span),
+                                lhs.span(),
+                            ));
+                        }
+                        Ok(*ret)
+                    }
+                    Some(_) => Ok(Type::Unknown),
+                    None if matches!(name.as_str(), "identity" | "id") => Ok(input),
+                    None => Ok(Type::Unknown),
+                }
+            }
+            // Module members are not typed across files yet.
+            Expr::Field { target, .. } => {
+                self.infer(target)?;
+                Ok(Type::Unknown)
+            }
+            Expr::Range { start, end, .. } => {
+                self.infer(start)?;
+                if let Some(end) = end {
+                    self.infer(end)?;
+                }
+                Ok(Type::Range)
+            }
+            Expr::Index { target, index, .. } => {
+                let target_ty = self.infer(target)?;
+                // Indexing with a range value slices.
+                if self.infer(index)? == Type::Range {
+                    return Ok(match target_ty {
+                        ty @ (Type::List(_) | Type::String) => ty,
+                        _ => Type::Unknown,
+                    });
+                }
+                Ok(match target_ty {
+                    Type::List(item) => *item,
+                    Type::String => Type::String,
+                    _ => Type::Unknown,
+                })
+            }
+            Expr::Slice {
+                target, start, end, ..
+            } => {
+                let target_ty = self.infer(target)?;
+                for bound in [start, end].into_iter().flatten() {
+                    self.infer(bound)?;
+                }
+                Ok(match target_ty {
+                    ty @ (Type::List(_) | Type::String) => ty,
+                    _ => Type::Unknown,
+                })
+            }
+            Expr::For {
+                var,
+                iter,
+                guard,
+                body,
+                ..
+            } => {
+                let item_ty = match self.infer(iter)? {
+                    Type::List(item) => *item,
+                    Type::Range => Type::Int,
+                    _ => Type::Unknown,
+                };
+                self.scoped(|this| {
+                    this.bind_type(var, item_ty);
+                    if let Some(guard) = guard {
+                        this.infer(guard)?;
+                    }
+                    let mut last = Type::Null;
+                    for item in body {
+                        last = this.infer(item)?;
+                    }
+                    Ok(Type::List(Box::new(last)))
+                })
+            }
+            Expr::Try { body, rescue, .. } => {
+                let body_ty = self.infer(body)?;
+                match rescue {
+                    Some(rescue) => self.scoped(|this| {
+                        this.bind_type(&rescue.name, Type::Record);
+                        Ok(body_ty.join(this.infer(&rescue.body)?))
+                    }),
+                    None => Ok(Type::Result(Box::new(body_ty), Box::new(Type::Record))),
+                }
+            }
+            Expr::Set { name, expr, .. } => {
+                self.check_set(name, expr)?;
+                Ok(self
+                    .lookup(name)
+                    .map(|info| info.ty.clone())
+                    .unwrap_or(Type::Unknown))
+            }
+        }
+    }
+
+    fn bind_pattern(&mut self, pat: &Pattern, ty: &Type) {
+        match pat {
+            Pattern::Wildcard(_)
+            | Pattern::Int(_, _)
+            | Pattern::String(_, _)
+            | Pattern::Range { .. } => {}
+            Pattern::Var(name, _) => self.bind_type(name, ty.clone()),
+            Pattern::List(items, _) => {
+                let item_ty = match ty {
+                    Type::List(item) => (**item).clone(),
+                    _ => Type::Unknown,
+                };
+                for item in items {
+                    self.bind_pattern(item, &item_ty);
+                }
+            }
+            Pattern::Ok(inner, _) => match ty {
+                Type::Result(ok, _) => self.bind_pattern(inner, ok),
+                _ => self.bind_pattern(inner, &Type::Unknown),
+            },
+            Pattern::Err(inner, _) => match ty {
+                Type::Result(_, err) => self.bind_pattern(inner, err),
+                _ => self.bind_pattern(inner, &Type::Unknown),
+            },
+        }
+    }
+}
+
+fn builtin_return_type(name: &str, mut args: Vec<Type>) -> Type {
+    match name {
+        "ok" if args.len() == 1 => Type::Result(Box::new(args.remove(0)), Box::new(Type::Unknown)),
+        "err" if args.len() == 1 => Type::Result(Box::new(Type::Unknown), Box::new(args.remove(0))),
+        "is_ok" | "is_err" => Type::Bool,
+        "get" if !args.is_empty() => {
+            let item = match args.remove(0) {
+                Type::List(item) => *item,
+                Type::String => Type::String,
+                _ => Type::Unknown,
+            };
+            Type::Result(Box::new(item), Box::new(Type::Record))
+        }
+        "map" if args.len() == 2 => match (&args[0], &args[1]) {
+            (Type::List(_), Type::Fn(_, ret)) => Type::List(ret.clone()),
+            (Type::List(_), _) => Type::List(Box::new(Type::Unknown)),
+            _ => Type::Unknown,
+        },
+        "take" | "to_list" if !args.is_empty() => match args.remove(0) {
+            Type::List(item) => Type::List(item),
+            Type::Range => Type::List(Box::new(Type::Int)),
+            _ => Type::List(Box::new(Type::Unknown)),
+        },
+        // `parent`, `file_name` and `extension` may yield null.
+        "path" | "join" | "normalize" => Type::Path,
+        "table" => Type::Table,
+        "lines" | "split" | "words" => Type::List(Box::new(Type::String)),
+        "parse" => Type::List(Box::new(Type::Result(
+            Box::new(Type::Record),
+            Box::new(Type::Record),
+        ))),
+        "regex_find_all" => Type::List(Box::new(Type::Unknown)),
+        "regex_replace" => Type::String,
+        "regex_split" => Type::List(Box::new(Type::String)),
+        "parse_kv" => Type::Result(Box::new(Type::Record), Box::new(Type::Record)),
+        "parse_logfmt" => Type::Result(
+            Box::new(Type::List(Box::new(Type::Record))),
+            Box::new(Type::Record),
+        ),
+        "from_json" => Type::Result(Box::new(Type::Unknown), Box::new(Type::Record)),
+        "to_json" | "to_csv" | "to_toml" => Type::String,
+        "from_toml" | "from_ini" => Type::Result(Box::new(Type::Record), Box::new(Type::Record)),
+        "from_csv" => Type::Result(
+            Box::new(Type::List(Box::new(Type::Unknown))),
+            Box::new(Type::Record),
+        ),
+        "run_text" => Type::Result(Box::new(Type::String), Box::new(Type::Record)),
+        _ => Type::Unknown,
+    }
+}
+
+/// Result type of `left op right`. `==`/`!=` accept anything; ordering needs
+/// one orderable type on both sides; `+`/`-` need one additive type.
+fn binop_type(op: BinOp, left: Type, right: Type, span: Span) -> Result<Type, TypeError> {
+    let orderable = |t: &Type| {
+        matches!(
+            t,
+            Type::Unknown
+                | Type::Int
+                | Type::Float
+                | Type::String
+                | Type::Duration
+                | Type::ByteSize
+        )
+    };
+    let additive = |t: &Type| {
+        matches!(
+            t,
+            Type::Unknown | Type::Int | Type::Duration | Type::ByteSize
+        )
+    };
+    let ok = match op {
+        BinOp::Eq | BinOp::NotEq => true,
+        BinOp::Add | BinOp::Sub => additive(&left) && additive(&right) && left.accepts(&right),
+        _ => orderable(&left) && orderable(&right) && left.accepts(&right),
+    };
+    if !ok {
+        return Err(OperandTypeError {
+            op: op.symbol().to_string(),
+            left: left.to_string(),
+            right: right.to_string(),
+            span,
+        }
+        .into());
+    }
+    Ok(match (op.is_arithmetic(), left) {
+        (false, _) => Type::Bool,
+        (true, Type::Unknown) => right,
+        (true, left) => left,
+    })
+}
+
+fn mismatch(expected: &Type, found: &Type, annotation: Span, span: Span) -> TypeError {
+    TypeMismatchError {
+        expected: expected.to_string(),
+        found: found.to_string(),
+        annotation,
+        span,
+    }
+    .into()
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Duration suffixes and their length in milliseconds, largest first.
+pub const DURATION_UNITS: &[(&str, u64)] = &[
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("m", 60_000),
+    ("s", 1_000),
+    ("ms", 1),
+];
+
+/// Byte-size suffixes and their size in bytes.
+pub const SIZE_UNITS: &[(&str, u64)] = &[
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("KB", 1_000),
+    ("B", 1),
+];
+
+/// Value of an integer literal with a unit suffix, e.g. `30s` or `10MiB`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitLiteral {
+    Duration(Duration),
+    Size(u64),
+}
+
+/// Why a suffixed literal was rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitError {
+    Unknown,
+    Overflow,
+}
+
+/// Applies `suffix` to `amount`.
+pub fn apply_suffix(amount: u64, suffix: &str) -> Result<UnitLiteral, UnitError> {
+    if let Some((_, ms)) = DURATION_UNITS.iter().find(|(name, _)| *name == suffix) {
+        let ms = amount.checked_mul(*ms).ok_or(UnitError::Overflow)?;
+        return Ok(UnitLiteral::Duration(Duration::from_millis(ms)));
+    }
+    if let Some((_, bytes)) = SIZE_UNITS.iter().find(|(name, _)| *name == suffix) {
+        let bytes = amount.checked_mul(*bytes).ok_or(UnitError::Overflow)?;
+        if i64::try_from(bytes).is_err() {
+            return Err(UnitError::Overflow);
+        }
+        return Ok(UnitLiteral::Size(bytes));
+    }
+    Err(UnitError::Unknown)
+}
+
+/// Every accepted suffix, for diagnostics.
+pub fn suffix_list() -> String {
+    DURATION_UNITS
+        .iter()
+        .rev()
+        .chain(SIZE_UNITS.iter().rev())
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Displays a duration in the largest unit that divides it exactly, e.g. `90m`
+/// or `1250ms`, so the text reads back as the same literal.
+pub struct HumanDuration(pub Duration);
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.0.as_millis();
+        if total == 0 {
+            return write!(f, "0s");
+        }
+        let (name, ms) = DURATION_UNITS
+            .iter()
+            .map(|(name, ms)| (name, u128::from(*ms)))
+            .find(|(_, ms)| total.is_multiple_of(*ms))
+            .unwrap_or((&"ms", 1));
+        write!(f, "{}{name}", total / ms)
+    }
+}
+
+/// Displays a byte count in the largest unit that divides it exactly, e.g. `10MiB`.
+pub struct HumanSize(pub u64);
+
+impl fmt::Display for HumanSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, bytes) = SIZE_UNITS
+            .iter()
+            .filter(|(_, bytes)| self.0 != 0 && self.0.is_multiple_of(*bytes))
+            .max_by_key(|(_, bytes)| *bytes)
+            .unwrap_or(&("B", 1));
+        write!(f, "{}{name}", self.0 / bytes)
+    }
+}
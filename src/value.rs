@@ -1,12 +1,63 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
 
 use crate::ast::Expr;
+use crate::eval::EvalEnv;
+use crate::table::Table;
+use crate::units::{HumanDuration, HumanSize};
+use unicode_xid::UnicodeXID;
+
+/// Text of a module file, kept by the functions defined in it for error reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleSource {
+    pub path: String,
+    pub text: String,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct UserFunction {
     pub param: String,
     pub body: Expr,
+    /// Bindings in scope where the function was created.
+    pub env: Rc<EvalEnv>,
+    /// Set when the function comes from an imported module, whose spans point
+    /// into that file rather than the caller's.
+    pub module: Option<Rc<ModuleSource>>,
+}
+
+/// Integer range `start..end`; `end` is exclusive and `None` means unbounded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeValue {
+    pub start: i64,
+    pub end: Option<i64>,
+}
+
+impl RangeValue {
+    pub fn contains(&self, n: i64) -> bool {
+        n >= self.start && self.end.is_none_or(|end| n < end)
+    }
+
+    /// Number of items, or `None` for an unbounded range.
+    pub fn len(&self) -> Option<usize> {
+        self.end
+            .map(|end| usize::try_from(end.saturating_sub(self.start)).unwrap_or(0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+}
+
+/// Range with `map` stages that run only as items are pulled (e.g. by `take`).
+/// Stages run in the `io` context of whatever forces the seq.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazySeq {
+    pub source: RangeValue,
+    pub stages: Vec<UserFunction>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -16,23 +67,69 @@ pub enum Value {
     Int(i64),
     Float(f64),
     String(String),
+    /// Filesystem path; kept as OS bytes, never converted through UTF-8.
+    Path(PathBuf),
     Bytes(Vec<u8>),
+    Duration(Duration),
+    /// A quantity of bytes, e.g. from `10MiB`; unlike `Bytes` it holds no data.
+    ByteSize(u64),
+    /// A TOML date, time or date-time, kept as written (e.g. `1979-05-27T07:32:00Z`).
+    DateTime(String),
     List(Vec<Value>),
     Record(BTreeMap<String, Value>),
+    /// Exports of an imported module, bound under the alias it was imported as.
+    Module {
+        alias: String,
+        exports: BTreeMap<String, Value>,
+    },
     Function(UserFunction),
+    Range(RangeValue),
+    Seq(LazySeq),
+    Table(Table),
     Ok(Box<Value>),
     Err(Box<Value>),
 }
 
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Path(_) => "path",
+            Value::Bytes(_) => "bytes",
+            Value::Duration(_) => "duration",
+            Value::ByteSize(_) => "byte size",
+            Value::DateTime(_) => "datetime",
+            Value::List(_) => "list",
+            Value::Record(_) => "record",
+            Value::Module { .. } => "module",
+            Value::Function(_) => "function",
+            Value::Range(_) => "range",
+            Value::Seq(_) => "seq",
+            Value::Table(_) => "table",
+            Value::Ok(_) => "ok",
+            Value::Err(_) => "err",
+        }
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::Null => write!(f, "null"),
             Value::Bool(b) => write!(f, "{b}"),
             Value::Int(n) => write!(f, "{n}"),
-            Value::Float(x) => write!(f, "{x}"),
+            // `{:?}` keeps the `.0` of whole floats, as JSON and TOML output do.
+            Value::Float(x) => write!(f, "{x:?}"),
             Value::String(s) => write!(f, "{s:?}"),
+            Value::Path(p) => write!(f, "p{:?}", p.to_string_lossy()),
             Value::Bytes(b) => write!(f, "<bytes len={}>", b.len()),
+            Value::Duration(d) => write!(f, "{}", HumanDuration(*d)),
+            Value::ByteSize(n) => write!(f, "{}", HumanSize(*n)),
+            Value::DateTime(text) => write!(f, "{text}"),
             Value::List(items) => {
                 write!(f, "[")?;
                 for (i, v) in items.iter().enumerate() {
@@ -43,10 +140,49 @@ impl fmt::Display for Value {
                 }
                 write!(f, "]")
             }
-            Value::Record(_) => write!(f, "<record>"),
+            Value::Record(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {v}", record_key(key))?;
+                }
+                write!(f, "}}")
+            }
+            Value::Module { alias, .. } => write!(f, "<module {alias}>"),
             Value::Function(func) => write!(f, "<fn {}>", func.param),
+            Value::Range(range) => match range.end {
+                Some(end) => write!(f, "{}..{end}", range.start),
+                None => write!(f, "{}..", range.start),
+            },
+            Value::Seq(seq) => write!(
+                f,
+                "<seq {} |> {} map>",
+                Value::Range(seq.source.clone()),
+                seq.stages.len()
+            ),
+            Value::Table(table) => write!(
+                f,
+                "<table {} columns, {} rows>",
+                table.columns.len(),
+                table.rows.len()
+            ),
             Value::Ok(v) => write!(f, "Ok({v})"),
             Value::Err(v) => write!(f, "Err({v})"),
         }
     }
 }
+
+/// A record field name as written in displayed values: bare when it is a valid
+/// identifier, quoted otherwise.
+pub fn record_key(key: &str) -> Cow<'_, str> {
+    let mut chars = key.chars();
+    let is_ident = chars.next().is_some_and(|c| c == '_' || c.is_xid_start())
+        && chars.all(|c| c.is_xid_continue());
+    if is_ident {
+        Cow::Borrowed(key)
+    } else {
+        Cow::Owned(format!("{key:?}"))
+    }
+}
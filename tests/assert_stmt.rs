@@ -0,0 +1,160 @@
+use mictylish::error::{EvalAssertionError, EvalError};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme, NamedSource, Report};
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn assertion(source: &str) -> EvalAssertionError {
+    match run_err(source) {
+        EvalError::Assertion(err) => err,
+        other => panic!("expected assertion failure, got {other:?}"),
+    }
+}
+
+/// Each label as `(source text under it, label text)`.
+fn labels(source: &str, err: &EvalAssertionError) -> Vec<(String, String)> {
+    err.labels()
+        .expect("labels")
+        .map(|label| {
+            let text = &source[label.offset()..label.offset() + label.len()];
+            (
+                text.to_string(),
+                label.label().unwrap_or_default().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn pair(text: &str, label: &str) -> (String, String) {
+    (text.to_string(), label.to_string())
+}
+
+#[test]
+fn passing_assertion_is_silent() {
+    let env = run("let code = 0 assert code == 0 let after = 1");
+    assert_eq!(env.get("after"), Some(&Value::Int(1)));
+}
+
+#[test]
+fn failure_labels_operand_values() {
+    let source = "let code = 127 assert code == 0";
+    let err = assertion(source);
+    assert_eq!(err.to_string(), "assertion failed");
+    assert_eq!(labels(source, &err), vec![pair("code", "code = 127")]);
+}
+
+#[test]
+fn nested_operators_list_their_variables() {
+    let source = "let a = 2 let b = 3 assert a + b == 6";
+    let err = assertion(source);
+    assert_eq!(
+        labels(source, &err),
+        vec![pair("a + b", "5 where a = 2, b = 3")]
+    );
+}
+
+#[test]
+fn nested_calls_label_their_arguments() {
+    let source = "let xs = [1, 2] assert is_ok(get(xs, 3))";
+    let err = assertion(source);
+    assert_eq!(
+        labels(source, &err),
+        vec![pair(
+            "get(xs, 3)",
+            "Err({index: 3, len: 2}) where xs = [1, 2]"
+        )]
+    );
+}
+
+#[test]
+fn method_calls_lists_and_indexing_are_labelled() {
+    let source = "let xs = [3, 4] let n = 1 assert [xs[n], n].take(1) == [0]";
+    let err = assertion(source);
+    assert_eq!(
+        labels(source, &err),
+        vec![pair("[xs[n], n].take(1)", "[4] where xs = [3, 4], n = 1")]
+    );
+}
+
+#[test]
+fn message_is_part_of_the_error() {
+    let err = assertion(r#"let free = 1GB assert free >= 5GB, "disk nearly full""#);
+    assert_eq!(err.to_string(), "assertion failed: disk nearly full");
+}
+
+#[test]
+fn rendered_report_shows_values_in_source() {
+    let source = "let code = 127 assert code == 0";
+    let report = Report::new(run_err(source)).with_source_code(NamedSource::new("t", source));
+    let rendered = format!("{report:?}");
+    assert!(rendered.contains("code = 127"), "{rendered}");
+    assert!(rendered.contains("assertion failed"), "{rendered}");
+}
+
+/// Column of `needle` in the rendered line that contains it.
+fn column(rendered: &str, needle: &str) -> usize {
+    let line = rendered
+        .lines()
+        .find(|line| line.contains(needle))
+        .unwrap_or_else(|| panic!("no line with {needle:?} in\n{rendered}"));
+    line[..line.find(needle).unwrap()].chars().count()
+}
+
+/// The failure of `source` as the terminal shows it, without colors.
+fn render(source: &str) -> String {
+    let report =
+        Report::new(run_err(source)).with_source_code(NamedSource::new("t", source.to_string()));
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .render_report(&mut out, report.as_ref())
+        .expect("render");
+    out
+}
+
+#[test]
+fn rendered_labels_sit_under_their_operands() {
+    let rendered = render("let code = 127 assert code == 0");
+    let code = column(&rendered, "code == 0");
+    let pointer = column(&rendered, "╰── code = 127");
+    assert!((code..code + 4).contains(&pointer), "{rendered}");
+
+    let rendered = render("let xs = [1] assert get(xs, 0) == ok(2)");
+    let call = column(&rendered, "get(xs, 0)");
+    let pointer = column(&rendered, "╰── Ok(1) where xs = [1]");
+    assert!((call..call + 10).contains(&pointer), "{rendered}");
+    let ok = column(&rendered, "ok(2)");
+    let pointer = column(&rendered, "╰── Ok(2)");
+    assert!((ok..ok + 5).contains(&pointer), "{rendered}");
+}
+
+#[test]
+fn non_bool_condition_is_a_type_error() {
+    let err = run_err("assert [1]");
+    assert!(matches!(err, EvalError::Type(_)));
+}
+
+#[test]
+fn assertion_failure_stops_the_program() {
+    let program = parse_program("let x = 1 assert x == 2 let y = 3").expect("parse");
+    let mut env = EvalEnv::new();
+    assert!(eval_program(&mut env, &program).is_err());
+    assert_eq!(env.get("y"), None);
+}
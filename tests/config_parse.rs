@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+use mictylish::{ini, toml};
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn field<'v>(value: &'v Value, path: &[&str]) -> &'v Value {
+    path.iter().fold(value, |value, key| match value {
+        Value::Record(fields) => &fields[*key],
+        other => panic!("expected record at `{key}`, got {other:?}"),
+    })
+}
+
+#[test]
+fn cargo_manifest_maps_to_records() {
+    let manifest = toml::parse(
+        r#"[package]
+name = "mictylish"
+version = "0.1.0"
+edition = "2024"
+
+[dependencies]
+glob = "0.3"
+tokio = { version = "1", features = ["rt-multi-thread", "macros"] }
+"#,
+    )
+    .expect("parse");
+    assert_eq!(field(&manifest, &["package", "name"]), &string("mictylish"));
+    assert_eq!(
+        field(&manifest, &["dependencies", "tokio"]),
+        &record(&[
+            (
+                "features",
+                Value::List(vec![string("rt-multi-thread"), string("macros")])
+            ),
+            ("version", string("1")),
+        ])
+    );
+}
+
+#[test]
+fn scalars_keep_their_types() {
+    let doc = toml::parse(
+        "int = 1_000\nhex = 0xff\nneg = -7\nfloat = 6.5e-1\nyes = true\ninf = -inf\nraw = 'C:\\dir'\n",
+    )
+    .expect("parse");
+    assert_eq!(
+        doc,
+        record(&[
+            ("float", Value::Float(0.65)),
+            ("hex", Value::Int(255)),
+            ("inf", Value::Float(f64::NEG_INFINITY)),
+            ("int", Value::Int(1000)),
+            ("neg", Value::Int(-7)),
+            ("raw", string("C:\\dir")),
+            ("yes", Value::Bool(true)),
+        ])
+    );
+}
+
+#[test]
+fn datetimes_are_preserved_as_written() {
+    let doc = toml::parse(
+        "odt = 1979-05-27T07:32:00-08:00\nldt = 1979-05-27 07:32:00.5\nday = 1979-05-27\nat = 07:32:00\n",
+    )
+    .expect("parse");
+    assert_eq!(
+        field(&doc, &["odt"]),
+        &Value::DateTime("1979-05-27T07:32:00-08:00".to_string())
+    );
+    assert_eq!(
+        field(&doc, &["ldt"]),
+        &Value::DateTime("1979-05-27 07:32:00.5".to_string())
+    );
+    assert_eq!(
+        field(&doc, &["day"]),
+        &Value::DateTime("1979-05-27".to_string())
+    );
+    assert_eq!(
+        field(&doc, &["at"]),
+        &Value::DateTime("07:32:00".to_string())
+    );
+    let written = toml::to_toml(&doc).expect("toml");
+    assert!(
+        written.contains("odt = 1979-05-27T07:32:00-08:00\n"),
+        "{written}"
+    );
+    assert_eq!(toml::parse(&written).expect("reparse"), doc);
+}
+
+#[test]
+fn dotted_keys_and_arrays_of_tables() {
+    let doc = toml::parse(
+        r#"site."google.com" = true
+
+[[servers]]
+name = "alpha"
+ip.v4 = "10.0.0.1"
+
+[[servers]]
+name = "beta"
+
+[servers.limits]
+cpu = 2
+"#,
+    )
+    .expect("parse");
+    assert_eq!(field(&doc, &["site", "google.com"]), &Value::Bool(true));
+    let Value::List(servers) = field(&doc, &["servers"]) else {
+        panic!("expected list")
+    };
+    assert_eq!(servers.len(), 2);
+    assert_eq!(field(&servers[0], &["ip", "v4"]), &string("10.0.0.1"));
+    assert_eq!(field(&servers[1], &["limits", "cpu"]), &Value::Int(2));
+}
+
+#[test]
+fn multi_line_strings() {
+    let doc = toml::parse(
+        "a = \"\"\"\nline one\nline \\\n    two\"\"\"\nb = '''\nkeep \\n raw'''\nc = \"tab\\tand \\u00e9\"\n",
+    )
+    .expect("parse");
+    assert_eq!(field(&doc, &["a"]), &string("line one\nline two"));
+    assert_eq!(field(&doc, &["b"]), &string("keep \\n raw"));
+    assert_eq!(field(&doc, &["c"]), &string("tab\tand é"));
+}
+
+#[test]
+fn errors_point_into_the_text() {
+    let cases = [
+        ("a = 1\nb = \n", "expected a value, found end of line", 2, 5),
+        ("a = 1\na = 2\n", "duplicate key `a`", 2, 1),
+        ("[t]\nx = 1\n[t]\n", "`t` is already defined", 3, 1),
+        ("a = {x = 1}\n[a.b]\n", "`a` cannot be extended", 2, 1),
+        ("n = 0123\n", "invalid number `0123`", 1, 5),
+        ("s = \"open\n", "unterminated string", 1, 5),
+        ("a = 1 b = 2\n", "expected end of line, found `b`", 1, 7),
+    ];
+    for (text, message, line, column) in cases {
+        let err = toml::parse(text).expect_err(text);
+        assert_eq!(
+            (err.message.as_str(), err.line, err.column),
+            (message, line, column),
+            "{text:?}"
+        );
+    }
+}
+
+#[test]
+fn from_toml_returns_result() {
+    let env = run(r#"let name = match from_toml("[package]
+name = 'web'
+") do Ok(doc) -> doc.package.name Err(e) -> e.message end"#);
+    assert_eq!(env.get("name"), Some(&string("web")));
+    let env = run(r#"let r = from_toml("x = ")"#);
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::Err(Box::new(record(&[
+            ("column", Value::Int(5)),
+            ("line", Value::Int(1)),
+            ("message", string("expected a value, found end of input")),
+        ]))))
+    );
+}
+
+#[test]
+fn to_toml_writes_sections() {
+    let doc = record(&[
+        ("name", string("app")),
+        (
+            "server",
+            record(&[
+                ("port", Value::Int(8080)),
+                ("tls", record(&[("on", Value::Bool(false))])),
+            ]),
+        ),
+        (
+            "workers",
+            Value::List(vec![
+                record(&[("id", Value::Int(1))]),
+                record(&[("id", Value::Int(2))]),
+            ]),
+        ),
+    ]);
+    let written = toml::to_toml(&doc).expect("toml");
+    assert_eq!(
+        written,
+        "name = \"app\"\n\n[server]\nport = 8080\n\n[server.tls]\non = false\n\n[[workers]]\nid = 1\n\n[[workers]]\nid = 2\n"
+    );
+    assert_eq!(toml::parse(&written).expect("reparse"), doc);
+}
+
+#[test]
+fn to_toml_rejects_null_and_non_records() {
+    let err = run_err("let t = to_toml([1])");
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+    assert_eq!(toml::to_toml(&record(&[("a", Value::Null)])), Err("null"));
+}
+
+#[test]
+fn ini_sections_become_records() {
+    let doc = ini::parse(
+        "; legacy settings\nname = svc\n\n[database]\nhost = db.local\nport: 5432\nratio = 0.5\nversion = 1.10\ndebug = true\nquoted = \"42\"\n",
+    )
+    .expect("parse");
+    assert_eq!(
+        doc,
+        record(&[
+            (
+                "database",
+                record(&[
+                    ("debug", Value::Bool(true)),
+                    ("host", string("db.local")),
+                    ("port", Value::Int(5432)),
+                    ("quoted", string("42")),
+                    ("ratio", Value::Float(0.5)),
+                    ("version", string("1.10")),
+                ])
+            ),
+            ("name", string("svc")),
+        ])
+    );
+}
+
+#[test]
+fn ini_errors_carry_location() {
+    let err = ini::parse("[a]\nx = 1\n  junk\n").expect_err("junk");
+    assert_eq!((err.line, err.column), (3, 3));
+    assert_eq!(err.message, "expected `key = value`, found `junk`");
+    let err = ini::parse("[a]\nx = 1\nx = 2\n").expect_err("duplicate");
+    assert_eq!((err.message.as_str(), err.line), ("duplicate key `x`", 3));
+    let err = ini::parse("[open\n").expect_err("header");
+    assert_eq!(err.line, 1);
+    let env = run(r#"let r = from_ini("[s]
+=1")"#);
+    assert!(
+        matches!(env.get("r"), Some(Value::Err(_))),
+        "{:?}",
+        env.get("r")
+    );
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use mictylish::csv::{self, CsvOptions};
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn parse(text: &str) -> Value {
+    csv::parse(text, &CsvOptions::default()).expect("parse")
+}
+
+#[test]
+fn header_row_names_record_fields() {
+    let env = run(r#"let Ok(rows) = from_csv("name,pid,cpu
+sshd,812,0.5
+cron,90,1.25
+") else []"#);
+    assert_eq!(
+        env.get("rows"),
+        Some(&Value::List(vec![
+            record(&[
+                ("cpu", Value::Float(0.5)),
+                ("name", string("sshd")),
+                ("pid", Value::Int(812)),
+            ]),
+            record(&[
+                ("cpu", Value::Float(1.25)),
+                ("name", string("cron")),
+                ("pid", Value::Int(90)),
+            ]),
+        ]))
+    );
+}
+
+#[test]
+fn columns_are_coerced_as_a_whole() {
+    let rows = parse("id,zip,mixed,flag,note\n1,00123,1,true,\n2,04000,x,false,\n3,,2.5,,\n");
+    let Value::List(rows) = rows else {
+        panic!("expected list")
+    };
+    assert_eq!(
+        rows[0],
+        record(&[
+            ("flag", Value::Bool(true)),
+            ("id", Value::Int(1)),
+            ("mixed", string("1")),
+            ("note", string("")),
+            ("zip", string("00123")),
+        ])
+    );
+    assert_eq!(
+        rows[2],
+        record(&[
+            ("flag", Value::Null),
+            ("id", Value::Int(3)),
+            ("mixed", string("2.5")),
+            ("note", string("")),
+            ("zip", string("")),
+        ])
+    );
+}
+
+#[test]
+fn types_can_be_turned_off() {
+    let env = run(r#"let Ok(rows) = from_csv("n
+1
+", types: false) else []"#);
+    assert_eq!(
+        env.get("rows"),
+        Some(&Value::List(vec![record(&[("n", string("1"))])]))
+    );
+}
+
+#[test]
+fn quoted_fields_hold_delimiters_quotes_and_newlines() {
+    let rows = parse("msg,n\r\n\"a, b\",1\r\n\"say \"\"hi\"\"\nbye\",2\r\n");
+    assert_eq!(
+        rows,
+        Value::List(vec![
+            record(&[("msg", string("a, b")), ("n", Value::Int(1))]),
+            record(&[("msg", string("say \"hi\"\nbye")), ("n", Value::Int(2))]),
+        ])
+    );
+}
+
+#[test]
+fn delimiter_and_quote_are_configurable() {
+    let env = run(r#"let Ok(rows) = from_csv("a	b
+'x	y'	2
+", delimiter: "tab", quote: "'") else []"#);
+    assert_eq!(
+        env.get("rows"),
+        Some(&Value::List(vec![record(&[
+            ("a", string("x\ty")),
+            ("b", Value::Int(2)),
+        ])]))
+    );
+}
+
+#[test]
+fn ambiguous_delimiter_and_quote_are_rejected() {
+    let err = run_err(r#"let rows = from_csv("a", quote: ",")"#);
+    let EvalError::CsvOption(err) = err else {
+        panic!("expected a csv option error, got {err:?}");
+    };
+    assert_eq!(err.message, "`delimiter` and `quote` are both ','");
+    let err = run_err(
+        r#"let rows = from_csv("a", delimiter: "
+")"#,
+    );
+    assert!(matches!(err, EvalError::CsvOption(_)), "{err:?}");
+}
+
+#[test]
+fn quoted_empty_row_is_kept() {
+    let options = CsvOptions {
+        header: false,
+        types: false,
+        ..CsvOptions::default()
+    };
+    let rows = csv::parse("a\n\"\"\n\nb\n", &options).expect("parse");
+    let row = |cell: &str| Value::List(vec![string(cell)]);
+    assert_eq!(rows, Value::List(vec![row("a"), row(""), row("b")]));
+}
+
+#[test]
+fn without_header_rows_are_lists() {
+    let env = run(r#"let Ok(rows) = from_csv("1;a
+2;b", delimiter: ";", header: false) else []"#);
+    assert_eq!(
+        env.get("rows"),
+        Some(&Value::List(vec![
+            Value::List(vec![Value::Int(1), string("a")]),
+            Value::List(vec![Value::Int(2), string("b")]),
+        ]))
+    );
+}
+
+#[test]
+fn ragged_rows_are_err_records_with_row_number() {
+    let env = run(r#"let r = from_csv("a,b
+1,2
+
+3
+")"#);
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::Err(Box::new(record(&[
+            ("line", Value::Int(4)),
+            ("message", string("expected 2 fields, found 1")),
+            ("row", Value::Int(3)),
+        ]))))
+    );
+}
+
+#[test]
+fn quoting_errors_carry_position() {
+    let err = csv::parse("a\n\"open\n", &CsvOptions::default()).expect_err("unterminated");
+    assert_eq!(
+        (err.message.as_str(), err.row, err.line),
+        ("unterminated quoted field", 2, 2)
+    );
+    let err = csv::parse("a\nx\"y\n", &CsvOptions::default()).expect_err("stray quote");
+    assert_eq!(err.row, 2);
+    assert!(err.message.contains("unquoted field"), "{}", err.message);
+    let err = csv::parse("a,a\n", &CsvOptions::default()).expect_err("duplicate");
+    assert_eq!(err.message, "duplicate column `a`");
+}
+
+#[test]
+fn to_csv_writes_header_from_records() {
+    let rows = Value::List(vec![
+        record(&[("name", string("a,b")), ("size", Value::ByteSize(1024))]),
+        record(&[("name", string("plain")), ("extra", Value::Null)]),
+    ]);
+    let Value::List(rows) = rows else {
+        unreachable!()
+    };
+    assert_eq!(
+        csv::to_csv(&rows, &CsvOptions::default()).expect("csv"),
+        "extra,name,size\n,\"a,b\",1024\n,plain,\n"
+    );
+}
+
+#[test]
+fn to_csv_round_trips_lists() {
+    let env = run(r#"let t = to_csv([[1, "x"], [2, "y"]], delimiter: "tab")"#);
+    assert_eq!(env.get("t"), Some(&string("1\tx\n2\ty\n")));
+}
+
+#[test]
+fn nested_cells_are_rejected() {
+    let err = run_err("let t = to_csv([[[1]]])");
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+    let err = run_err(r#"let t = from_csv("a", delimiter: ",,")"#);
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+}
@@ -0,0 +1,188 @@
+use mictylish::error::ResolveError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn resolve_err(source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+#[test]
+fn io_call_outside_boundary_is_rejected_before_eval() {
+    let program =
+        parse_program(r#"let mut n = 1 set n = 2 let x = run_text("echo", "hi")"#).expect("parse");
+    let mut resolver = Resolver::new();
+    let err = resolver.resolve_program(&program).expect_err("io outside");
+    match err {
+        ResolveError::IoOutsideBoundary(e) => {
+            assert_eq!(e.name, "run_text");
+            assert_eq!(e.chain, "run_text");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn defining_impure_fn_is_allowed() {
+    let program = parse_program(r#"let get = fn p -> run_text(p) end"#).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect("defining an impure function has no effect");
+}
+
+#[test]
+fn piping_into_impure_fn_outside_io_is_rejected() {
+    let err = resolve_err(r#"let get = fn p -> run_text(p) end let y = "echo" |> get"#);
+    match err {
+        ResolveError::IoOutsideBoundary(e) => {
+            assert_eq!(e.name, "get");
+            assert_eq!(e.chain, "get -> run_text");
+            assert_eq!(e.via.len(), 1);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn impurity_propagates_through_fn_chain() {
+    let err = resolve_err(
+        r#"let get = fn p -> run_text(p) end
+           let deploy = fn p -> p |> get end
+           let y = "echo" |> deploy"#,
+    );
+    match err {
+        ResolveError::IoOutsideBoundary(e) => assert_eq!(e.chain, "deploy -> get -> run_text"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn alias_of_impure_fn_stays_impure() {
+    let err = resolve_err(
+        r#"let get = fn p -> run_text(p) end let fetch = get let y = "echo" |> fetch"#,
+    );
+    match err {
+        ResolveError::IoOutsideBoundary(e) => assert_eq!(e.chain, "fetch -> run_text"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn io_block_inside_fn_makes_it_pure() {
+    let env = run(
+        r#"let get = fn p -> io do run_text(p, "wrapped") end end let y = "echo" |> get"#,
+    );
+    assert_eq!(
+        env.get("y"),
+        Some(&Value::Ok(Box::new(Value::String("wrapped".to_string()))))
+    );
+}
+
+#[test]
+fn impure_match_arm_outside_io_is_rejected() {
+    let err = resolve_err(r#"let y = match 1 do 1 -> run_text("true") _ -> ok(1) end"#);
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)));
+}
+
+#[test]
+fn set_keeps_mutable_fn_binding_impure() {
+    let err = resolve_err(
+        r#"let mut f = fn p -> run_text(p) end
+           set f = fn p -> p end
+           let y = "echo" |> f"#,
+    );
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)));
+}
+
+#[test]
+fn impurity_is_tracked_across_repl_lines() {
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&parse_program("let get = fn p -> run_text(p) end").unwrap())
+        .unwrap();
+    let err = resolver
+        .resolve_program(&parse_program(r#""echo" |> get"#).unwrap())
+        .expect_err("impure call outside io");
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)));
+    resolver
+        .resolve_program(&parse_program(r#"io do "echo" |> get end"#).unwrap())
+        .expect("allowed inside io");
+}
+
+#[test]
+fn destructured_impure_fn_stays_impure() {
+    let err = resolve_err(r#"let [f] = [fn p -> run_text(p) end] let y = "echo" |> f"#);
+    match err {
+        ResolveError::IoOutsideBoundary(e) => assert_eq!(e.chain, "f -> run_text"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    let err = resolve_err(
+        r#"let fs = [fn p -> run_text(p) end]
+           let y = match fs do [g] -> "echo" |> g _ -> ok("") end"#,
+    );
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)), "{err:?}");
+    let err =
+        resolve_err(r#"let fs = [fn p -> run_text(p) end] let g = fs[0] let y = "echo" |> g"#);
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)), "{err:?}");
+}
+
+#[test]
+fn applying_a_parameter_follows_the_argument() {
+    let env = run(r#"let apply = fn f -> "echo" |> f end
+           let y = fn p -> p end |> apply
+           let each = fn f -> map([1, 2], f) end
+           let z = fn n -> n + n end |> each"#);
+    assert_eq!(env.get("y"), Some(&Value::String("echo".to_string())));
+    assert_eq!(
+        env.get("z"),
+        Some(&Value::List(vec![Value::Int(2), Value::Int(4)]))
+    );
+    let err = resolve_err(
+        r#"let apply = fn f -> "echo" |> f end
+           let get = fn p -> run_text(p) end
+           let y = get |> apply"#,
+    );
+    match err {
+        ResolveError::IoOutsideBoundary(e) => assert_eq!(e.chain, "apply -> f -> run_text"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn curried_function_carries_its_effect_to_the_result() {
+    let mk = "let mk = fn p -> fn a -> run_text(p, a) end end\n";
+    let err = resolve_err(&format!(
+        r#"{mk}let echo = "echo" |> mk
+           let y = "x" |> echo"#
+    ));
+    match err {
+        ResolveError::IoOutsideBoundary(e) => assert_eq!(e.chain, "echo -> run_text"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    let err = resolve_err(&format!(r#"{mk}let y = "x".mk("echo")"#));
+    match err {
+        ResolveError::IoOutsideBoundary(e) => assert_eq!(e.chain, "mk -> run_text"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    let env = run(&format!(
+        r#"{mk}let echo = "echo" |> mk
+           let y = io do "x" |> echo end"#
+    ));
+    let y = env.get("y");
+    assert!(matches!(y, Some(Value::Ok(_))), "{y:?}");
+}
@@ -0,0 +1,125 @@
+use mictylish::error::{EvalError, ResolveError};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::typeck::{Type, TypeChecker};
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn resolve_err(source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+fn ints(values: &[i64]) -> Value {
+    Value::List(values.iter().copied().map(Value::Int).collect())
+}
+
+#[test]
+fn for_over_list_yields_list() {
+    let env = run(r#"let xs = for x in ["a", "b"] do [x, x] end"#);
+    let pair = |s: &str| Value::List(vec![Value::String(s.into()), Value::String(s.into())]);
+    assert_eq!(
+        env.get("xs"),
+        Some(&Value::List(vec![pair("a"), pair("b")]))
+    );
+}
+
+#[test]
+fn for_over_range_and_seq() {
+    let env =
+        run("let a = for i in 0..3 do i end let b = for i in map(1..3, fn n -> [n] end) do i end");
+    assert_eq!(env.get("a"), Some(&ints(&[0, 1, 2])));
+    assert_eq!(
+        env.get("b"),
+        Some(&Value::List(vec![ints(&[1]), ints(&[2])]))
+    );
+}
+
+#[test]
+fn when_filter_skips_items() {
+    let env = run("let xs = for x in [1, 2, 3, 2] when x != 2 do x end");
+    assert_eq!(env.get("xs"), Some(&ints(&[1, 3])));
+}
+
+#[test]
+fn loop_variable_is_not_visible_after_loop() {
+    let err = resolve_err("let xs = for x in [1] do x end let y = x");
+    assert!(matches!(err, ResolveError::Undefined(_)));
+}
+
+#[test]
+fn loop_variable_cannot_shadow() {
+    let err = resolve_err("let x = 0 let xs = for x in [1] do x end");
+    assert!(matches!(err, ResolveError::Shadowing(_)));
+}
+
+#[test]
+fn set_in_loop_body_updates_accumulator() {
+    let env = run("let mut last = 0 for x in [1, 2, 3] do set last = x end");
+    assert_eq!(env.get("last"), Some(&Value::Int(3)));
+    assert_eq!(env.get("x"), None);
+}
+
+#[test]
+fn set_in_loop_body_sees_previous_iteration() {
+    let env = run("let mut acc = [] let ys = for x in 1..4 do set acc = [acc, x] x end");
+    let step = |prev: Value, n: i64| Value::List(vec![prev, Value::Int(n)]);
+    assert_eq!(env.get("acc"), Some(&step(step(step(ints(&[]), 1), 2), 3)));
+    assert_eq!(env.get("ys"), Some(&ints(&[1, 2, 3])));
+}
+
+#[test]
+fn nested_loops_propagate_sets() {
+    let env =
+        run("let mut last = [] for a in [1, 2] do for b in [3, 4] do set last = [a, b] end end");
+    assert_eq!(env.get("last"), Some(&ints(&[2, 4])));
+}
+
+#[test]
+fn set_of_immutable_binding_in_loop_is_rejected() {
+    let err = resolve_err("let n = 0 for x in [1] do set n = x end");
+    assert!(matches!(err, ResolveError::SetNotMutable(_)));
+}
+
+#[test]
+fn set_outside_statement_level_loop_is_rejected() {
+    let err = resolve_err("let mut n = 0 let f = fn y -> for x in [y] do set n = x end end");
+    assert!(matches!(err, ResolveError::SetOutsideLoop(_)));
+}
+
+#[test]
+fn unbounded_range_loop_is_rejected() {
+    let err = run_err("for i in 0.. do i end");
+    assert!(matches!(err, EvalError::UnboundedRange(_)));
+}
+
+#[test]
+fn for_has_list_type() {
+    let program = parse_program("let xs = for x in [1, 2] do x end").expect("parse");
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program).expect("typeck");
+    assert_eq!(
+        checker.type_of("xs"),
+        Some(&Type::List(Box::new(Type::Int)))
+    );
+}
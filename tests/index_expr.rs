@@ -0,0 +1,145 @@
+use mictylish::ast::{Expr, Stmt};
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn s(v: &str) -> Value {
+    Value::String(v.to_string())
+}
+
+#[test]
+fn parses_index_and_slice() {
+    let program = parse_program("let a = xs[0] let b = xs[1..] let c = xs[..-1]").expect("parse");
+    let exprs: Vec<&Expr> = program
+        .stmts
+        .iter()
+        .map(|stmt| match stmt {
+            Stmt::Let { expr, .. } => expr,
+            other => panic!("expected Let, got {other:?}"),
+        })
+        .collect();
+    assert!(matches!(exprs[0], Expr::Index { .. }));
+    assert!(matches!(exprs[1], Expr::Slice { start: Some(_), end: None, .. }));
+    assert!(matches!(exprs[2], Expr::Slice { start: None, end: Some(_), .. }));
+}
+
+#[test]
+fn index_into_list() {
+    let env = run("let xs = [10, 20, 30] let a = xs[0] let b = xs[2]");
+    assert_eq!(env.get("a"), Some(&Value::Int(10)));
+    assert_eq!(env.get("b"), Some(&Value::Int(30)));
+}
+
+#[test]
+fn negative_index_counts_from_end() {
+    let env = run("let xs = [10, 20, 30] let last = xs[-1] let first = xs[-3]");
+    assert_eq!(env.get("last"), Some(&Value::Int(30)));
+    assert_eq!(env.get("first"), Some(&Value::Int(10)));
+}
+
+#[test]
+fn chained_index_on_nested_list() {
+    let env = run("let m = [[1, 2], [3, 4]] let x = m[1][0]");
+    assert_eq!(env.get("x"), Some(&Value::Int(3)));
+}
+
+#[test]
+fn slice_list() {
+    let env = run("let xs = [1, 2, 3, 4] let a = xs[1..3] let b = xs[2..] let c = xs[..-1]");
+    assert_eq!(
+        env.get("a"),
+        Some(&Value::List(vec![Value::Int(2), Value::Int(3)]))
+    );
+    assert_eq!(
+        env.get("b"),
+        Some(&Value::List(vec![Value::Int(3), Value::Int(4)]))
+    );
+    assert_eq!(
+        env.get("c"),
+        Some(&Value::List(vec![Value::Int(1), Value::Int(2), Value::Int(3)]))
+    );
+}
+
+#[test]
+fn string_index_counts_characters() {
+    let env = run(r#"let s = "日本語ok" let a = s[1] let b = s[-1] let c = s[0..3]"#);
+    assert_eq!(env.get("a"), Some(&s("本")));
+    assert_eq!(env.get("b"), Some(&s("k")));
+    assert_eq!(env.get("c"), Some(&s("日本語")));
+}
+
+#[test]
+fn index_out_of_range_is_eval_error() {
+    let err = run_err("let xs = [1, 2] let x = xs[2]");
+    match err {
+        EvalError::IndexOutOfRange(e) => {
+            assert_eq!(e.index, 2);
+            assert_eq!(e.len, 2);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(matches!(
+        run_err("let xs = [1, 2] let x = xs[-3]"),
+        EvalError::IndexOutOfRange(_)
+    ));
+    assert!(matches!(
+        run_err("let xs = [1, 2] let x = xs[0..5]"),
+        EvalError::IndexOutOfRange(_)
+    ));
+}
+
+#[test]
+fn indexing_non_sequence_is_type_error() {
+    assert!(matches!(run_err("let n = 5 let x = n[0]"), EvalError::Type(_)));
+    assert!(matches!(
+        run_err(r#"let xs = [1] let x = xs["a"]"#),
+        EvalError::Type(_)
+    ));
+}
+
+#[test]
+fn get_returns_result() {
+    let env = run("let xs = [1, 2] let a = get(xs, -1) let b = get(xs, 5)");
+    assert_eq!(env.get("a"), Some(&Value::Ok(Box::new(Value::Int(2)))));
+    match env.get("b") {
+        Some(Value::Err(inner)) => match inner.as_ref() {
+            Value::Record(fields) => {
+                assert_eq!(fields.get("index"), Some(&Value::Int(5)));
+                assert_eq!(fields.get("len"), Some(&Value::Int(2)));
+            }
+            other => panic!("expected record, got {other:?}"),
+        },
+        other => panic!("expected Err, got {other:?}"),
+    }
+}
+
+#[test]
+fn spaced_bracket_in_match_arm_is_next_pattern() {
+    let env = run("let x = match [2] do [1] -> 10 [n] -> n end");
+    assert_eq!(env.get("x"), Some(&Value::Int(2)));
+}
+
+#[test]
+fn negative_int_literal_and_pattern() {
+    let env = run("let x = match -1 do -1 -> \"neg\" _ -> \"other\" end");
+    assert_eq!(env.get("x"), Some(&s("neg")));
+}
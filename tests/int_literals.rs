@@ -0,0 +1,139 @@
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::lexer::lex;
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::token::TokenKind;
+use mictylish::value::Value;
+use miette::Diagnostic;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn int(source: &str) -> u64 {
+    match lex(source).expect("lex")[0].kind {
+        TokenKind::Int(n) => n,
+        ref other => panic!("expected integer, got {other:?}"),
+    }
+}
+
+#[test]
+fn prefixed_literals() {
+    assert_eq!(int("0x1F"), 31);
+    assert_eq!(int("0xff"), 255);
+    assert_eq!(int("0o755"), 0o755);
+    assert_eq!(int("0b1010"), 10);
+}
+
+#[test]
+fn digit_separators() {
+    assert_eq!(int("1_000_000"), 1_000_000);
+    assert_eq!(int("0xFFFF_0000"), 0xFFFF_0000);
+    assert_eq!(int("0b1111_0000"), 0b1111_0000);
+    assert_eq!(
+        lex("1_000ms").expect("lex")[0].kind,
+        TokenKind::Duration(std::time::Duration::from_secs(1))
+    );
+}
+
+#[test]
+fn unary_minus_applies_to_every_form() {
+    let env = run("let a = -0x10 let b = -1_000 let c = -0b1");
+    assert_eq!(env.get("a"), Some(&Value::Int(-16)));
+    assert_eq!(env.get("b"), Some(&Value::Int(-1000)));
+    assert_eq!(env.get("c"), Some(&Value::Int(-1)));
+}
+
+#[test]
+fn full_i64_range_is_accepted() {
+    let env = run("let lo = -9223372036854775808 let hi = 0x7fff_ffff_ffff_ffff");
+    assert_eq!(env.get("lo"), Some(&Value::Int(i64::MIN)));
+    assert_eq!(env.get("hi"), Some(&Value::Int(i64::MAX)));
+}
+
+#[test]
+fn min_int_is_accepted_after_open_brackets_and_commas() {
+    let env = run(
+        "let a = ok(-9223372036854775808)
+let b = [-9223372036854775808]
+let c = [0,-9223372036854775808]",
+    );
+    assert_eq!(
+        env.get("a"),
+        Some(&Value::Ok(Box::new(Value::Int(i64::MIN))))
+    );
+    assert_eq!(env.get("b"), Some(&Value::List(vec![Value::Int(i64::MIN)])));
+    assert_eq!(
+        env.get("c"),
+        Some(&Value::List(vec![Value::Int(0), Value::Int(i64::MIN)]))
+    );
+}
+
+#[test]
+fn out_of_range_literal_reports_supported_range() {
+    let err = parse_program("let x = 0xffff_ffff_ffff_ffff").expect_err("should fail");
+    assert!(err.to_string().contains("out of range"), "{err}");
+    let help = err.help().expect("help").to_string();
+    assert!(
+        help.contains("-9223372036854775808 to 9223372036854775807"),
+        "{help}"
+    );
+    let err = parse_program("let x = 9223372036854775808").expect_err("should fail");
+    assert!(err.to_string().contains("out of range"), "{err}");
+}
+
+#[test]
+fn invalid_digit_is_reported_at_the_digit() {
+    let err = parse_program("let m = 0o758").expect_err("should fail");
+    assert!(
+        err.to_string()
+            .contains("invalid digit '8' in octal literal"),
+        "{err}"
+    );
+    assert_eq!(err.span.offset(), 12);
+    let err = parse_program("let m = 0x").expect_err("should fail");
+    assert!(err.to_string().contains("no digits"), "{err}");
+}
+
+#[test]
+fn subtraction_of_min_magnitude_is_not_folded() {
+    let err = parse_program("let a = 1 let b = a-9223372036854775808").expect_err("should fail");
+    assert!(err.to_string().contains("out of range"), "{err}");
+}
+
+#[test]
+fn minus_after_an_operand_subtracts_however_it_is_spaced() {
+    let env =
+        run("let n = 5 let a = n - 1 let b = n-1 let c = n -1 let d = [5 -3] let e = [7, 8][0 -1]");
+    for (name, value) in [("a", 4), ("b", 4), ("c", 4), ("e", 8)] {
+        assert_eq!(env.get(name), Some(&Value::Int(value)), "{name}");
+    }
+    assert_eq!(env.get("d"), Some(&Value::List(vec![Value::Int(2)])));
+    let env = run("let x = match -1 do 2 -> 1\n-1 -> 0 _ -> 3 end");
+    assert_eq!(env.get("x"), Some(&Value::Int(0)));
+}
+
+#[test]
+fn prefix_minus_negates_any_operand() {
+    let env = run(
+        "let a = 4 let b = -a let c = -(3) let d = --a let e = 1 - -a
+let inc = fn x -> x + 1 end let f = -2.inc()",
+    );
+    for (name, value) in [("b", -4), ("c", -3), ("d", 4), ("e", 5), ("f", -1)] {
+        assert_eq!(env.get(name), Some(&Value::Int(value)), "{name}");
+    }
+    let program = parse_program(r#"let s = -"a""#).expect("parse");
+    let err = eval_program(&mut EvalEnv::new(), &program).expect_err("not an int");
+    assert!(
+        err.to_string().contains("expected int, found string"),
+        "{err}"
+    );
+    let program = parse_program("let m = -9223372036854775808 let n = -m").expect("parse");
+    let err = eval_program(&mut EvalEnv::new(), &program).expect_err("overflow");
+    assert!(err.to_string().contains("overflows"), "{err}");
+}
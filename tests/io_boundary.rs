@@ -1,4 +1,4 @@
-use mictylish::error::EvalError;
+use mictylish::error::{EvalError, ResolveError};
 use mictylish::eval::{eval_program, EvalEnv};
 use mictylish::parser::parse_program;
 use mictylish::resolver::Resolver;
@@ -40,8 +40,12 @@ fn io_block_allows_run_text() {
 
 #[test]
 fn run_text_outside_io_is_rejected() {
-    let err = run_err(r#"let x = run_text("echo", "hi")"#);
-    assert!(matches!(err, EvalError::IoRequired(_)));
+    let program = parse_program(r#"let x = run_text("echo", "hi")"#).expect("parse");
+    let mut resolver = Resolver::new();
+    let err = resolver
+        .resolve_program(&program)
+        .expect_err("io outside boundary");
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)));
 }
 
 #[test]
@@ -144,3 +148,34 @@ fn with_ok_pattern_falls_to_else_on_err() {
     );
     assert_eq!(env.get("msg"), Some(&Value::String("failed".to_string())));
 }
+
+#[test]
+fn lazy_seq_runs_io_only_where_it_is_forced() {
+    let source = r#"let get = fn n -> run_text("echo", "x") end
+let s = io do map(0.., get) end
+let first = take(s, 2)"#;
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    let err = resolver
+        .resolve_program(&program)
+        .expect_err("forcing the seq outside io");
+    match err {
+        ResolveError::IoOutsideBoundary(e) => assert_eq!(e.chain, "take -> s -> run_text"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    let mut env = EvalEnv::new();
+    let err = eval_program(&mut env, &program).expect_err("io outside boundary");
+    assert!(matches!(err, EvalError::IoRequired(_)), "{err:?}");
+
+    let env = run(
+        r#"let get = fn n -> run_text("echo", "x") end
+let s = io do map(0.., get) end
+let first = io do take(s, 1) end"#,
+    );
+    assert_eq!(
+        env.get("first"),
+        Some(&Value::List(vec![Value::Ok(Box::new(Value::String(
+            "x".to_string()
+        )))]))
+    );
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+use miette::{Diagnostic, NamedSource, Report};
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+/// A path that does not exist yet, unique to this test.
+fn marker(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("mictylish-ensure-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn ensure_runs_after_successful_body() {
+    let mark = marker("ok");
+    let env = run(&format!(
+        r#"let r = io do [1, 2][1] ensure run_text("touch", p"{}") end"#,
+        mark.display()
+    ));
+    assert_eq!(env.get("r"), Some(&Value::Int(2)));
+    assert!(mark.exists());
+}
+
+#[test]
+fn ensure_runs_when_body_fails_and_keeps_original_error() {
+    let mark = marker("fail");
+    let err = run_err(&format!(
+        r#"let r = io do [1][5] ensure run_text("touch", p"{}") end"#,
+        mark.display()
+    ));
+    assert!(matches!(err, EvalError::IndexOutOfRange(_)), "{err:?}");
+    assert!(mark.exists());
+}
+
+#[test]
+fn cleanup_error_after_success_is_reported() {
+    let err = run_err("let r = io do 1 ensure [1][5] end");
+    assert!(matches!(err, EvalError::IndexOutOfRange(_)), "{err:?}");
+}
+
+#[test]
+fn cleanup_error_is_attached_to_original() {
+    let source = r#"let r = io do [1][5] ensure join("a", "b") end"#;
+    let err = run_err(source);
+    let EvalError::Ensure(ensure) = &err else {
+        panic!("expected ensure error, got {err:?}");
+    };
+    assert!(matches!(*ensure.original, EvalError::IndexOutOfRange(_)));
+    assert_eq!(
+        err.code().map(|code| code.to_string()).as_deref(),
+        Some("mictylish::eval_index_out_of_range")
+    );
+    let related: Vec<_> = err.related().expect("related").collect();
+    assert_eq!(related.len(), 1);
+    assert_eq!(
+        related[0].code().map(|code| code.to_string()).as_deref(),
+        Some("mictylish::eval_type")
+    );
+
+    let rendered = format!(
+        "{:?}",
+        Report::new(err).with_source_code(NamedSource::new("t", source))
+    );
+    assert!(rendered.contains("index"), "{rendered}");
+    assert!(rendered.contains("expected path"), "{rendered}");
+}
+
+#[test]
+fn rescue_sees_the_original_error_kind() {
+    let env = run("let k = io do try do io do [1][5] ensure [2][9] end rescue e -> e.kind end end");
+    assert_eq!(
+        env.get("k"),
+        Some(&Value::String("index_out_of_range".to_string()))
+    );
+}
+
+#[test]
+fn ensure_is_contextual() {
+    let env = run("let ensure = 1 let r = io do ensure ensure ensure end");
+    assert_eq!(env.get("r"), Some(&Value::Int(1)));
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::json;
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+/// Evaluates `source` with `text` bound to `json`; the resolver is skipped
+/// because it cannot see the pre-bound name (string literals have no escapes).
+fn eval_with_text(source: &str, json: &str) -> Result<EvalEnv, EvalError> {
+    let program = parse_program(source).expect("parse");
+    let mut env = EvalEnv::new();
+    env.insert("text".to_string(), Value::String(json.to_string()));
+    eval_program(&mut env, &program).map(|_| env)
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+#[test]
+fn objects_arrays_and_numbers_map_to_values() {
+    let value =
+        json::parse(r#"{"name": "api", "replicas": 3, "cpu": 0.5, "tags": ["a", null, true]}"#)
+            .expect("parse");
+    assert_eq!(
+        value,
+        record(&[
+            ("cpu", Value::Float(0.5)),
+            ("name", string("api")),
+            ("replicas", Value::Int(3)),
+            (
+                "tags",
+                Value::List(vec![string("a"), Value::Null, Value::Bool(true)])
+            ),
+        ])
+    );
+}
+
+#[test]
+fn numbers_outside_i64_become_floats() {
+    assert_eq!(json::parse("-12").expect("int"), Value::Int(-12));
+    assert_eq!(json::parse("1e3").expect("exp"), Value::Float(1000.0));
+    assert_eq!(
+        json::parse("18446744073709551616").expect("big"),
+        Value::Float(18446744073709551616.0)
+    );
+    assert!(json::parse("01").is_err());
+    assert!(json::parse("1.").is_err());
+}
+
+#[test]
+fn string_escapes_are_decoded() {
+    assert_eq!(
+        json::parse(r#""tab\tquote\" é 🚀""#).expect("parse"),
+        string("tab\tquote\" é 🚀")
+    );
+    assert!(json::parse(r#""\ud83d""#).is_err());
+}
+
+#[test]
+fn from_json_returns_ok_and_fields_are_accessible() {
+    let env = eval_with_text(
+        "let name = match from_json(text) do Ok(v) -> v.metadata.name Err(e) -> e.message end",
+        r#"{"metadata": {"name": "web-1"}}"#,
+    )
+    .expect("eval");
+    assert_eq!(env.get("name"), Some(&string("web-1")));
+}
+
+#[test]
+fn parse_errors_are_err_records_with_position() {
+    let env =
+        eval_with_text("let r = from_json(text)", "{\n  \"a\": 1,\n  \"b\" 2\n}").expect("eval");
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::Err(Box::new(record(&[
+            ("column", Value::Int(7)),
+            ("line", Value::Int(3)),
+            ("message", string("expected `:`, found `2`")),
+        ]))))
+    );
+}
+
+#[test]
+fn trailing_content_and_deep_nesting_are_rejected() {
+    let err = json::parse("[1] x").expect_err("trailing");
+    assert_eq!((err.line, err.column), (1, 5));
+    let deep = "[".repeat(200) + &"]".repeat(200);
+    let err = json::parse(&deep).expect_err("deep");
+    assert!(err.message.contains("nesting deeper"), "{}", err.message);
+}
+
+#[test]
+fn to_json_is_compact_by_default() {
+    let env = run(r#"let j = to_json([1, "two", [true], 1KiB])"#);
+    assert_eq!(env.get("j"), Some(&string(r#"[1,"two",[true],1024]"#)));
+}
+
+#[test]
+fn to_json_pretty_indents() {
+    let value = record(&[
+        ("empty", Value::List(vec![])),
+        ("items", Value::List(vec![Value::Int(1), Value::Float(2.0)])),
+        ("name", string("a\"b")),
+    ]);
+    assert_eq!(
+        json::to_json(&value, true).expect("json"),
+        r#"{
+  "empty": [],
+  "items": [
+    1,
+    2.0
+  ],
+  "name": "a\"b"
+}"#
+    );
+    let env = run("let j = to_json([1], pretty: true)");
+    assert_eq!(env.get("j"), Some(&string("[\n  1\n]")));
+}
+
+#[test]
+fn values_round_trip() {
+    let source = r#"{"a":[1,2.5,"x\n"],"b":{"c":null,"d":false}}"#;
+    let value = json::parse(source).expect("parse");
+    assert_eq!(json::to_json(&value, false).expect("json"), source);
+}
+
+#[test]
+fn true_and_false_are_literals() {
+    let env = run("let t = true let f = false");
+    assert_eq!(env.get("t"), Some(&Value::Bool(true)));
+    assert_eq!(env.get("f"), Some(&Value::Bool(false)));
+    assert!(parse_program("let true = 1").is_err());
+}
+
+#[test]
+fn functions_cannot_be_written_as_json() {
+    let program = parse_program("let f = fn x -> x end let j = to_json(f)").expect("parse");
+    let mut env = EvalEnv::new();
+    let err = eval_program(&mut env, &program).expect_err("should fail");
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+    assert!(err.to_string().contains("function"), "{err}");
+}
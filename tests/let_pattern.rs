@@ -0,0 +1,121 @@
+use mictylish::ast::{Pattern, Stmt};
+use mictylish::error::{EvalError, ResolveError};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn resolve_err(source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+#[test]
+fn parses_list_destructuring_let() {
+    let program = parse_program("let [a, b] = pair").expect("parse");
+    match &program.stmts[0] {
+        Stmt::LetPattern {
+            pattern: Pattern::List(items, _),
+            else_body: None,
+            ..
+        } => assert_eq!(items.len(), 2),
+        other => panic!("expected LetPattern, got {other:?}"),
+    }
+}
+
+#[test]
+fn list_destructuring_binds_each_name() {
+    let env = run(r#"let pair = [1, "two"] let [a, b] = pair"#);
+    assert_eq!(env.get("a"), Some(&Value::Int(1)));
+    assert_eq!(env.get("b"), Some(&Value::String("two".to_string())));
+}
+
+#[test]
+fn nested_destructuring_with_wildcard() {
+    let env = run("let [[x, _], y] = [[1, 2], 3]");
+    assert_eq!(env.get("x"), Some(&Value::Int(1)));
+    assert_eq!(env.get("y"), Some(&Value::Int(3)));
+    assert_eq!(env.get("_"), None);
+}
+
+#[test]
+fn list_shape_mismatch_is_eval_error() {
+    let program = parse_program("let [a, b] = [1]").expect("parse");
+    let mut env = EvalEnv::new();
+    let err = eval_program(&mut env, &program).expect_err("length mismatch");
+    assert!(matches!(err, EvalError::LetPattern(_)));
+}
+
+#[test]
+fn refutable_let_with_else_uses_value_on_match() {
+    let env = run(r#"let Ok(out) = io do run_text("echo", "hi") end else "fallback""#);
+    assert_eq!(env.get("out"), Some(&Value::String("hi".to_string())));
+}
+
+#[test]
+fn refutable_let_with_else_uses_fallback() {
+    let env = run(r#"let Ok(out) = io do run_text("false") end else "fallback""#);
+    assert_eq!(env.get("out"), Some(&Value::String("fallback".to_string())));
+}
+
+#[test]
+fn fallback_is_bound_without_matching() {
+    let env = run(r#"let Ok(out) = err(1) else ok("wrapped")"#);
+    assert_eq!(
+        env.get("out"),
+        Some(&Value::Ok(Box::new(Value::String("wrapped".to_string()))))
+    );
+}
+
+#[test]
+fn let_else_requires_a_single_binding() {
+    let err = resolve_err("let Ok([a, b]) = ok([1, 2]) else [3, 4]");
+    let ResolveError::LetElseBinding(err) = err else {
+        panic!("expected a let-else binding error, got {err:?}");
+    };
+    assert_eq!(err.found, 2);
+    let err = resolve_err("let Ok(_) = ok(1) else 0");
+    assert!(matches!(err, ResolveError::LetElseBinding(_)), "{err:?}");
+}
+
+#[test]
+fn refutable_let_without_else_is_rejected() {
+    let err = resolve_err("let Ok(out) = ok(1)");
+    assert!(matches!(err, ResolveError::RefutableLet(_)));
+}
+
+#[test]
+fn destructuring_rejects_shadowing() {
+    let err = resolve_err("let a = 1 let [a, b] = [2, 3]");
+    assert!(matches!(err, ResolveError::Shadowing(_)));
+}
+
+#[test]
+fn destructuring_rejects_duplicate_names_in_pattern() {
+    let err = resolve_err("let [a, a] = [2, 3]");
+    assert!(matches!(err, ResolveError::Shadowing(_)));
+}
+
+#[test]
+fn else_branch_cannot_see_pattern_bindings() {
+    let err = resolve_err("let Ok(v) = ok(1) else ok(v)");
+    assert!(matches!(err, ResolveError::Undefined(_)));
+}
+
+#[test]
+fn else_branch_io_outside_boundary_is_rejected() {
+    let err = resolve_err(r#"let Ok(v) = ok(1) else run_text("true")"#);
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)));
+}
@@ -0,0 +1,104 @@
+use mictylish::ast::{Expr, Stmt};
+use mictylish::error::ResolveError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn resolve_err(source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+fn ints(values: &[i64]) -> Value {
+    Value::List(values.iter().copied().map(Value::Int).collect())
+}
+
+#[test]
+fn parses_method_call_distinct_from_field() {
+    let program = parse_program("xs.take(2) r.code").expect("parse");
+    match &program.stmts[0] {
+        Stmt::Expr(Expr::MethodCall { name, args, .. }) => {
+            assert_eq!(name, "take");
+            assert_eq!(args.len(), 1);
+        }
+        other => panic!("expected MethodCall, got {other:?}"),
+    }
+    assert!(matches!(&program.stmts[1], Stmt::Expr(Expr::Field { .. })));
+}
+
+#[test]
+fn builtin_method_passes_receiver_first() {
+    let env = run("let xs = [1, 2, 3] let a = xs.take(2) let b = xs.get(0)");
+    assert_eq!(env.get("a"), Some(&ints(&[1, 2])));
+    assert_eq!(env.get("b"), Some(&Value::Ok(Box::new(Value::Int(1)))));
+}
+
+#[test]
+fn method_calls_chain_left_to_right() {
+    let env = run("let wrap = fn x -> [x] end let nums = 0..10 let r = nums.map(wrap).take(2)");
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::List(vec![ints(&[0]), ints(&[1])]))
+    );
+}
+
+#[test]
+fn user_function_method_applies_to_receiver() {
+    let env = run("let pair = fn x -> [x, x] end let r = 7.pair()");
+    assert_eq!(env.get("r"), Some(&ints(&[7, 7])));
+}
+
+#[test]
+fn extra_arguments_are_applied_in_turn() {
+    let env = run("let second = fn a -> fn b -> [b] end end let r = 1.second(2)");
+    assert_eq!(env.get("r"), Some(&ints(&[2])));
+}
+
+#[test]
+fn unknown_method_is_rejected() {
+    let err = resolve_err("let r = [1].nope()");
+    assert!(matches!(err, ResolveError::Undefined(_)));
+}
+
+#[test]
+fn local_function_wins_over_builtin_with_warning() {
+    let program = parse_program("let take = fn x -> [x] end let r = 5.take()").expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let warnings = resolver.take_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].picked, "function in scope");
+    assert_eq!(warnings[0].other, "builtin");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    assert_eq!(env.get("r"), Some(&ints(&[5])));
+}
+
+#[test]
+fn unambiguous_call_has_no_warning() {
+    let program = parse_program("let r = [1].to_list()").expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    assert!(resolver.take_warnings().is_empty());
+}
+
+#[test]
+fn io_builtin_method_requires_io_block() {
+    let err = resolve_err(r#"let r = "echo".run_text("hi")"#);
+    assert!(matches!(err, ResolveError::IoOutsideBoundary(_)));
+    let env = run(r#"let r = io do "echo".run_text("hi") end"#);
+    assert!(matches!(env.get("r"), Some(Value::Ok(_))));
+}
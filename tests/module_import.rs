@@ -0,0 +1,291 @@
+use std::path::{Path, PathBuf};
+
+use miette::Diagnostic;
+use mictylish::error::{EvalError, ModuleError, ResolveError};
+use mictylish::eval::{eval_program_with_imports, EvalEnv};
+use mictylish::module::ModuleLoader;
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+/// Creates a fresh directory holding `files`, written relative to it.
+fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mictylish-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (path, source) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("mkdir");
+        std::fs::write(path, source).expect("write");
+    }
+    dir
+}
+
+fn run(dir: &Path, source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    let imports = ModuleLoader::new()
+        .link(&program, dir, &mut resolver)
+        .expect("link");
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program_with_imports(&mut env, &program, &imports).expect("eval");
+    env
+}
+
+fn link_err(dir: &Path, source: &str) -> ModuleError {
+    let program = parse_program(source).expect("parse");
+    ModuleLoader::new()
+        .link(&program, dir, &mut Resolver::new())
+        .expect_err("should fail to load")
+}
+
+fn resolve_err(dir: &Path, source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    ModuleLoader::new()
+        .link(&program, dir, &mut resolver)
+        .expect("link");
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+#[test]
+fn qualified_access_reads_exported_value() {
+    let dir = project(
+        "qualified",
+        &[("lib/deploy.myl", r#"let release = "v1"; export release"#)],
+    );
+    let env = run(&dir, r#"import "lib/deploy.myl" as deploy let r = deploy.release"#);
+    assert_eq!(env.get("r"), Some(&Value::String("v1".to_string())));
+}
+
+#[test]
+fn exported_function_can_be_piped_into() {
+    let dir = project(
+        "pipe",
+        &[("util.myl", r#"let wrap = fn x -> [x] end; export wrap"#)],
+    );
+    let env = run(&dir, r#"import "util.myl" as util let r = 1 |> util.wrap"#);
+    assert_eq!(env.get("r"), Some(&Value::List(vec![Value::Int(1)])));
+}
+
+#[test]
+fn unexported_name_is_rejected() {
+    let dir = project(
+        "private",
+        &[("lib.myl", r#"let secret = 1; let open = 2; export open"#)],
+    );
+    let err = resolve_err(&dir, r#"import "lib.myl" as lib let x = lib.secret"#);
+    assert!(matches!(err, ResolveError::NotExported(_)));
+}
+
+#[test]
+fn import_alias_cannot_shadow_local_binding() {
+    let dir = project("shadow", &[("lib.myl", r#"let a = 1; export a"#)]);
+    let err = resolve_err(&dir, r#"let lib = 0 import "lib.myl" as lib"#);
+    assert!(matches!(err, ResolveError::Shadowing(_)));
+}
+
+#[test]
+fn export_of_undefined_name_is_rejected() {
+    let dir = project("undefined-export", &[("lib.myl", r#"export missing"#)]);
+    let err = link_err(&dir, r#"import "lib.myl" as lib"#);
+    assert!(matches!(err, ModuleError::InFile(_)));
+}
+
+#[test]
+fn nested_imports_resolve_relative_to_importing_file() {
+    let dir = project(
+        "nested",
+        &[
+            ("lib/base.myl", r#"let n = 41; export n"#),
+            (
+                "lib/top.myl",
+                r#"import "base.myl" as base; let m = [base.n, 1]; export m"#,
+            ),
+        ],
+    );
+    let env = run(&dir, r#"import "lib/top.myl" as top let r = top.m"#);
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::List(vec![Value::Int(41), Value::Int(1)]))
+    );
+}
+
+#[test]
+fn import_cycle_is_detected() {
+    let dir = project(
+        "cycle",
+        &[
+            ("a.myl", r#"import "b.myl" as b; let x = 1; export x"#),
+            ("b.myl", r#"import "a.myl" as a; let y = 2; export y"#),
+        ],
+    );
+    let err = link_err(&dir, r#"import "a.myl" as a"#);
+    let ModuleError::InFile(outer) = err else {
+        panic!("expected error inside a.myl, got {err:?}");
+    };
+    let rendered = format!("{:?}", outer.errors[0]);
+    assert!(rendered.contains("import cycle"), "{rendered}");
+}
+
+#[test]
+fn missing_module_is_reported() {
+    let dir = project("missing", &[]);
+    let err = link_err(&dir, r#"import "nope.myl" as nope"#);
+    assert!(matches!(err, ModuleError::NotFound(_)));
+}
+
+#[test]
+fn error_inside_module_carries_that_files_source() {
+    let dir = project("in-file", &[("bad.myl", r#"let x = y; export x"#)]);
+    let err = link_err(&dir, r#"import "bad.myl" as bad"#);
+    let ModuleError::InFile(inner) = err else {
+        panic!("expected ModuleFileError, got {err:?}");
+    };
+    assert!(inner.path.ends_with("bad.myl"));
+    assert!(inner.errors[0].source_code().is_some());
+}
+
+#[test]
+fn field_access_reads_record_fields() {
+    let dir = project("field", &[]);
+    let env = run(
+        &dir,
+        r#"let r = io do run_text("sh", "-c", "exit 3") end let code = match r do Err(e) -> e.code _ -> 0 end"#,
+    );
+    assert_eq!(env.get("code"), Some(&Value::Int(3)));
+}
+
+#[test]
+fn module_member_can_be_called_as_method() {
+    let dir = project(
+        "member-call",
+        &[("util.myl", r#"let wrap = fn x -> [x] end; export wrap"#)],
+    );
+    let env = run(&dir, r#"import "util.myl" as util let r = util.wrap(1)"#);
+    assert_eq!(env.get("r"), Some(&Value::List(vec![Value::Int(1)])));
+}
+
+#[test]
+fn module_member_shadowing_builtin_is_reported() {
+    let dir = project(
+        "member-builtin",
+        &[("util.myl", r#"let take = fn x -> [x] end; export take"#)],
+    );
+    let program =
+        parse_program(r#"import "util.myl" as util let r = util.take(1)"#).expect("parse");
+    let mut resolver = Resolver::new();
+    ModuleLoader::new()
+        .link(&program, &dir, &mut resolver)
+        .expect("link");
+    resolver.resolve_program(&program).expect("resolve");
+    let warnings = resolver.take_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].picked, "module member");
+}
+
+#[test]
+fn exported_function_can_call_private_helper() {
+    let dir = project(
+        "closure",
+        &[(
+            "m.myl",
+            r#"let helper = fn x -> [x] end; let wrap = fn x -> x |> helper end; export wrap"#,
+        )],
+    );
+    let env = run(&dir, r#"import "m.myl" as m let r = 1 |> m.wrap"#);
+    assert_eq!(env.get("r"), Some(&Value::List(vec![Value::Int(1)])));
+}
+
+#[test]
+fn runtime_error_in_module_function_points_into_module() {
+    let module = r#"let at = fn i -> [1][i] end; export at"#;
+    let dir = project("closure-error", &[("m.myl", module)]);
+    let program = parse_program(r#"import "m.myl" as m let r = 5 |> m.at"#).expect("parse");
+    let mut resolver = Resolver::new();
+    let imports = ModuleLoader::new()
+        .link(&program, &dir, &mut resolver)
+        .expect("link");
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    let err = eval_program_with_imports(&mut env, &program, &imports).expect_err("out of range");
+    let EvalError::InModule(inner) = &err else {
+        panic!("expected an error in module code, got {err:?}");
+    };
+    assert!(inner.source_code.name().ends_with("m.myl"));
+    let label = err.labels().and_then(|mut labels| labels.next()).expect("label");
+    let source = err.source_code().expect("module source");
+    let snippet = source.read_span(label.inner(), 0, 0).expect("span inside module");
+    let offset = label.offset();
+    assert_eq!(
+        std::str::from_utf8(snippet.data()).unwrap(),
+        &module[offset..offset + label.len()]
+    );
+}
+
+#[test]
+fn copy_of_module_value_is_not_an_alias() {
+    let dir = project(
+        "member-copy",
+        &[("util.myl", r#"let first = fn x -> "member" end; export first"#)],
+    );
+    let env = run(
+        &dir,
+        r#"import "util.myl" as util
+let first = fn x -> "local" end
+let copy = util
+let a = util.first(1)
+let b = copy.first()"#,
+    );
+    assert_eq!(env.get("a"), Some(&Value::String("member".to_string())));
+    assert_eq!(env.get("b"), Some(&Value::String("local".to_string())));
+}
+
+#[test]
+fn module_files_end_statements_at_newlines() {
+    let dir = project(
+        "newline",
+        &[
+            (
+                "ok.myl",
+                "let xs = [\n  1,\n  2\n]\nlet first = xs[0]\nexport xs, first\n",
+            ),
+            ("juxtaposed.myl", "let a = 1 let b = 2\nexport a, b\n"),
+        ],
+    );
+    let env = run(&dir, r#"import "ok.myl" as m let r = [m.first, m.xs]"#);
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::List(vec![
+            Value::Int(1),
+            Value::List(vec![Value::Int(1), Value::Int(2)]),
+        ]))
+    );
+    let err = link_err(&dir, r#"import "juxtaposed.myl" as j"#);
+    let ModuleError::InFile(inner) = err else {
+        panic!("expected ModuleFileError, got {err:?}");
+    };
+    let message = inner.errors[0].to_string();
+    assert!(message.contains("expected `;` or a newline"), "{message}");
+}
+
+#[test]
+fn io_chain_of_imported_function_stops_at_the_module() {
+    let dir = project(
+        "sealed",
+        &[(
+            "lib/deploy.myl",
+            "let push = fn v -> run_text(\"echo\", v) end\nlet release = fn v -> v |> push end\nexport release",
+        )],
+    );
+    let source = r#"import "lib/deploy.myl" as deploy let r = "hi" |> deploy.release"#;
+    let ResolveError::IoOutsideBoundary(err) = resolve_err(&dir, source) else {
+        panic!("expected an io error");
+    };
+    assert_eq!(err.chain, "deploy.release");
+    assert!(err.via.is_empty(), "{:?}", err.via);
+    let start = err.span.offset();
+    assert_eq!(&source[start..start + err.span.len()], "deploy.release");
+}
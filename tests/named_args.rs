@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use mictylish::ast::{Expr, Stmt};
+use mictylish::command::CommandSpec;
+use mictylish::error::{EvalError, ResolveError};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::runtime::run_command;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn resolve_err(source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+fn ok_string(value: Option<&Value>) -> &str {
+    match value {
+        Some(Value::Ok(inner)) => match inner.as_ref() {
+            Value::String(s) => s,
+            other => panic!("expected string, got {other:?}"),
+        },
+        other => panic!("expected Ok, got {other:?}"),
+    }
+}
+
+#[test]
+fn parses_named_arguments_after_positional() {
+    let program = parse_program(r#"run_text("ls", "-l", cwd: "/srv")"#).expect("parse");
+    match &program.stmts[0] {
+        Stmt::Expr(Expr::Call { args, named, .. }) => {
+            assert_eq!(args.len(), 2);
+            assert_eq!(named.len(), 1);
+            assert_eq!(named[0].name, "cwd");
+        }
+        other => panic!("expected Call, got {other:?}"),
+    }
+}
+
+#[test]
+fn positional_after_named_is_parse_error() {
+    let err = parse_program(r#"run_text("ls", cwd: "/", "-l")"#).expect_err("should fail");
+    assert!(err.message.contains("positional argument after named"));
+}
+
+#[test]
+fn run_text_cwd_option() {
+    let env = run(r#"let x = io do run_text("pwd", cwd: "/") end"#);
+    assert_eq!(ok_string(env.get("x")), "/");
+}
+
+#[test]
+fn run_text_stdin_option() {
+    let env = run(r#"let x = io do run_text("cat", stdin: "hello") end"#);
+    assert_eq!(ok_string(env.get("x")), "hello");
+}
+
+#[test]
+fn empty_stdin_is_piped_but_missing_stdin_is_not() {
+    let env = run(
+        r#"let probe = "[ -p /dev/stdin ] && echo piped || echo none"
+let given = io do run_text("sh", "-c", probe, stdin: "") end
+let missing = io do run_text("sh", "-c", probe) end"#,
+    );
+    assert_eq!(ok_string(env.get("given")), "piped");
+    assert_eq!(ok_string(env.get("missing")), "none");
+}
+
+#[test]
+fn run_text_timeout_option_yields_err_value() {
+    let env = run(r#"let x = io do run_text("sleep", "5", timeout: 50) end"#);
+    match env.get("x") {
+        Some(Value::Err(record)) => match record.as_ref() {
+            Value::Record(fields) => assert!(matches!(
+                fields.get("reason"),
+                Some(Value::String(reason)) if reason.contains("timed out")
+            )),
+            other => panic!("expected record, got {other:?}"),
+        },
+        other => panic!("expected Err, got {other:?}"),
+    }
+}
+
+#[test]
+fn named_arguments_pass_through_method_calls() {
+    let env = run(r#"let x = io do "cat".run_text(stdin: "piped") end"#);
+    assert_eq!(ok_string(env.get("x")), "piped");
+}
+
+#[test]
+fn command_env_is_added_to_inherited_environment() {
+    let spec = CommandSpec::new("sh", vec!["-c".to_string(), "echo $GREETING".to_string()])
+        .with_env("GREETING", "hi")
+        .with_timeout(Duration::from_secs(5));
+    let out = run_command(&spec).expect("sh should run");
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "hi");
+}
+
+#[test]
+fn unknown_named_argument_is_rejected_at_resolve_time() {
+    let err = resolve_err(r#"let x = io do run_text("ls", cwdd: "/") end"#);
+    assert!(matches!(err, ResolveError::UnknownNamedArg(_)));
+}
+
+#[test]
+fn duplicate_named_argument_is_rejected() {
+    let err = resolve_err(r#"let x = io do run_text("ls", cwd: "/", cwd: "/tmp") end"#);
+    assert!(matches!(err, ResolveError::DuplicateNamedArg(_)));
+}
+
+#[test]
+fn named_argument_to_builtin_without_options_is_rejected() {
+    let err = resolve_err("let x = take([1], n: 1)");
+    assert!(matches!(err, ResolveError::UnknownNamedArg(_)));
+}
+
+#[test]
+fn builtin_arity_is_checked_at_resolve_time() {
+    let err = resolve_err("let x = take([1])");
+    assert!(matches!(err, ResolveError::BuiltinArity(_)));
+    let err = resolve_err(r#"let x = io do run_text() end"#);
+    assert!(matches!(err, ResolveError::BuiltinArity(_)));
+}
+
+#[test]
+fn named_arguments_to_user_functions_are_rejected() {
+    let err = resolve_err("let f = fn x -> x end let y = 1.f(n: 2)");
+    assert!(matches!(err, ResolveError::UnknownNamedArg(_)));
+}
+
+#[test]
+fn option_of_wrong_type_is_reported() {
+    let err = run_err(r#"let x = io do run_text("ls", cwd: 1) end"#);
+    assert!(matches!(err, EvalError::Type(_)));
+}
@@ -52,6 +52,7 @@ fn parses_mutable_let_with_precise_spans() {
             mutable,
             expr,
             span: stmt_span,
+            ..
         } => {
             assert_eq!(name, "items");
             assert!(*mutable);
@@ -175,6 +176,7 @@ fn parses_fn_expression_in_let() {
                     param_span,
                     body,
                     span: fn_span,
+                    ..
                 } => {
                     assert_eq!(param, "x");
                     assert_eq!(param_span, &span(12, 1));
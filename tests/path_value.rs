@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use mictylish::ast::{Expr, Stmt};
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::typeck::{Type, TypeChecker};
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn path(p: &str) -> Value {
+    Value::Path(PathBuf::from(p))
+}
+
+#[test]
+fn path_literal_parses_to_path_expr() {
+    let program = parse_program(r#"let dir = p"./logs""#).expect("parse");
+    match &program.stmts[0] {
+        Stmt::Let { expr, .. } => assert!(matches!(expr, Expr::Path(p, _) if p == "./logs")),
+        other => panic!("expected let, got {other:?}"),
+    }
+}
+
+#[test]
+fn path_literal_evaluates_to_path_value() {
+    let env = run(r#"let dir = p"./logs" let from_text = path("./logs")"#);
+    assert_eq!(env.get("dir"), Some(&path("./logs")));
+    assert_eq!(env.get("from_text"), Some(&path("./logs")));
+    assert_eq!(path("./logs").to_string(), r#"p"./logs""#);
+}
+
+#[test]
+fn join_and_component_accessors() {
+    let env = run(
+        r#"let log = join(p"/var/log", "app/current.log") let dir = parent(log) let name = file_name(log) let ext = extension(log) let none = extension(p"/var/log")"#,
+    );
+    assert_eq!(env.get("log"), Some(&path("/var/log/app/current.log")));
+    assert_eq!(env.get("dir"), Some(&path("/var/log/app")));
+    assert_eq!(env.get("name"), Some(&Value::String("current.log".into())));
+    assert_eq!(env.get("ext"), Some(&Value::String("log".into())));
+    assert_eq!(env.get("none"), Some(&Value::Null));
+}
+
+#[test]
+fn parent_of_bare_name_is_current_dir() {
+    let env = run(r#"let dir = parent(p"notes.txt") let root = parent(p"/")"#);
+    assert_eq!(env.get("dir"), Some(&path(".")));
+    assert_eq!(env.get("root"), Some(&Value::Null));
+}
+
+#[test]
+fn normalize_is_lexical() {
+    let env = run(r#"let n = normalize(p"/srv/./app/../data/") let r = normalize(p"../a/./b")"#);
+    assert_eq!(env.get("n"), Some(&path("/srv/data")));
+    assert_eq!(env.get("r"), Some(&path("../a/b")));
+}
+
+#[test]
+fn join_rejects_absolute_segment() {
+    let err = run_err(r#"let p = join(p"/srv/app", "/etc/passwd")"#);
+    assert!(matches!(err, EvalError::PathJoin(_)));
+}
+
+#[test]
+fn join_rejects_escaping_segment() {
+    let err = run_err(r#"let p = join(p"/srv/app", "data/../../etc")"#);
+    assert!(matches!(err, EvalError::PathJoin(_)));
+    let env = run(r#"let p = join(p"/srv/app", "data/../cache")"#);
+    assert_eq!(env.get("p"), Some(&path("/srv/app/data/../cache")));
+}
+
+#[test]
+fn strings_are_not_paths() {
+    let err = run_err(r#"let p = join("/srv", "app")"#);
+    assert!(matches!(err, EvalError::Type(_)));
+}
+
+#[test]
+fn path_values_pass_to_commands() {
+    let env = run(
+        r#"let listed = io do run_text("ls", "-d", p"/") end let here = io do run_text("pwd", cwd: p"/") end"#,
+    );
+    assert_eq!(
+        env.get("listed"),
+        Some(&Value::Ok(Box::new(Value::String("/".into()))))
+    );
+    assert_eq!(
+        env.get("here"),
+        Some(&Value::Ok(Box::new(Value::String("/".into()))))
+    );
+}
+
+#[test]
+fn path_builtins_have_path_type() {
+    let program =
+        parse_program(r#"let a = p"/srv" let b = join(a, "x") let c: Path = normalize(b)"#)
+            .expect("parse");
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program).expect("typeck");
+    assert_eq!(checker.type_of("a"), Some(&Type::Path));
+    assert_eq!(checker.type_of("b"), Some(&Type::Path));
+    assert_eq!(checker.type_of("c"), Some(&Type::Path));
+}
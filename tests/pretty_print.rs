@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use mictylish::pretty::{pretty, PrettyOptions};
+use mictylish::value::Value;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn run_text_err() -> Value {
+    Value::Err(Box::new(record(&[
+        ("code", Value::Int(1)),
+        ("program", string("cargo")),
+        (
+            "stderr",
+            string("error: could not compile\n  --> src/main.rs:3:5\n"),
+        ),
+    ])))
+}
+
+#[test]
+fn records_display_their_fields() {
+    let value = record(&[("code", Value::Int(1)), ("stderr", string("boom"))]);
+    assert_eq!(value.to_string(), r#"{code: 1, stderr: "boom"}"#);
+    assert_eq!(record(&[]).to_string(), "{}");
+}
+
+#[test]
+fn non_identifier_keys_are_quoted() {
+    let value = record(&[("content-type", string("json")), ("ok", Value::Bool(true))]);
+    assert_eq!(value.to_string(), r#"{"content-type": "json", ok: true}"#);
+}
+
+#[test]
+fn short_values_stay_on_one_line() {
+    let value = Value::List(vec![Value::Int(1), record(&[("a", Value::Null)])]);
+    assert_eq!(pretty(&value, &PrettyOptions::default()), "[1, {a: null}]");
+}
+
+#[test]
+fn wide_values_are_indented() {
+    let options = PrettyOptions {
+        width: 30,
+        ..PrettyOptions::default()
+    };
+    assert_eq!(
+        pretty(&run_text_err(), &options),
+        r#"Err({
+  code: 1,
+  program: "cargo",
+  stderr: "error: could not compile\n  --> src/main.rs:3:5\n",
+})"#
+    );
+}
+
+#[test]
+fn nested_lists_indent_per_level() {
+    let options = PrettyOptions {
+        width: 12,
+        ..PrettyOptions::default()
+    };
+    let inner = Value::List(vec![Value::Int(100), Value::Int(200), Value::Int(300)]);
+    let value = record(&[("xs", inner)]);
+    assert_eq!(
+        pretty(&value, &options),
+        "{\n  xs: [\n    100,\n    200,\n    300,\n  ],\n}"
+    );
+}
+
+#[test]
+fn long_strings_are_truncated() {
+    let options = PrettyOptions {
+        max_chars: Some(5),
+        ..PrettyOptions::default()
+    };
+    assert_eq!(
+        pretty(&string("abcdefghij"), &options),
+        r#""abcde"…(+5 chars)"#
+    );
+    assert_eq!(pretty(&string("abc"), &options), r#""abc""#);
+}
+
+#[test]
+fn long_lists_and_records_are_truncated() {
+    let options = PrettyOptions {
+        max_items: Some(2),
+        ..PrettyOptions::default()
+    };
+    let list = Value::List((1..=5).map(Value::Int).collect());
+    assert_eq!(pretty(&list, &options), "[1, 2, … 3 more]");
+    let value = record(&[
+        ("a", Value::Int(1)),
+        ("b", Value::Int(2)),
+        ("c", Value::Int(3)),
+    ]);
+    assert_eq!(pretty(&value, &options), "{a: 1, b: 2, … 1 more}");
+}
+
+#[test]
+fn multi_line_strings_can_print_verbatim() {
+    let options = PrettyOptions {
+        verbatim: true,
+        ..PrettyOptions::default()
+    };
+    assert_eq!(
+        pretty(&run_text_err(), &options),
+        r#"Err({
+  code: 1,
+  program: "cargo",
+  stderr: """
+    error: could not compile
+      --> src/main.rs:3:5
+    """,
+})"#
+    );
+}
+
+#[test]
+fn settings_are_parsed_from_repl_commands() {
+    let mut options = PrettyOptions::default();
+    options.set("items", "3").expect("items");
+    options.set("chars", "off").expect("chars");
+    options.set("verbatim", "on").expect("verbatim");
+    assert_eq!(options.max_items, Some(3));
+    assert_eq!(options.max_chars, None);
+    assert!(options.verbatim);
+    assert!(options.set("items", "lots").is_err());
+    assert!(options.set("colour", "on").is_err());
+}
+
+#[test]
+fn whole_floats_keep_their_fraction() {
+    let value = Value::Ok(Box::new(Value::List(vec![
+        Value::Float(1.0),
+        Value::Float(0.5),
+    ])));
+    assert_eq!(value.to_string(), "Ok([1.0, 0.5])");
+}
+
+#[test]
+fn wide_characters_count_as_two_columns() {
+    let options = PrettyOptions {
+        width: 14,
+        ..PrettyOptions::default()
+    };
+    let value = Value::List(vec![string("日本語"), string("ok")]);
+    assert_eq!(pretty(&value, &options), "[\n  \"日本語\",\n  \"ok\",\n]");
+    let value = Value::List(vec![string("abc"), string("ok")]);
+    assert_eq!(pretty(&value, &options), r#"["abc", "ok"]"#);
+}
@@ -0,0 +1,157 @@
+use mictylish::error::{EvalError, ResolveError};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::{RangeValue, Value};
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn ints(values: &[i64]) -> Value {
+    Value::List(values.iter().copied().map(Value::Int).collect())
+}
+
+#[test]
+fn range_evaluates_to_lazy_value() {
+    let env = run("let r = 1..10 let open = 0..");
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::Range(RangeValue {
+            start: 1,
+            end: Some(10)
+        }))
+    );
+    assert_eq!(
+        env.get("open"),
+        Some(&Value::Range(RangeValue {
+            start: 0,
+            end: None
+        }))
+    );
+}
+
+#[test]
+fn spaced_dots_take_an_end() {
+    let env = run("let r = 1 .. 4 let n = 3 let s = n .. n\nlet t = 0 ..\nlet u = 0.. n");
+    let range = |start, end| Some(Value::Range(RangeValue { start, end }));
+    assert_eq!(env.get("r").cloned(), range(1, Some(4)));
+    assert_eq!(env.get("s").cloned(), range(3, Some(3)));
+    assert_eq!(env.get("t").cloned(), range(0, None));
+    assert_eq!(env.get("u").cloned(), range(0, None));
+}
+
+#[test]
+fn range_display() {
+    let env = run("let r = 1..3 let open = 0..");
+    assert_eq!(env.get("r").unwrap().to_string(), "1..3");
+    assert_eq!(env.get("open").unwrap().to_string(), "0..");
+}
+
+#[test]
+fn take_from_infinite_range() {
+    let env = run("let xs = take(0.., 3)");
+    assert_eq!(env.get("xs"), Some(&ints(&[0, 1, 2])));
+}
+
+#[test]
+fn map_over_infinite_range_is_lazy() {
+    let env = run(
+        "let double = fn n -> [n, n] end
+         let pairs = map(0.., double)
+         let first = take(pairs, 2)",
+    );
+    assert!(matches!(env.get("pairs"), Some(Value::Seq(_))));
+    assert_eq!(
+        env.get("first"),
+        Some(&Value::List(vec![ints(&[0, 0]), ints(&[1, 1])]))
+    );
+}
+
+#[test]
+fn map_over_huge_range_only_runs_pulled_items() {
+    let env = run("let wrap = fn n -> ok(n) end let xs = take(map(0..9000000000000, wrap), 1)");
+    assert_eq!(
+        env.get("xs"),
+        Some(&Value::List(vec![Value::Ok(Box::new(Value::Int(0)))]))
+    );
+}
+
+#[test]
+fn map_over_list_is_eager() {
+    let env = run("let wrap = fn n -> ok(n) end let xs = map([1, 2], wrap)");
+    assert_eq!(
+        env.get("xs"),
+        Some(&Value::List(vec![
+            Value::Ok(Box::new(Value::Int(1))),
+            Value::Ok(Box::new(Value::Int(2)))
+        ]))
+    );
+}
+
+#[test]
+fn to_list_materializes_bounded_range() {
+    let env = run("let xs = to_list(2..5)");
+    assert_eq!(env.get("xs"), Some(&ints(&[2, 3, 4])));
+}
+
+#[test]
+fn to_list_of_unbounded_range_is_rejected() {
+    assert!(matches!(
+        run_err("let xs = to_list(0..)"),
+        EvalError::UnboundedRange(_)
+    ));
+}
+
+#[test]
+fn range_pattern_matches_ints() {
+    let env = run(
+        r#"let size = fn n -> match n do 0..10 -> "small" 10.. -> "large" _ -> "negative" end end
+           let a = 3 |> size
+           let b = 10 |> size
+           let c = -1 |> size"#,
+    );
+    assert_eq!(env.get("a"), Some(&Value::String("small".to_string())));
+    assert_eq!(env.get("b"), Some(&Value::String("large".to_string())));
+    assert_eq!(env.get("c"), Some(&Value::String("negative".to_string())));
+}
+
+#[test]
+fn range_value_slices_list() {
+    let env = run("let xs = [1, 2, 3, 4] let r = 1..3 let a = xs[r] let b = xs[2..]");
+    assert_eq!(env.get("a"), Some(&ints(&[2, 3])));
+    assert_eq!(env.get("b"), Some(&ints(&[3, 4])));
+}
+
+#[test]
+fn range_bounds_must_be_ints() {
+    assert!(matches!(
+        run_err(r#"let r = "a"..3"#),
+        EvalError::Type(_)
+    ));
+}
+
+#[test]
+fn map_with_impure_fn_outside_io_is_rejected() {
+    let program =
+        parse_program(r#"let get = fn p -> run_text(p) end let xs = map(["true"], get)"#)
+            .expect("parse");
+    let mut resolver = Resolver::new();
+    match resolver.resolve_program(&program) {
+        Err(ResolveError::IoOutsideBoundary(e)) => assert_eq!(e.chain, "map -> get -> run_text"),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
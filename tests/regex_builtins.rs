@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use mictylish::error::{EvalError, ResolveError};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn resolve_err(source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn strings(items: &[&str]) -> Value {
+    Value::List(items.iter().map(|s| string(s)).collect())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+#[test]
+fn named_captures_become_records() {
+    let env = run(
+        r#"let m = regex_match("took 12ms on web-3", "(?P<ms>\d+)ms on (?P<host>\S+)(?P<extra>!)?")
+let plain = regex_match("took 12ms", "\d+")
+let none = regex_match("idle", "\d+")"#,
+    );
+    assert_eq!(
+        env.get("m"),
+        Some(&record(&[
+            ("extra", Value::Null),
+            ("host", string("web-3")),
+            ("ms", string("12")),
+        ]))
+    );
+    assert_eq!(env.get("plain"), Some(&string("12")));
+    assert_eq!(env.get("none"), Some(&Value::Null));
+}
+
+#[test]
+fn find_all_filters_output_like_grep() {
+    let env = run(r#"let out = "ok  build
+ERROR disk full
+ok  test
+ERROR timeout"
+let errors = out.regex_find_all("(?m)^ERROR (?P<reason>.*)$")
+let hits = regex_find_all(out, "(?m)^ERROR .*$")"#);
+    assert_eq!(
+        env.get("errors"),
+        Some(&Value::List(vec![
+            record(&[("reason", string("disk full"))]),
+            record(&[("reason", string("timeout"))]),
+        ]))
+    );
+    assert_eq!(
+        env.get("hits"),
+        Some(&strings(&["ERROR disk full", "ERROR timeout"]))
+    );
+}
+
+#[test]
+fn replace_and_split() {
+    let env = run(
+        r#"let swapped = regex_replace("a=1, b=2", "(?P<k>\w+)=(?P<v>\d+)", "$v:$k")
+let fields = "a1b22c".regex_split("\d+")"#,
+    );
+    assert_eq!(env.get("swapped"), Some(&string("1:a, 2:b")));
+    assert_eq!(env.get("fields"), Some(&strings(&["a", "b", "c"])));
+}
+
+#[test]
+fn literal_patterns_are_checked_before_running() {
+    let err = resolve_err(r#"let m = regex_match("x", "(unclosed")"#);
+    let ResolveError::InvalidRegex(err) = err else {
+        panic!("expected an invalid regex, got {err:?}");
+    };
+    assert_eq!(err.message, "unclosed group");
+    let err = resolve_err(r#"let parts = "x".regex_split("[z-a]")"#);
+    assert!(matches!(err, ResolveError::InvalidRegex(_)), "{err:?}");
+}
+
+#[test]
+fn computed_patterns_fail_when_evaluated() {
+    let err = run_err(
+        r#"let p = "(?P<n>"
+let m = regex_match("x", p)"#,
+    );
+    assert!(matches!(err, EvalError::InvalidRegex(_)), "{err:?}");
+    let err = run_err(r#"let m = regex_match(1, "x")"#);
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+}
@@ -1,4 +1,4 @@
-use mictylish::error::{EvalError, ResolveError};
+use mictylish::error::ResolveError;
 use mictylish::eval::{eval_program, EvalEnv};
 use mictylish::parser::parse_program;
 use mictylish::resolver::Resolver;
@@ -0,0 +1,141 @@
+use mictylish::ast::Stmt;
+use mictylish::error::ParseError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::{parse_program, parse_program_with, StatementMode};
+use mictylish::resolver::Resolver;
+use mictylish::value::Value;
+
+fn run_lines(source: &str) -> EvalEnv {
+    let program = parse_program_with(source, StatementMode::Newline).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn lines_err(source: &str) -> ParseError {
+    parse_program_with(source, StatementMode::Newline).expect_err("should be rejected")
+}
+
+#[test]
+fn semicolons_separate_statements_in_either_mode() {
+    let program = parse_program("let a = 1; let b = 2;; a").expect("parse");
+    assert_eq!(program.stmts.len(), 3);
+    assert!(matches!(program.stmts[2], Stmt::Expr(_)));
+    let env = run_lines("let a = 1; let b = [a, a]");
+    assert_eq!(
+        env.get("b"),
+        Some(&Value::List(vec![Value::Int(1), Value::Int(1)]))
+    );
+}
+
+#[test]
+fn newline_ends_statements() {
+    let env = run_lines(
+        "let wrap = fn x -> [x] end
+let n = 2
+let r = n |> wrap
+",
+    );
+    assert_eq!(env.get("r"), Some(&Value::List(vec![Value::Int(2)])));
+}
+
+#[test]
+fn statements_on_one_line_need_a_semicolon() {
+    let source = "let x = 1 let y = 2";
+    assert_eq!(parse_program(source).expect("parse").stmts.len(), 2);
+    let err = lines_err(source);
+    assert!(
+        err.to_string().contains("expected `;` or a newline"),
+        "{err}"
+    );
+    assert_eq!(err.span.offset(), source.find("let y").unwrap());
+}
+
+#[test]
+fn stray_expression_is_reported_at_its_start() {
+    let source = "let x = 1 2";
+    let err = lines_err(source);
+    assert!(err.to_string().contains("before integer literal"), "{err}");
+    assert_eq!(err.span.offset(), 10);
+}
+
+#[test]
+fn trailing_pipe_continues_onto_next_line() {
+    let env = run_lines(
+        "let wrap = fn x -> [x] end
+let r = 1 |>
+  wrap",
+    );
+    assert_eq!(env.get("r"), Some(&Value::List(vec![Value::Int(1)])));
+}
+
+#[test]
+fn leading_pipe_does_not_continue() {
+    let source = "let wrap = fn x -> [x] end
+let r = 1
+  |> wrap";
+    let err = lines_err(source);
+    assert!(err.to_string().contains("expected expression"), "{err}");
+    assert_eq!(err.span.offset(), source.find("|>").unwrap());
+    assert!(parse_program(source).is_ok());
+}
+
+#[test]
+fn open_delimiters_continue_across_lines() {
+    let env = run_lines(
+        "let xs = [
+  1
+  + 2,
+  3
+]
+let n = take(
+  xs,
+  2
+)",
+    );
+    assert_eq!(
+        env.get("n"),
+        Some(&Value::List(vec![Value::Int(3), Value::Int(3)]))
+    );
+}
+
+#[test]
+fn trailing_operator_other_than_pipe_is_rejected() {
+    let source = "let n = 1 +
+  2";
+    let err = lines_err(source);
+    assert!(err.to_string().contains("`+` cannot continue"), "{err}");
+    assert_eq!(err.span.offset(), source.find('2').unwrap());
+}
+
+#[test]
+fn block_items_are_separated_by_newlines() {
+    let env = run_lines(
+        "let mut last = 0
+for x in [1, 2, 3] do
+  set last = x
+  x
+end",
+    );
+    assert_eq!(env.get("last"), Some(&Value::Int(3)));
+    let err = lines_err("let mut last = 0\nfor x in [1] do set last = x x end");
+    assert!(
+        err.to_string().contains("expected `;` or a newline"),
+        "{err}"
+    );
+}
+
+#[test]
+fn multi_line_match_keeps_negative_patterns() {
+    let env = run_lines(
+        "let n = -1
+let r = match n do
+  1 -> 10
+  -1 -> 20
+  _ -> 0
+end",
+    );
+    assert_eq!(env.get("r"), Some(&Value::Int(20)));
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::pretty::{pretty, tabular, PrettyOptions};
+use mictylish::table::Table;
+use mictylish::value::Value;
+use miette::Diagnostic;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn files() -> Value {
+    Value::List(vec![
+        record(&[
+            ("name", string("Cargo.toml")),
+            ("size", Value::ByteSize(512)),
+        ]),
+        record(&[
+            ("name", string("README.md")),
+            ("size", Value::ByteSize(4096)),
+        ]),
+    ])
+}
+
+/// Evaluates `source` with `files` bound to [`files`]; the resolver is skipped
+/// because it cannot see the pre-bound name.
+fn eval_with_files(source: &str) -> Result<EvalEnv, EvalError> {
+    let program = parse_program(source).expect("parse");
+    let mut env = EvalEnv::new();
+    env.insert("files".to_string(), files());
+    eval_program(&mut env, &program).map(|_| env)
+}
+
+fn table_of(source: &str) -> Table {
+    match eval_with_files(source).expect("eval").get("t") {
+        Some(Value::Table(table)) => table.clone(),
+        other => panic!("expected table, got {other:?}"),
+    }
+}
+
+#[test]
+fn record_lists_render_as_aligned_tables() {
+    assert_eq!(
+        pretty(&files(), &PrettyOptions::default()),
+        "name        size
+──────────  ────
+Cargo.toml  512B
+README.md   4KiB"
+    );
+}
+
+#[test]
+fn mixed_lists_are_not_tables() {
+    let mixed = Value::List(vec![
+        record(&[("a", Value::Int(1))]),
+        record(&[("b", Value::Int(2))]),
+    ]);
+    let options = PrettyOptions::default();
+    assert!(tabular(&mixed, &options).is_none());
+    assert_eq!(pretty(&mixed, &options), "[{a: 1}, {b: 2}]");
+    assert!(tabular(&Value::List(vec![]), &options).is_none());
+}
+
+#[test]
+fn tables_can_be_turned_off() {
+    let options = PrettyOptions {
+        tables: false,
+        ..PrettyOptions::default()
+    };
+    assert!(pretty(&files(), &options).starts_with("[{name: \"Cargo.toml\""));
+}
+
+#[test]
+fn numbers_are_right_aligned() {
+    let rows = Value::List(vec![
+        record(&[("n", Value::Int(7)), ("tag", string("a"))]),
+        record(&[("n", Value::Int(1234)), ("tag", string("b"))]),
+    ]);
+    assert_eq!(
+        pretty(&rows, &PrettyOptions::default()),
+        "   n  tag
+────  ───
+   7  a
+1234  b"
+    );
+}
+
+#[test]
+fn wide_cells_are_truncated_by_display_width() {
+    let table = Table::new(vec![BTreeMap::from([(
+        "msg".to_string(),
+        string("ビルドに失敗しました"),
+    )])])
+    .with_max_width(9);
+    assert_eq!(table.render(None, false), "msg\n─────────\nビルドに…");
+}
+
+#[test]
+fn control_characters_stay_on_one_line() {
+    let table = Table::new(vec![BTreeMap::from([(
+        "stderr".to_string(),
+        string("a\nb"),
+    )])]);
+    assert_eq!(table.render(None, false).lines().last(), Some("a\\nb"));
+}
+
+#[test]
+fn color_styles_header_and_rule() {
+    let options = PrettyOptions {
+        color: true,
+        ..PrettyOptions::default()
+    };
+    let text = pretty(&files(), &options);
+    assert!(
+        text.starts_with("\x1b[1mname        size\x1b[0m\n\x1b[2m"),
+        "{text:?}"
+    );
+}
+
+#[test]
+fn row_limit_follows_item_limit() {
+    let options = PrettyOptions {
+        max_items: Some(1),
+        ..PrettyOptions::default()
+    };
+    assert!(pretty(&files(), &options).ends_with("Cargo.toml  512B\n… 1 more rows"));
+}
+
+#[test]
+fn table_builtin_selects_columns_and_width() {
+    let table = table_of(r#"let t = table(files, columns: ["size", "name"], width: 6)"#);
+    assert_eq!(table.columns, vec!["size", "name"]);
+    assert_eq!(
+        table.render(None, false),
+        "size  name
+────  ──────
+512B  Cargo…
+4KiB  READM…"
+    );
+}
+
+#[test]
+fn table_builtin_forces_layout_for_mixed_records() {
+    let program = parse_program("let t = table(rows)").expect("parse");
+    let mut env = EvalEnv::new();
+    env.insert(
+        "rows".to_string(),
+        Value::List(vec![
+            record(&[("a", Value::Int(1))]),
+            record(&[("b", Value::Int(2))]),
+        ]),
+    );
+    eval_program(&mut env, &program).expect("eval");
+    let t = env.get("t").expect("t");
+    assert_eq!(t.to_string(), "<table 2 columns, 2 rows>");
+    assert_eq!(
+        pretty(t, &PrettyOptions::default()),
+        "a  b
+─  ─
+1
+   2"
+    );
+}
+
+#[test]
+fn table_builtin_rejects_non_records() {
+    let err = eval_with_files("let t = table([1, 2])").expect_err("should fail");
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+    let err = eval_with_files(r#"let t = table(files, width: "6")"#).expect_err("should fail");
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+}
+
+#[test]
+fn table_builtin_checks_option_values() {
+    let err = eval_with_files("let t = table(files, width: 0)").expect_err("should fail");
+    assert!(matches!(err, EvalError::TableOption(_)), "{err:?}");
+    assert_eq!(err.to_string(), "`width` must be at least 1, found 0");
+    let err = eval_with_files(r#"let t = table(files, columns: ["size", "nope"])"#)
+        .expect_err("should fail");
+    assert_eq!(err.to_string(), "no column named 'nope'");
+    let help = err.help().expect("help").to_string();
+    assert_eq!(help, "the rows have 'name', 'size'");
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::template::Template;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn ok(fields: &[(&str, Value)]) -> Value {
+    Value::Ok(Box::new(record(fields)))
+}
+
+#[test]
+fn passwd_lines_become_records() {
+    let env = run(
+        r#"let users = parse("{user}:{_}:{uid:int}:{gid:int}:{gecos}:{home}:{shell}", "root:x:0:0:root:/root:/bin/bash
+daemon:x:1:1::/usr/sbin:/usr/sbin/nologin
+")"#,
+    );
+    assert_eq!(
+        env.get("users"),
+        Some(&Value::List(vec![
+            ok(&[
+                ("gecos", string("root")),
+                ("gid", Value::Int(0)),
+                ("home", string("/root")),
+                ("shell", string("/bin/bash")),
+                ("uid", Value::Int(0)),
+                ("user", string("root")),
+            ]),
+            ok(&[
+                ("gecos", string("")),
+                ("gid", Value::Int(1)),
+                ("home", string("/usr/sbin")),
+                ("shell", string("/usr/sbin/nologin")),
+                ("uid", Value::Int(1)),
+                ("user", string("daemon")),
+            ]),
+        ]))
+    );
+}
+
+#[test]
+fn non_matching_lines_are_err_items() {
+    let env = run(r#"let rows = parse("{name}={size:int}", "a=1
+
+b=lots
+c")"#);
+    assert_eq!(
+        env.get("rows"),
+        Some(&Value::List(vec![
+            ok(&[("name", string("a")), ("size", Value::Int(1))]),
+            Value::Err(Box::new(record(&[
+                ("column", Value::Int(3)),
+                ("line", Value::Int(3)),
+                ("message", string("`lots` is not an int for `{size}`")),
+                ("text", string("b=lots")),
+            ]))),
+            Value::Err(Box::new(record(&[
+                ("column", Value::Int(2)),
+                ("line", Value::Int(4)),
+                ("message", string("expected `=` after `{name}`")),
+                ("text", string("c")),
+            ]))),
+        ]))
+    );
+}
+
+#[test]
+fn spaces_match_aligned_columns() {
+    let template = Template::parse("{name} {size:int} {unit}").expect("template");
+    assert_eq!(
+        template.match_line("my file     12   kB  "),
+        Ok(record(&[
+            ("name", string("my file")),
+            ("size", Value::Int(12)),
+            ("unit", string("kB")),
+        ]))
+    );
+    let template = Template::parse("{{{key}}} -> {ratio:float} {ok:bool}").expect("template");
+    assert_eq!(
+        template.match_line("{x} -> 0.5 true"),
+        Ok(record(&[
+            ("key", string("x")),
+            ("ok", Value::Bool(true)),
+            ("ratio", Value::Float(0.5)),
+        ]))
+    );
+    let miss = template
+        .match_line("{x} => 1 true")
+        .expect_err("wrong arrow");
+    assert_eq!((miss.message.as_str(), miss.column), ("expected `->`", 5));
+}
+
+#[test]
+fn trailing_template_space_matches_the_line_end() {
+    let template = Template::parse("{user}: {uid:int} ").expect("template");
+    let expected = Ok(record(&[("uid", Value::Int(7)), ("user", string("root"))]));
+    assert_eq!(template.match_line("root: 7"), expected);
+    assert_eq!(template.match_line("root: 7   "), expected);
+}
+
+#[test]
+fn unconvertible_field_is_reported_over_a_longer_untyped_match() {
+    let template = Template::parse("{user}:{uid:int}:{_}").expect("template");
+    let miss = template.match_line("bob:abc:y").expect_err("abc is no uid");
+    assert_eq!(
+        (miss.message.as_str(), miss.column),
+        ("`abc` is not an int for `{uid}`", 5)
+    );
+}
+
+#[test]
+fn parse_accepts_a_list_of_lines() {
+    let env = run(r#"let out = "pid 12
+pid 7"
+let pids = parse("pid {n:int}", out.lines())"#);
+    assert_eq!(
+        env.get("pids"),
+        Some(&Value::List(vec![
+            ok(&[("n", Value::Int(12))]),
+            ok(&[("n", Value::Int(7))]),
+        ]))
+    );
+}
+
+#[test]
+fn bad_templates_are_reported() {
+    for template in ["{a}{b}", "{a:number}", "{a", "a}", "{a} {a}", "{}"] {
+        let err = run_err(&format!(r#"let rows = parse("{template}", "x")"#));
+        assert!(matches!(err, EvalError::Template(_)), "{template}: {err:?}");
+    }
+    assert_eq!(
+        Template::parse("{a}{b}"),
+        Err("`{a}` and `{b}` need literal text between them".to_string())
+    );
+    assert!(Template::parse("{_}:{_}:{id}").is_ok());
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use mictylish::error::EvalError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::text;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn strings(items: &[&str]) -> Value {
+    Value::List(items.iter().map(|s| string(s)).collect())
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+#[test]
+fn lines_drop_the_final_break() {
+    let env = run("let out = lines(\"a\nb\n\nc\n\")");
+    assert_eq!(env.get("out"), Some(&strings(&["a", "b", "", "c"])));
+    assert_eq!(text::lines("x\r\ny"), vec!["x", "y"]);
+    assert!(text::lines("").is_empty());
+}
+
+#[test]
+fn method_calls_break_up_output() {
+    let env = run(r#"let text = "a,b,,c"
+let parts = text.split(",")
+let ws = "  one two
+three ".words()
+let rows = "x
+y".lines()"#);
+    assert_eq!(env.get("parts"), Some(&strings(&["a", "b", "", "c"])));
+    assert_eq!(env.get("ws"), Some(&strings(&["one", "two", "three"])));
+    assert_eq!(env.get("rows"), Some(&strings(&["x", "y"])));
+}
+
+#[test]
+fn split_rejects_an_empty_separator() {
+    let err = run_err(r#"let parts = split("abc", "")"#);
+    assert!(matches!(err, EvalError::EmptySeparator(_)), "{err:?}");
+    assert_eq!(err.to_string(), "`split` separator is empty");
+    let err = run_err(r#"let parts = split("abc", 1)"#);
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+    let err = run_err("let parts = lines(3)");
+    assert!(matches!(err, EvalError::Type(_)), "{err:?}");
+}
+
+#[test]
+fn parse_kv_reads_env_and_dotenv() {
+    let doc = text::parse_kv(
+        "# settings\nexport HOME=/home/me\nPATH=/bin:/usr/bin\nGREETING=\"hello world\"\nEMPTY=\nURL=a=b\nPATH='/opt/bin'\n",
+    )
+    .expect("parse");
+    assert_eq!(
+        doc,
+        record(&[
+            ("EMPTY", string("")),
+            ("GREETING", string("hello world")),
+            ("HOME", string("/home/me")),
+            ("PATH", string("/opt/bin")),
+            ("URL", string("a=b")),
+        ])
+    );
+}
+
+#[test]
+fn parse_kv_continues_multi_line_values() {
+    let doc = text::parse_kv("KEY=first\nsecond\nOTHER=1\n").expect("parse");
+    assert_eq!(
+        doc,
+        record(&[("KEY", string("first\nsecond")), ("OTHER", string("1"))])
+    );
+    let err = text::parse_kv("\n  stray\n").expect_err("no key yet");
+    assert_eq!((err.line, err.column), (2, 3));
+    let err = text::parse_kv("BAD KEY=1").expect_err("space in key");
+    assert_eq!(err.message, "invalid key `BAD KEY`");
+}
+
+#[test]
+fn parse_logfmt_makes_one_record_per_line() {
+    let env = run(
+        r#"let logs = match parse_logfmt("level=info msg=disk_full retry
+level=warn at=12:00:01
+
+") do Ok(rows) -> rows Err(e) -> [] end"#,
+    );
+    assert_eq!(
+        env.get("logs"),
+        Some(&Value::List(vec![
+            record(&[
+                ("level", string("info")),
+                ("msg", string("disk_full")),
+                ("retry", Value::Bool(true)),
+            ]),
+            record(&[("at", string("12:00:01")), ("level", string("warn"))]),
+        ]))
+    );
+}
+
+#[test]
+fn parse_logfmt_escapes_and_errors() {
+    let rows = text::parse_logfmt(r#"msg="say \"hi\"\n" empty="#).expect("parse");
+    assert_eq!(
+        rows,
+        Value::List(vec![record(&[
+            ("empty", string("")),
+            ("msg", string("say \"hi\"\n")),
+        ])])
+    );
+    let err = text::parse_logfmt("a=1\nmsg=\"open").expect_err("unterminated");
+    assert_eq!(err.message, "unterminated quoted value");
+    assert_eq!((err.line, err.column), (2, 5));
+    let err = text::parse_logfmt("=1").expect_err("no key");
+    assert_eq!(err.message, "expected a key before `=`");
+}
+
+#[test]
+fn parse_kv_errors_come_back_as_err() {
+    let env = run(r#"let r = parse_kv("oops")"#);
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::Err(Box::new(record(&[
+            ("column", Value::Int(1)),
+            ("line", Value::Int(1)),
+            ("message", string("expected `KEY=VALUE`, found `oops`")),
+        ]))))
+    );
+}
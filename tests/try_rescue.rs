@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use mictylish::ast::{Expr, Stmt};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::typeck::{Type, TypeChecker};
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn field<'a>(record: &'a Value, name: &str) -> &'a Value {
+    match record {
+        Value::Record(fields) => fields.get(name).expect("field"),
+        other => panic!("expected record, got {other:?}"),
+    }
+}
+
+#[test]
+fn parses_try_with_rescue() {
+    let program = parse_program("try do x rescue e -> e end").expect("parse");
+    match &program.stmts[0] {
+        Stmt::Expr(Expr::Try {
+            rescue: Some(rescue),
+            ..
+        }) => assert_eq!(rescue.name, "e"),
+        other => panic!("expected Try, got {other:?}"),
+    }
+}
+
+#[test]
+fn successful_body_is_returned_unchanged_with_rescue() {
+    let env = run("let x = try do [1][0] rescue e -> 0 end");
+    assert_eq!(env.get("x"), Some(&Value::Int(1)));
+}
+
+#[test]
+fn try_without_rescue_wraps_result() {
+    let env = run("let a = try do [1][0] end let b = try do [1][5] end");
+    assert_eq!(env.get("a"), Some(&Value::Ok(Box::new(Value::Int(1)))));
+    match env.get("b") {
+        Some(Value::Err(record)) => {
+            assert_eq!(
+                field(record, "kind"),
+                &Value::String("index_out_of_range".to_string())
+            );
+        }
+        other => panic!("expected Err record, got {other:?}"),
+    }
+}
+
+#[test]
+fn rescue_binds_error_record() {
+    let source = "let k = try do match 3 do 1 -> 1 end rescue e -> e end";
+    let env = run(source);
+    let record = env.get("k").expect("k");
+    assert_eq!(
+        field(record, "kind"),
+        &Value::String("match_exhausted".to_string())
+    );
+    let Value::String(message) = field(record, "message") else {
+        panic!("message should be a string");
+    };
+    assert!(message.contains("match"), "{message}");
+    let mut span = BTreeMap::new();
+    span.insert("offset".to_string(), Value::Int(15));
+    span.insert("len".to_string(), Value::Int(21));
+    assert_eq!(field(record, "span"), &Value::Record(span));
+}
+
+#[test]
+fn rescue_handler_can_read_fields() {
+    let env = run(r#"let r = io do try do run_text(1) rescue e -> [e.kind, e.message] end end"#);
+    assert_eq!(
+        env.get("r"),
+        Some(&Value::List(vec![
+            Value::String("type".to_string()),
+            Value::String("expected string or path, found int".to_string()),
+        ]))
+    );
+}
+
+#[test]
+fn missing_run_text_program_is_an_arity_error() {
+    let program = parse_program("let r = io do try do run_text() end end").expect("parse");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    let Some(Value::Err(record)) = env.get("r") else {
+        panic!("expected a caught error, got {:?}", env.get("r"));
+    };
+    assert_eq!(
+        field(record, "kind"),
+        &Value::String("builtin_arity".to_string())
+    );
+}
+
+#[test]
+fn script_continues_after_rescued_error() {
+    let env = run("let a = try do [][0] rescue e -> 0 end let b = [a, 1]");
+    assert_eq!(
+        env.get("b"),
+        Some(&Value::List(vec![Value::Int(0), Value::Int(1)]))
+    );
+}
+
+#[test]
+fn rescue_variable_is_scoped_to_handler() {
+    let program = parse_program("let a = try do 1 rescue e -> e end let b = e").expect("parse");
+    let mut resolver = Resolver::new();
+    assert!(resolver.resolve_program(&program).is_err());
+}
+
+#[test]
+fn try_types() {
+    let program =
+        parse_program("let a = try do 1 end let b = try do 1 rescue e -> 2 end").expect("parse");
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program).expect("typeck");
+    assert_eq!(
+        checker.type_of("a"),
+        Some(&Type::Result(Box::new(Type::Int), Box::new(Type::Record)))
+    );
+    assert_eq!(checker.type_of("b"), Some(&Type::Int));
+}
@@ -0,0 +1,237 @@
+use mictylish::ast::Stmt;
+use mictylish::error::TypeError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::parser::parse_program;
+use mictylish::repl::check_line;
+use mictylish::resolver::Resolver;
+use mictylish::span::span;
+use mictylish::typeck::{Type, TypeChecker};
+use mictylish::value::Value;
+
+fn check(source: &str) -> Result<TypeChecker, TypeError> {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program)?;
+    Ok(checker)
+}
+
+fn check_err(source: &str) -> TypeError {
+    check(source).expect_err("should be a type error")
+}
+
+#[test]
+fn parses_let_annotation() {
+    let program = parse_program("let n: List[Int] = [1]").expect("parse");
+    let Stmt::Let { ty: Some(ty), .. } = &program.stmts[0] else {
+        panic!("expected annotated let");
+    };
+    assert_eq!(ty.name, "List");
+    assert_eq!(ty.args[0].name, "Int");
+    assert_eq!(ty.span, span(7, 9));
+}
+
+#[test]
+fn annotated_let_accepts_matching_value() {
+    let checker = check(r#"let n: Int = 1 let s: String = "a""#).expect("well typed");
+    assert_eq!(checker.type_of("n"), Some(&Type::Int));
+}
+
+#[test]
+fn annotated_let_mismatch_labels_both_spans() {
+    let source = r#"let n: Int = "one""#;
+    match check_err(source) {
+        TypeError::Mismatch(e) => {
+            assert_eq!(e.expected, "Int");
+            assert_eq!(e.found, "String");
+            assert_eq!(e.annotation, span(7, 3));
+            assert_eq!(e.span, span(13, 5));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn list_item_mismatch_points_at_item() {
+    let source = r#"let xs: List[Int] = [1, "two"]"#;
+    match check_err(source) {
+        TypeError::Mismatch(e) => assert_eq!(e.span, span(24, 5)),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn match_arm_mismatch_points_at_arm_body() {
+    let err = check_err(r#"let s: String = match 1 do 1 -> "one" _ -> 2 end"#);
+    assert!(matches!(err, TypeError::Mismatch(e) if e.found == "Int"));
+}
+
+#[test]
+fn run_text_result_type_is_known() {
+    let checker = check(r#"let r: Result[String, Record] = io do run_text("echo") end"#)
+        .expect("run_text returns Result[String, Record]");
+    assert_eq!(
+        checker.type_of("r"),
+        Some(&Type::Result(Box::new(Type::String), Box::new(Type::Record)))
+    );
+    let err = check_err(r#"let r: Result[Int, Record] = io do run_text("echo") end"#);
+    assert!(matches!(err, TypeError::Mismatch(_)));
+}
+
+#[test]
+fn ok_pattern_binds_inner_type() {
+    let err = check_err(
+        r#"let r = io do run_text("echo") end
+           let n: Int = match r do Ok(out) -> out Err(_) -> 0 end"#,
+    );
+    assert!(matches!(err, TypeError::Mismatch(e) if e.found == "String"));
+}
+
+#[test]
+fn fn_param_annotation_checks_pipe_input() {
+    let source = r#"let f = fn (p: String) -> p end let y = 1 |> f"#;
+    match check_err(source) {
+        TypeError::Mismatch(e) => {
+            assert_eq!(e.expected, "String");
+            assert_eq!(e.annotation, span(15, 6));
+            assert_eq!(e.span, span(40, 1));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn fn_return_annotation_checks_body() {
+    let err = check_err(r#"let f = fn (p: String): Int -> p end"#);
+    assert!(matches!(err, TypeError::Mismatch(e) if e.expected == "Int" && e.found == "String"));
+}
+
+#[test]
+fn fn_type_is_inferred_from_annotations() {
+    let checker = check(r#"let f = fn (p: String): Bool -> p == "x" end"#).expect("ok");
+    assert_eq!(
+        checker.type_of("f"),
+        Some(&Type::Fn(Box::new(Type::String), Box::new(Type::Bool)))
+    );
+}
+
+#[test]
+fn set_respects_declared_type() {
+    let err = check_err(r#"let mut n: Int = 0 set n = "x""#);
+    assert!(matches!(err, TypeError::Mismatch(_)));
+    check("let mut n: Int = 0 set n = 1").expect("same type");
+}
+
+#[test]
+fn unknown_type_name_is_rejected() {
+    let err = check_err("let n: Integer = 1");
+    assert!(matches!(err, TypeError::UnknownType(e) if e.name == "Integer"));
+}
+
+#[test]
+fn wrong_type_arity_is_rejected() {
+    let err = check_err("let n: List = [1]");
+    assert!(matches!(err, TypeError::Arity(e) if e.expected == 1 && e.found == 0));
+}
+
+#[test]
+fn unannotated_programs_still_check() {
+    for source in [
+        "let mut x = 1 set x = \"hello\"",
+        "let id = fn x -> x end let y = 1 |> id let z = \"s\" |> id",
+        "let x = match 1 do 1 -> \"one\" _ -> 2 end",
+        "let r = with Ok(v) <- ok(1) do v else \"none\" end",
+        "let xs = [1, \"a\", [2]]",
+    ] {
+        check(source).unwrap_or_else(|e| panic!("{source}: {e:?}"));
+    }
+}
+
+#[test]
+fn annotations_do_not_change_evaluation() {
+    let program = parse_program(r#"let f = fn (p: String): String -> p end let y: String = "v" |> f"#)
+        .expect("parse");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    assert_eq!(env.get("y"), Some(&Value::String("v".to_string())));
+}
+
+#[test]
+fn function_parameters_are_contravariant() {
+    let takes_any = Type::Fn(Box::new(Type::Unknown), Box::new(Type::Int));
+    let takes_list =
+        |item: Type| Type::Fn(Box::new(Type::List(Box::new(item))), Box::new(Type::Int));
+    assert!(takes_list(Type::Int).accepts(&takes_any));
+    assert!(takes_list(Type::Int).accepts(&takes_list(Type::Unknown)));
+    assert!(!takes_list(Type::Int).accepts(&takes_list(Type::String)));
+    let err = check_err(r#"let f = fn (p: String): Int -> 1 end let g: Fn[Int, Int] = f"#);
+    assert!(matches!(err, TypeError::Mismatch(_)), "{err:?}");
+}
+
+#[test]
+fn rejected_repl_line_defines_nothing() {
+    let mut resolver = Resolver::new();
+    let mut checker = TypeChecker::new();
+    let line = |source: &str| parse_program(source).expect("parse");
+    check_line(&mut resolver, &mut checker, &line(r#"let x: Int = "s""#), |_| {})
+        .expect_err("type error");
+    check_line(&mut resolver, &mut checker, &line("let x = 1"), |_| {})
+        .expect("`x` was never defined");
+    assert_eq!(checker.type_of("x"), Some(&Type::Int));
+}
+
+#[test]
+fn def_annotates_parameters_and_return_type() {
+    let checker = check(
+        r#"def first(xs: List[Int]) -> Result[String, Record] do io do run_text("echo") end end"#,
+    )
+    .expect("well typed");
+    assert_eq!(
+        checker.type_of("first"),
+        Some(&Type::Fn(
+            Box::new(Type::List(Box::new(Type::Int))),
+            Box::new(Type::Result(Box::new(Type::String), Box::new(Type::Record)))
+        ))
+    );
+    let source = r#"def label(n: Int) -> String do n end"#;
+    match check_err(source) {
+        TypeError::Mismatch(e) => {
+            assert_eq!((e.expected.as_str(), e.found.as_str()), ("String", "Int"));
+            assert_eq!(e.annotation, span(21, 6));
+            assert_eq!(e.span, span(31, 1));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    let err = check_err(r#"def shout(s: String) do s end let y = 1 |> shout"#);
+    assert!(matches!(err, TypeError::Mismatch(e) if e.expected == "String"));
+}
+
+#[test]
+fn def_with_several_parameters_is_curried() {
+    let source = "def add(a: Int, b: Int) -> Int do a + b end let n = 2.add(3)";
+    let checker = check(source).expect("well typed");
+    let int = || Box::new(Type::Int);
+    assert_eq!(
+        checker.type_of("add"),
+        Some(&Type::Fn(int(), Box::new(Type::Fn(int(), int()))))
+    );
+    let program = parse_program(source).expect("parse");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    assert_eq!(env.get("n"), Some(&Value::Int(5)));
+    assert!(parse_program("def f() do 1 end").is_err());
+}
+
+#[test]
+fn negation_needs_an_int() {
+    let checker = check("let a = 1 let b = -a").expect("well typed");
+    assert_eq!(checker.type_of("b"), Some(&Type::Int));
+    match check_err(r#"let s = "x" let n = -s"#) {
+        TypeError::Negate(e) => {
+            assert_eq!(e.found, "String");
+            assert_eq!(e.span, span(20, 2));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
@@ -0,0 +1,68 @@
+use mictylish::error::ResolveError;
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::lexer::lex;
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::token::TokenKind;
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn resolve_err(source: &str) -> ResolveError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver
+        .resolve_program(&program)
+        .expect_err("should be rejected")
+}
+
+#[test]
+fn japanese_identifiers_bind_and_resolve() {
+    let env = run("let 件数 = 3 let 結果 = [件数, 件数]");
+    assert_eq!(env.get("件数"), Some(&Value::Int(3)));
+    assert_eq!(
+        env.get("結果"),
+        Some(&Value::List(vec![Value::Int(3), Value::Int(3)]))
+    );
+}
+
+#[test]
+fn identifiers_may_mix_scripts_and_digits() {
+    let env = run("let café_2 = 1 let ログ_v1 = café_2");
+    assert_eq!(env.get("ログ_v1"), Some(&Value::Int(1)));
+}
+
+#[test]
+fn decomposed_spelling_is_normalized_to_nfc() {
+    let tokens = lex("cafe\u{301}").expect("lex");
+    assert_eq!(tokens[0].kind, TokenKind::Ident("caf\u{e9}".to_string()));
+    assert_eq!(tokens[0].span.len(), "cafe\u{301}".len());
+}
+
+#[test]
+fn visually_identical_names_cannot_shadow() {
+    let err = resolve_err("let caf\u{e9} = 1 let cafe\u{301} = 2");
+    assert!(matches!(err, ResolveError::Shadowing(_)));
+}
+
+#[test]
+fn non_identifier_symbols_are_rejected() {
+    let err = parse_program("let a→b = 1").expect_err("should fail");
+    assert!(
+        err.to_string().contains("unexpected character '→'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn full_width_space_separates_tokens() {
+    let env = run("let 値\u{3000}= 1");
+    assert_eq!(env.get("値"), Some(&Value::Int(1)));
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use mictylish::error::{EvalError, TypeError};
+use mictylish::eval::{eval_program, EvalEnv};
+use mictylish::lexer::lex;
+use mictylish::parser::parse_program;
+use mictylish::resolver::Resolver;
+use mictylish::token::TokenKind;
+use mictylish::typeck::{Type, TypeChecker};
+use mictylish::value::Value;
+
+fn run(source: &str) -> EvalEnv {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect("eval");
+    env
+}
+
+fn run_err(source: &str) -> EvalError {
+    let program = parse_program(source).expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_program(&program).expect("resolve");
+    let mut env = EvalEnv::new();
+    eval_program(&mut env, &program).expect_err("should fail")
+}
+
+fn type_err(source: &str) -> TypeError {
+    let program = parse_program(source).expect("parse");
+    TypeChecker::new()
+        .check_program(&program)
+        .expect_err("should be rejected")
+}
+
+fn kinds(source: &str) -> Vec<TokenKind> {
+    lex(source)
+        .expect("lex")
+        .into_iter()
+        .map(|token| token.kind)
+        .collect()
+}
+
+#[test]
+fn lexes_duration_and_size_literals() {
+    assert_eq!(
+        kinds("30s 5m 250ms 2h 1d 10MiB 2GB 512B"),
+        vec![
+            TokenKind::Duration(Duration::from_secs(30)),
+            TokenKind::Duration(Duration::from_secs(300)),
+            TokenKind::Duration(Duration::from_millis(250)),
+            TokenKind::Duration(Duration::from_secs(7200)),
+            TokenKind::Duration(Duration::from_secs(86_400)),
+            TokenKind::Size(10 * 1024 * 1024),
+            TokenKind::Size(2_000_000_000),
+            TokenKind::Size(512),
+            TokenKind::Eof,
+        ]
+    );
+}
+
+#[test]
+fn unknown_unit_is_a_parse_error() {
+    let err = parse_program("let t = 30sec").expect_err("should fail");
+    assert!(err.to_string().contains("unknown unit 'sec'"), "{err}");
+}
+
+#[test]
+fn only_ascii_letters_start_a_unit() {
+    assert_eq!(
+        kinds("3件"),
+        vec![
+            TokenKind::Int(3),
+            TokenKind::Ident("件".to_string()),
+            TokenKind::Eof,
+        ]
+    );
+}
+
+#[test]
+fn oversized_literal_is_a_parse_error() {
+    let err = parse_program("let s = 99999999999TiB").expect_err("should fail");
+    assert!(err.to_string().contains("out of range"), "{err}");
+}
+
+#[test]
+fn literals_evaluate_to_unit_values() {
+    let env = run("let t = 250ms let s = 10MiB");
+    assert_eq!(
+        env.get("t"),
+        Some(&Value::Duration(Duration::from_millis(250)))
+    );
+    assert_eq!(env.get("s"), Some(&Value::ByteSize(10 * 1024 * 1024)));
+}
+
+#[test]
+fn arithmetic_keeps_units() {
+    let env = run("let t = 1h + 30m - 15s let s = 1GiB - 512MiB let n = 40 + 2");
+    assert_eq!(
+        env.get("t"),
+        Some(&Value::Duration(Duration::from_secs(3600 + 1800 - 15)))
+    );
+    assert_eq!(env.get("s"), Some(&Value::ByteSize(512 * 1024 * 1024)));
+    assert_eq!(env.get("n"), Some(&Value::Int(42)));
+}
+
+#[test]
+fn comparisons_across_units() {
+    let env = run("let a = 90s > 1m let b = 1000KB <= 1MB let c = 1MiB < 1MB let d = 3 >= 4");
+    assert_eq!(env.get("a"), Some(&Value::Bool(true)));
+    assert_eq!(env.get("b"), Some(&Value::Bool(true)));
+    assert_eq!(env.get("c"), Some(&Value::Bool(false)));
+    assert_eq!(env.get("d"), Some(&Value::Bool(false)));
+}
+
+#[test]
+fn display_is_human_readable() {
+    let show = |source: &str| run(source).get("x").expect("x").to_string();
+    assert_eq!(show("let x = 90m"), "90m");
+    assert_eq!(show("let x = 2h + 60m"), "3h");
+    assert_eq!(show("let x = 1s + 250ms"), "1250ms");
+    assert_eq!(show("let x = 5m - 5m"), "0s");
+    assert_eq!(show("let x = 2048KiB"), "2MiB");
+    assert_eq!(show("let x = 2GB"), "2GB");
+    assert_eq!(show("let x = 1KiB + 1B"), "1025B");
+}
+
+#[test]
+fn displayed_durations_lex_back_to_the_same_value() {
+    for ms in [0, 250, 1_250, 60_000, 5_400_000, 90_061_001] {
+        let d = Duration::from_millis(ms);
+        let text = Value::Duration(d).to_string();
+        assert_eq!(
+            kinds(&text),
+            vec![TokenKind::Duration(d), TokenKind::Eof],
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn negative_duration_is_an_error() {
+    let err = run_err("let t = 1s - 2s");
+    assert!(matches!(err, EvalError::Arithmetic(_)));
+}
+
+#[test]
+fn mixing_units_is_rejected() {
+    let err = type_err("let t = 30s + 1MiB");
+    assert!(matches!(err, TypeError::Operands(_)));
+    let err = type_err("let t = 30s < 30");
+    assert!(matches!(err, TypeError::Operands(_)));
+    let err = run_err("let t = 30s + 1MiB");
+    assert!(matches!(err, EvalError::Operands(_)));
+}
+
+#[test]
+fn unit_types_are_inferred() {
+    let program = parse_program("let t: Duration = 1m + 5s let s = 4KB").expect("parse");
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program).expect("typeck");
+    assert_eq!(checker.type_of("t"), Some(&Type::Duration));
+    assert_eq!(checker.type_of("s"), Some(&Type::ByteSize));
+}
+
+#[test]
+fn negative_pattern_on_its_own_line_is_not_subtraction() {
+    let env = run(
+        "let n = -1 let r = match n do 1 -> 10\n-1 -> 20 _ -> 0 end let d = 5 - 2 == 3 let e = 5-2",
+    );
+    assert_eq!(env.get("r"), Some(&Value::Int(20)));
+    assert_eq!(env.get("d"), Some(&Value::Bool(true)));
+    assert_eq!(env.get("e"), Some(&Value::Int(3)));
+}
+
+#[test]
+fn timeout_accepts_duration() {
+    let env = run(r#"let r = io do run_text("sleep", "5", timeout: 100ms) end"#);
+    assert!(matches!(env.get("r"), Some(Value::Err(_))));
+}